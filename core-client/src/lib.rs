@@ -2,6 +2,7 @@
 //!
 //! By default this crate does not implement any transports,
 //! use corresponding features (`tls`, `http` or `ws`) to opt-in for them.
+//! Binary message encodings are enabled with `msgpack` and `cbor` features.
 //!
 //! See documentation of [`jsonrpc-client-transports`](https://docs.rs/jsonrpc-client-transports) for more details.
 
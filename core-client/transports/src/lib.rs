@@ -8,7 +8,7 @@ use jsonrpc_core::futures::{
 	task::{Context, Poll},
 	Future, Stream, StreamExt,
 };
-use jsonrpc_core::{Error, Params};
+use jsonrpc_core::{Error, Params, RpcErrorCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value;
@@ -57,6 +57,20 @@ impl From<Error> for RpcError {
 	}
 }
 
+impl RpcError {
+	/// Converts an error returned by the server into a typed application error.
+	///
+	/// Returns the original error if it was not returned by the server or doesn't map to `E`.
+	pub fn into_typed<E: RpcErrorCode>(self) -> Result<E, RpcError> {
+		if let RpcError::JsonRpcError(ref error) = self {
+			if let Some(error) = E::from_error(error) {
+				return Ok(error);
+			}
+		}
+		Err(self)
+	}
+}
+
 /// A result returned by the client.
 pub type RpcResult<T> = Result<T, RpcError>;
 
@@ -6,21 +6,54 @@ use super::RequestBuilder;
 use crate::{RpcChannel, RpcError, RpcMessage, RpcResult};
 use futures::{future, Future, FutureExt, StreamExt, TryFutureExt};
 use hyper::{http, Client, Request, Uri};
+use jsonrpc_core::encoding::{Encoding, Json};
+use jsonrpc_core::Version;
+use std::sync::Arc;
 
 /// Create a HTTP Client
 pub async fn connect<TClient>(url: &str) -> RpcResult<TClient>
+where
+	TClient: From<RpcChannel>,
+{
+	connect_with_encoding(url, Json).await
+}
+
+/// Create a HTTP Client talking given JSON-RPC protocol version.
+///
+/// Use `Version::V1` to talk to legacy JSON-RPC 1.0 servers.
+pub async fn connect_with_version<TClient>(url: &str, version: Version) -> RpcResult<TClient>
 where
 	TClient: From<RpcChannel>,
 {
 	let url: Uri = url.parse().map_err(|e| RpcError::Other(Box::new(e)))?;
 
-	let (client_api, client_worker) = do_connect(url).await;
+	let (client_api, client_worker) = do_connect(url, Arc::new(Json), RequestBuilder::with_version(version)).await;
 	tokio::spawn(client_worker);
 
 	Ok(TClient::from(client_api))
 }
 
-async fn do_connect(url: Uri) -> (RpcChannel, impl Future<Output = ()>) {
+/// Create a HTTP Client exchanging messages in given encoding.
+///
+/// The server has to accept the encoding's content type (see `ServerBuilder::encoding`).
+pub async fn connect_with_encoding<TClient, E>(url: &str, encoding: E) -> RpcResult<TClient>
+where
+	TClient: From<RpcChannel>,
+	E: Encoding,
+{
+	let url: Uri = url.parse().map_err(|e| RpcError::Other(Box::new(e)))?;
+
+	let (client_api, client_worker) = do_connect(url, Arc::new(encoding), RequestBuilder::new()).await;
+	tokio::spawn(client_worker);
+
+	Ok(TClient::from(client_api))
+}
+
+async fn do_connect(
+	url: Uri,
+	encoding: Arc<dyn Encoding>,
+	mut request_builder: RequestBuilder,
+) -> (RpcChannel, impl Future<Output = ()>) {
 	let max_parallel = 8;
 
 	#[cfg(feature = "tls")]
@@ -30,9 +63,6 @@ async fn do_connect(url: Uri) -> (RpcChannel, impl Future<Output = ()>) {
 
 	#[cfg(not(feature = "tls"))]
 	let client = Client::new();
-	// Keep track of internal request IDs when building subsequent requests
-	let mut request_builder = RequestBuilder::new();
-
 	let (sender, receiver) = futures::channel::mpsc::unbounded();
 
 	let fut = receiver
@@ -50,24 +80,23 @@ async fn do_connect(url: Uri) -> (RpcChannel, impl Future<Output = ()>) {
 			})
 		})
 		.map(move |(request, sender)| {
+			let content_type = http::header::HeaderValue::from_static(encoding.content_type());
+			let request = encoding
+				.encode_json(&request)
+				.expect("Requests are serialized to valid JSON; qed");
 			let request = Request::post(&url)
-				.header(
-					http::header::CONTENT_TYPE,
-					http::header::HeaderValue::from_static("application/json"),
-				)
-				.header(
-					http::header::ACCEPT,
-					http::header::HeaderValue::from_static("application/json"),
-				)
+				.header(http::header::CONTENT_TYPE, content_type.clone())
+				.header(http::header::ACCEPT, content_type)
 				.body(request.into())
 				.expect("Uri and request headers are valid; qed");
 
+			let encoding = encoding.clone();
 			client
 				.request(request)
-				.then(|response| async move { (response, sender) })
+				.then(|response| async move { (response, sender, encoding) })
 		})
 		.buffer_unordered(max_parallel)
-		.for_each(|(response, sender)| async {
+		.for_each(|(response, sender, encoding)| async move {
 			let result = match response {
 				Ok(ref res) if !res.status().is_success() => {
 					log::trace!("http result status {}", res.status());
@@ -87,7 +116,13 @@ async fn do_connect(url: Uri) -> (RpcChannel, impl Future<Output = ()>) {
 			if let Some(sender) = sender {
 				let response = result
 					.and_then(|response| {
-						let response_str = String::from_utf8_lossy(response.as_ref()).into_owned();
+						let response_str = if encoding.is_text() {
+							String::from_utf8_lossy(response.as_ref()).into_owned()
+						} else {
+							encoding
+								.decode_json(response.as_ref())
+								.map_err(|e| RpcError::ParseError(encoding.content_type().into(), Box::new(e)))?
+						};
 						super::parse_response(&response_str)
 					})
 					.and_then(|r| r.1);
@@ -194,6 +229,40 @@ mod tests {
 		tokio::runtime::Runtime::new().unwrap().block_on(run).unwrap();
 	}
 
+	#[test]
+	fn should_talk_to_jsonrpc_1_servers() {
+		crate::logger::init_log();
+
+		// given
+		let mut strict = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V1Strict);
+		strict.extend_with(io());
+		let server = ServerBuilder::new(strict)
+			.start_http(&"127.0.0.1:0".parse().unwrap())
+			.unwrap();
+		let uri = format!("http://{}", server.address());
+
+		// when
+		let run = async {
+			let client: TestClient = connect_with_version(&uri, Version::V1).await?;
+			let result = client.hello("http").await?;
+			let error = client.fail().await.unwrap_err();
+
+			// then
+			assert_eq!("hello http", result);
+			assert_matches!(
+				error,
+				RpcError::JsonRpcError(Error {
+					code: ErrorCode::ServerError(-34),
+					..
+				})
+			);
+			Ok(()) as RpcResult<_>
+		};
+
+		tokio::runtime::Runtime::new().unwrap().block_on(run).unwrap();
+		server.close();
+	}
+
 	#[test]
 	fn should_send_notification() {
 		crate::logger::init_log();
@@ -3,21 +3,54 @@
 
 use crate::transports::duplex::duplex;
 use crate::{RpcChannel, RpcError};
-use futures::{SinkExt, StreamExt, TryStreamExt};
-use jsonrpc_server_utils::codecs::StreamCodec;
+use futures::{future, SinkExt, StreamExt, TryStreamExt};
+use jsonrpc_core::encoding::{Encoding, Json};
+use jsonrpc_server_utils::codecs::{EncodedCodec, Separator};
 use jsonrpc_server_utils::tokio;
 use jsonrpc_server_utils::tokio_util::codec::Decoder as _;
 use parity_tokio_ipc::Endpoint;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Connect to a JSON-RPC IPC server.
 pub async fn connect<P: AsRef<Path>, Client: From<RpcChannel>>(path: P) -> Result<Client, RpcError> {
+	connect_with_encoding(path, Json).await
+}
+
+/// Connect to an IPC server exchanging messages in given encoding.
+///
+/// Messages of binary encodings are prefixed with their length instead of being delimited.
+pub async fn connect_with_encoding<P, Client, E>(path: P, encoding: E) -> Result<Client, RpcError>
+where
+	P: AsRef<Path>,
+	Client: From<RpcChannel>,
+	E: Encoding,
+{
 	let connection = Endpoint::connect(path)
 		.await
 		.map_err(|e| RpcError::Other(Box::new(e)))?;
-	let (sink, stream) = StreamCodec::stream_incoming().framed(connection).split();
-	let sink = sink.sink_map_err(|e| RpcError::Other(Box::new(e)));
-	let stream = stream.map_err(|e| log::error!("IPC stream error: {}", e));
+	let encoding: Arc<dyn Encoding> = Arc::new(encoding);
+	let codec = EncodedCodec::new(&*encoding, Separator::Empty, Default::default());
+	let (sink, stream) = codec.framed(connection).split();
+	let sink_encoding = encoding.clone();
+	let sink = sink
+		.sink_map_err(|e| RpcError::Other(Box::new(e)))
+		.with(move |request: String| {
+			future::ready(
+				sink_encoding
+					.encode_json(&request)
+					.map_err(|e| RpcError::Other(Box::new(e))),
+			)
+		});
+	let stream = stream
+		.map_err(|e| log::error!("IPC stream error: {}", e))
+		.and_then(move |response| {
+			future::ready(
+				encoding
+					.decode_json(&response)
+					.map_err(|e| log::error!("IPC stream error: {}", e)),
+			)
+		});
 
 	let (client, sender) = duplex(
 		Box::pin(sink),
@@ -111,4 +144,32 @@ mod tests {
 
 		tokio::runtime::Runtime::new().unwrap().block_on(client_fut);
 	}
+
+	#[test]
+	fn should_call_with_binary_encoding() {
+		use jsonrpc_core::encoding::MessagePack;
+
+		let sock_path = dummy_endpoint();
+
+		let mut io = IoHandler::new();
+		io.add_method("greeting", |_params| async { Ok(Value::String("Hello!".into())) });
+		let builder = ServerBuilder::new(io).encoding(MessagePack);
+		let _server = builder.start(&sock_path).expect("Couldn't open socket");
+
+		let client_fut = async move {
+			let client: RawClient = connect_with_encoding(sock_path, MessagePack).await.unwrap();
+			let fut = client.call_method("greeting", Params::None);
+
+			match fut.await {
+				Ok(val) => assert_eq!(&val, "Hello!"),
+				Err(err) => panic!("IPC RPC call failed: {}", err),
+			}
+		};
+		// a response the client can't match would leave the call pending forever
+		let client_fut = async move { tokio::time::timeout(std::time::Duration::from_secs(5), client_fut).await };
+		tokio::runtime::Runtime::new()
+			.unwrap()
+			.block_on(client_fut)
+			.expect("IPC RPC call timed out");
+	}
 }
@@ -21,12 +21,21 @@ pub use duplex::duplex;
 /// Creates JSON-RPC requests
 pub struct RequestBuilder {
 	id: u64,
+	version: Version,
 }
 
 impl RequestBuilder {
 	/// Create a new RequestBuilder
 	pub fn new() -> Self {
-		RequestBuilder { id: 0 }
+		Self::with_version(Version::V2)
+	}
+
+	/// Create a new RequestBuilder producing requests of given protocol version.
+	///
+	/// With `Version::V1` requests are sent in JSON-RPC 1.0 format, notifications
+	/// as calls with `null` id.
+	pub fn with_version(version: Version) -> Self {
+		RequestBuilder { id: 0, version }
 	}
 
 	fn next_id(&mut self) -> Id {
@@ -39,7 +48,7 @@ impl RequestBuilder {
 	fn single_request(&mut self, method: String, params: Params) -> (Id, String) {
 		let id = self.next_id();
 		let request = jsonrpc_core::Request::Single(Call::MethodCall(MethodCall {
-			jsonrpc: Some(Version::V2),
+			jsonrpc: Some(self.version),
 			method,
 			params,
 			id: id.clone(),
@@ -64,7 +73,7 @@ impl RequestBuilder {
 
 	fn notification(&mut self, msg: &NotifyMessage) -> String {
 		let request = jsonrpc_core::Request::Single(Call::Notification(Notification {
-			jsonrpc: Some(Version::V2),
+			jsonrpc: Some(self.version),
 			method: msg.method.clone(),
 			params: msg.params.clone(),
 		}));
@@ -168,6 +177,19 @@ mod tests {
 	use super::*;
 	use jsonrpc_core::{Failure, Notification, Output, Params, Success, Value, Version};
 
+	#[test]
+	fn should_build_jsonrpc_1_requests() {
+		let mut builder = RequestBuilder::with_version(Version::V1);
+
+		let (id, request) = builder.single_request("getblockcount".into(), Params::None);
+		assert_eq!(id, Id::Num(0));
+		assert_eq!(request, r#"{"method":"getblockcount","params":[],"id":0}"#);
+
+		let (result_id, result, _, _) = parse_response(r#"{"result":10,"error":null,"id":0}"#).unwrap();
+		assert_eq!(result_id, Id::Num(0));
+		assert_eq!(result.unwrap(), Value::from(10));
+	}
+
 	#[test]
 	fn notification_deserialize() {
 		let dsr = r#"{"jsonrpc":"2.0","method":"hello","params":[10]}"#;
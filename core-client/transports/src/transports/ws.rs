@@ -2,9 +2,11 @@
 use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use crate::{RpcChannel, RpcError};
+use jsonrpc_core::encoding::{Encoding, Json};
 use websocket::{ClientBuilder, OwnedMessage};
 
 /// Connect to a JSON-RPC websocket server.
@@ -17,7 +19,7 @@ where
 	T: From<RpcChannel>,
 {
 	let client_builder = ClientBuilder::new(url).map_err(|e| RpcError::Other(Box::new(e)))?;
-	Ok(do_connect(client_builder))
+	Ok(do_connect(client_builder, Arc::new(Json)))
 }
 
 /// Connect to a JSON-RPC websocket server.
@@ -28,10 +30,25 @@ where
 	T: From<RpcChannel>,
 {
 	let client_builder = ClientBuilder::from_url(url);
-	do_connect(client_builder)
+	do_connect(client_builder, Arc::new(Json))
 }
 
-fn do_connect<T>(client_builder: ClientBuilder) -> impl Future<Output = Result<T, RpcError>>
+/// Connect to a JSON-RPC websocket server exchanging messages in given encoding.
+///
+/// Messages of binary encodings are sent in binary frames.
+pub fn connect_with_encoding<T, E>(url: &url::Url, encoding: E) -> impl Future<Output = Result<T, RpcError>>
+where
+	T: From<RpcChannel>,
+	E: Encoding,
+{
+	let client_builder = ClientBuilder::from_url(url);
+	do_connect(client_builder, Arc::new(encoding))
+}
+
+fn do_connect<T>(
+	client_builder: ClientBuilder,
+	encoding: Arc<dyn Encoding>,
+) -> impl Future<Output = Result<T, RpcError>>
 where
 	T: From<RpcChannel>,
 {
@@ -43,12 +60,12 @@ where
 		.async_connect(None)
 		.compat()
 		.map_err(|error| RpcError::Other(Box::new(error)))
-		.map_ok(|(client, _)| {
+		.map_ok(move |(client, _)| {
 			let (sink, stream) = client.split();
 
 			let sink = sink.sink_compat().sink_map_err(|e| RpcError::Other(Box::new(e)));
 			let stream = stream.compat().map_err(|e| RpcError::Other(Box::new(e)));
-			let (sink, stream) = WebsocketClient::new(sink, stream).split();
+			let (sink, stream) = WebsocketClient::new(sink, stream, encoding).split();
 			let (sink, stream) = (
 				Box::pin(sink),
 				Box::pin(
@@ -69,6 +86,7 @@ struct WebsocketClient<TSink, TStream> {
 	sink: TSink,
 	stream: TStream,
 	queue: VecDeque<OwnedMessage>,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<TSink, TStream, TError> WebsocketClient<TSink, TStream>
@@ -77,11 +95,12 @@ where
 	TStream: futures::Stream<Item = Result<OwnedMessage, TError>> + Unpin,
 	TError: std::error::Error + Send + 'static,
 {
-	pub fn new(sink: TSink, stream: TStream) -> Self {
+	pub fn new(sink: TSink, stream: TStream, encoding: Arc<dyn Encoding>) -> Self {
 		Self {
 			sink,
 			stream,
 			queue: VecDeque::new(),
+			encoding,
 		}
 	}
 
@@ -122,7 +141,15 @@ where
 	type Error = RpcError;
 
 	fn start_send(mut self: Pin<&mut Self>, request: String) -> Result<(), Self::Error> {
-		let request = OwnedMessage::Text(request);
+		let request = if self.encoding.is_text() {
+			OwnedMessage::Text(request)
+		} else {
+			let request = self
+				.encoding
+				.encode_json(&request)
+				.map_err(|e| RpcError::Other(Box::new(e)))?;
+			OwnedMessage::Binary(request)
+		};
 
 		if self.queue.is_empty() {
 			let this = Pin::into_inner(self);
@@ -183,6 +210,14 @@ where
 			match Pin::new(&mut this.stream).poll_next(cx) {
 				Poll::Ready(Some(Ok(message))) => match message {
 					OwnedMessage::Text(data) => return Poll::Ready(Some(Ok(data))),
+					OwnedMessage::Binary(data) if !this.encoding.is_text() => {
+						let encoding = &this.encoding;
+						return Poll::Ready(Some(
+							encoding
+								.decode_json(&data)
+								.map_err(|e| RpcError::ParseError(encoding.content_type().into(), Box::new(e))),
+						));
+					}
 					OwnedMessage::Binary(data) => log::info!("server sent binary data {:?}", data),
 					OwnedMessage::Ping(p) => this.queue.push_front(OwnedMessage::Pong(p)),
 					OwnedMessage::Pong(_) => {}
@@ -0,0 +1,131 @@
+//! Per-method access control.
+//!
+//! Methods are tagged with required permissions (see `MetaIoHandler::require_permission`)
+//! and an `AccessPolicy` decides whether the caller described by request metadata has them.
+//!
+//! ```rust
+//! use jsonrpc_core::{MetaIoHandler, Metadata, Value};
+//!
+//! #[derive(Clone, Default)]
+//! struct Meta {
+//!     // e.g. extracted from a header by `MetaExtractor` of the server
+//!     admin: bool,
+//! }
+//! impl Metadata for Meta {}
+//!
+//! let mut io = MetaIoHandler::<Meta>::default();
+//! io.add_sync_method("admin_stop", |_| Ok(Value::Bool(true)));
+//! io.require_permission("admin_*", "admin");
+//! io.set_access_policy(|meta: &Meta, permission: &str| permission == "admin" && meta.admin);
+//!
+//! let request = r#"{"jsonrpc": "2.0", "method": "admin_stop", "id": 1}"#;
+//! let denied = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Access denied"},"id":1}"#;
+//! assert_eq!(io.handle_request_sync(request, Meta { admin: false }), Some(denied.into()));
+//! assert_eq!(
+//!     io.handle_request_sync(request, Meta { admin: true }),
+//!     Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.into())
+//! );
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::calls::Metadata;
+
+/// Decides whether the caller of a request has a permission.
+pub trait AccessPolicy<T: Metadata>: Send + Sync + 'static {
+	/// Returns `true` if the caller described by `meta` has given permission.
+	fn is_allowed(&self, meta: &T, permission: &str) -> bool;
+}
+
+impl<T: Metadata, F> AccessPolicy<T> for F
+where
+	F: Fn(&T, &str) -> bool + Send + Sync + 'static,
+{
+	fn is_allowed(&self, meta: &T, permission: &str) -> bool {
+		self(meta, permission)
+	}
+}
+
+/// Permissions required by methods and the policy checking them.
+pub(crate) struct AccessControl<T> {
+	// method name or prefix (if it ends with `*`) with required permission
+	rules: Vec<(String, String)>,
+	policy: Option<Arc<dyn AccessPolicy<T>>>,
+}
+
+impl<T: Metadata> AccessControl<T> {
+	/// Requires permission for methods matching given pattern.
+	pub(crate) fn require(&mut self, pattern: &str, permission: &str) {
+		let rule = (pattern.to_owned(), permission.to_owned());
+		if !self.rules.contains(&rule) {
+			self.rules.push(rule);
+		}
+	}
+
+	/// Sets the policy checking permissions.
+	pub(crate) fn set_policy(&mut self, policy: Arc<dyn AccessPolicy<T>>) {
+		self.policy = Some(policy);
+	}
+
+	/// Adds rules of another access control, and its policy if there is none yet.
+	pub(crate) fn extend(&mut self, other: AccessControl<T>) {
+		for (pattern, permission) in &other.rules {
+			self.require(pattern, permission);
+		}
+		if self.policy.is_none() {
+			self.policy = other.policy;
+		}
+	}
+
+	/// Returns `true` if all permissions required by any of given names are granted.
+	///
+	/// Methods which require a permission are denied if there is no policy.
+	pub(crate) fn is_allowed(&self, names: &[&str], meta: &T) -> bool {
+		let mut required = self
+			.rules
+			.iter()
+			.filter(|(pattern, _)| names.iter().any(|name| matches(pattern, name)))
+			.map(|(_, permission)| permission);
+
+		match self.policy {
+			Some(ref policy) => required.all(|permission| policy.is_allowed(meta, permission)),
+			None => required.next().is_none(),
+		}
+	}
+}
+
+/// Returns `true` if `name` is the method name given by `pattern`, or starts with the prefix given as `prefix*`.
+pub(crate) fn matches(pattern: &str, name: &str) -> bool {
+	match pattern.strip_suffix('*') {
+		Some(prefix) => name.starts_with(prefix),
+		None => pattern == name,
+	}
+}
+
+impl<T> Default for AccessControl<T> {
+	fn default() -> Self {
+		AccessControl {
+			rules: Vec::new(),
+			policy: None,
+		}
+	}
+}
+
+impl<T> Clone for AccessControl<T> {
+	fn clone(&self) -> Self {
+		AccessControl {
+			rules: self.rules.clone(),
+			policy: self.policy.clone(),
+		}
+	}
+}
+
+impl<T> fmt::Debug for AccessControl<T> {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("AccessControl")
+			.field("rules", &self.rules)
+			.field("policy", &self.policy.is_some())
+			.finish()
+	}
+}
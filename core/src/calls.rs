@@ -1,15 +1,57 @@
+use crate::cancellation::CancellationToken;
+use crate::discover::MethodDescription;
+use crate::types::raw::RawParams;
 use crate::types::{Error, Params, Value};
 use crate::BoxFuture;
 use std::fmt;
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Metadata trait
-pub trait Metadata: Clone + Send + 'static {}
+pub trait Metadata: Clone + Send + 'static {
+	/// Token cancelled when the call should be aborted.
+	///
+	/// Transports provide a per-session token (cancelled once the connection is closed),
+	/// `MetaIoHandler` replaces it with a per-call one before invoking the method.
+	fn cancellation_token(&self) -> Option<CancellationToken> {
+		None
+	}
+
+	/// Replaces the cancellation token.
+	fn set_cancellation_token(&mut self, _token: CancellationToken) {}
+}
 impl Metadata for () {}
-impl<T: Metadata> Metadata for Option<T> {}
-impl<T: Metadata> Metadata for Box<T> {}
+impl<T: Metadata> Metadata for Option<T> {
+	fn cancellation_token(&self) -> Option<CancellationToken> {
+		self.as_ref().and_then(Metadata::cancellation_token)
+	}
+
+	fn set_cancellation_token(&mut self, token: CancellationToken) {
+		if let Some(meta) = self.as_mut() {
+			meta.set_cancellation_token(token)
+		}
+	}
+}
+impl<T: Metadata> Metadata for Box<T> {
+	fn cancellation_token(&self) -> Option<CancellationToken> {
+		(**self).cancellation_token()
+	}
+
+	fn set_cancellation_token(&mut self, token: CancellationToken) {
+		(**self).set_cancellation_token(token)
+	}
+}
 impl<T: Sync + Send + 'static> Metadata for Arc<T> {}
+impl Metadata for CancellationToken {
+	fn cancellation_token(&self) -> Option<CancellationToken> {
+		Some(self.clone())
+	}
+
+	fn set_cancellation_token(&mut self, token: CancellationToken) {
+		*self = token;
+	}
+}
 
 /// A future-conversion trait.
 pub trait WrapFuture<T, E> {
@@ -47,6 +89,26 @@ pub trait RpcMethodSimple: Send + Sync + 'static {
 pub trait RpcMethod<T: Metadata>: Send + Sync + 'static {
 	/// Call method
 	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>>;
+
+	/// Call method with unparsed params.
+	///
+	/// By default params are converted to `Params`, override to deserialize them directly.
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<crate::Result<Value>> {
+		match params.into_params() {
+			Ok(params) => self.call(params, meta),
+			Err(err) => Box::pin(async { Err(err) }),
+		}
+	}
+
+	/// Description of the method used for service discovery.
+	fn description(&self) -> Option<&MethodDescription> {
+		None
+	}
+
+	/// Maximal time the method is allowed to run.
+	fn timeout(&self) -> Option<Duration> {
+		None
+	}
 }
 
 /// Notification
@@ -59,6 +121,36 @@ pub trait RpcNotificationSimple: Send + Sync + 'static {
 pub trait RpcNotification<T: Metadata>: Send + Sync + 'static {
 	/// Execute notification
 	fn execute(&self, params: Params, meta: T);
+
+	/// Execute notification with unparsed params.
+	///
+	/// By default params are converted to `Params`, override to deserialize them directly.
+	fn execute_raw(&self, params: RawParams, meta: T) {
+		if let Ok(params) = params.into_params() {
+			self.execute(params, meta)
+		}
+	}
+
+	/// Description of the notification used for service discovery.
+	fn description(&self) -> Option<&MethodDescription> {
+		None
+	}
+}
+
+/// Handler of calls to methods which are not registered explicitly.
+pub trait RpcFallback<T: Metadata>: Send + Sync + 'static {
+	/// Call method with given name.
+	fn call(&self, method: String, params: Params, meta: T) -> BoxFuture<crate::Result<Value>>;
+
+	/// Handle notification with given name.
+	///
+	/// By default the notification is handled like a call and the result is discarded.
+	fn notify(&self, method: String, params: Params, meta: T) -> BoxFuture<()> {
+		let result = self.call(method, params, meta);
+		Box::pin(async move {
+			let _ = result.await;
+		})
+	}
 }
 
 /// Possible Remote Procedures with Metadata
@@ -124,6 +216,17 @@ where
 	}
 }
 
+impl<F: Send + Sync + 'static, X: Send + 'static, T> RpcFallback<T> for F
+where
+	T: Metadata,
+	F: Fn(String, Params, T) -> X,
+	X: Future<Output = Result<Value, Error>>,
+{
+	fn call(&self, method: String, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		Box::pin(self(method, params, meta))
+	}
+}
+
 impl<F: Send + Sync + 'static, T> RpcNotification<T> for F
 where
 	T: Metadata,
@@ -0,0 +1,368 @@
+//! Cooperative cancellation of in-flight calls.
+//!
+//! Transports create a `CancellationToken` per session and cancel it when the
+//! connection goes away. If the metadata exposes that token (see `Metadata::cancellation_token`)
+//! `MetaIoHandler` derives a child token for every method call, so the call is
+//! aborted either when the session ends or when the client sends a
+//! `$/cancelRequest` notification with the id of the call.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::task::{Context, Poll};
+
+use futures_util::future::{self, Either};
+use futures_util::task::AtomicWaker;
+
+use crate::streaming::{self, ResultStream};
+use crate::types::{Error, Id, Value};
+use crate::BoxFuture;
+
+/// Name of the built-in notification cancelling an in-flight call.
+///
+/// Expects params in the form of `{"id": <id of the call>}`.
+pub const CANCEL_REQUEST_METHOD: &str = "$/cancelRequest";
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct State {
+	cancelled: bool,
+	next_hook: usize,
+	hooks: Vec<(usize, Hook)>,
+}
+
+#[derive(Default)]
+struct Inner {
+	state: Mutex<State>,
+	parent: Option<(Weak<Inner>, usize)>,
+}
+
+impl Inner {
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn add_hook(&self, hook: Hook) -> Option<usize> {
+		let mut state = self.lock();
+		if state.cancelled {
+			drop(state);
+			hook();
+			return None;
+		}
+
+		let id = state.next_hook;
+		state.next_hook += 1;
+		state.hooks.push((id, hook));
+		Some(id)
+	}
+
+	fn remove_hook(&self, id: usize) {
+		self.lock().hooks.retain(|(hook_id, _)| *hook_id != id);
+	}
+}
+
+impl Drop for Inner {
+	fn drop(&mut self) {
+		if let Some((ref parent, hook)) = self.parent {
+			if let Some(parent) = parent.upgrade() {
+				parent.remove_hook(hook);
+			}
+		}
+	}
+}
+
+/// A token signalling that a call (or a whole session) was cancelled.
+///
+/// Cloned tokens share the state, cancelling any of them cancels all.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+	inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+	/// Creates new, not cancelled token.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Creates a token that is cancelled together with this one,
+	/// but can also be cancelled on its own.
+	pub fn child_token(&self) -> Self {
+		let inner = Arc::new_cyclic(|child: &Weak<Inner>| {
+			let child = child.clone();
+			let hook = self.inner.add_hook(Box::new(move || {
+				if let Some(inner) = child.upgrade() {
+					CancellationToken { inner }.cancel()
+				}
+			}));
+
+			match hook {
+				Some(hook) => Inner {
+					state: Default::default(),
+					parent: Some((Arc::downgrade(&self.inner), hook)),
+				},
+				// The parent is already cancelled.
+				None => Inner {
+					state: Mutex::new(State {
+						cancelled: true,
+						..Default::default()
+					}),
+					parent: None,
+				},
+			}
+		});
+
+		CancellationToken { inner }
+	}
+
+	/// Cancels the token, running all registered hooks.
+	///
+	/// Subsequent calls have no effect.
+	pub fn cancel(&self) {
+		let hooks = {
+			let mut state = self.inner.lock();
+			if state.cancelled {
+				return;
+			}
+			state.cancelled = true;
+			std::mem::take(&mut state.hooks)
+		};
+
+		for (_, hook) in hooks {
+			hook();
+		}
+	}
+
+	/// Returns `true` if the token was cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.inner.lock().cancelled
+	}
+
+	/// Registers a hook run once the token is cancelled.
+	///
+	/// The hook is run immediately if the token is already cancelled.
+	pub fn on_cancel<F: FnOnce() + Send + 'static>(&self, hook: F) {
+		self.inner.add_hook(Box::new(hook));
+	}
+
+	/// Returns a future resolving once the token is cancelled.
+	pub fn cancelled(&self) -> Cancelled {
+		Cancelled {
+			token: self.clone(),
+			waker: Arc::new(AtomicWaker::new()),
+			hook: None,
+		}
+	}
+
+	/// Returns a guard cancelling the token once dropped.
+	pub fn drop_guard(self) -> DropGuard {
+		DropGuard { token: self }
+	}
+
+	/// Identity of the shared state, used to scope request ids to a session.
+	fn key(&self) -> usize {
+		Arc::as_ptr(&self.inner) as usize
+	}
+}
+
+impl fmt::Debug for CancellationToken {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("CancellationToken")
+			.field("cancelled", &self.is_cancelled())
+			.finish()
+	}
+}
+
+/// Cancels the token when dropped, see `CancellationToken::drop_guard`.
+#[derive(Debug)]
+pub struct DropGuard {
+	token: CancellationToken,
+}
+
+impl Drop for DropGuard {
+	fn drop(&mut self) {
+		self.token.cancel()
+	}
+}
+
+/// Future returned by `CancellationToken::cancelled`.
+pub struct Cancelled {
+	token: CancellationToken,
+	waker: Arc<AtomicWaker>,
+	hook: Option<usize>,
+}
+
+impl Future for Cancelled {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+		self.waker.register(cx.waker());
+		if self.token.is_cancelled() {
+			return Poll::Ready(());
+		}
+
+		if self.hook.is_none() {
+			let waker = self.waker.clone();
+			self.hook = self.token.inner.add_hook(Box::new(move || waker.wake()));
+		}
+
+		if self.token.is_cancelled() {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+impl Drop for Cancelled {
+	fn drop(&mut self) {
+		if let Some(hook) = self.hook.take() {
+			self.token.inner.remove_hook(hook);
+		}
+	}
+}
+
+/// Calls currently executed by `MetaIoHandler`, keyed by session and request id.
+#[derive(Default)]
+pub(crate) struct InFlight {
+	calls: Mutex<HashMap<(usize, Id), CancellationToken>>,
+}
+
+impl InFlight {
+	fn lock(&self) -> MutexGuard<'_, HashMap<(usize, Id), CancellationToken>> {
+		self.calls.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Creates a token for a call with given id made within given session.
+	pub(crate) fn register(self: &Arc<Self>, session: &CancellationToken, id: Id) -> (CancellationToken, Registration) {
+		let token = session.child_token();
+		let key = (session.key(), id);
+		self.lock().insert(key.clone(), token.clone());
+
+		let registration = Registration {
+			in_flight: self.clone(),
+			key,
+			token: token.clone(),
+		};
+		(token, registration)
+	}
+
+	/// Cancels a call with given id made within given session.
+	pub(crate) fn cancel(&self, session: &CancellationToken, id: Id) {
+		let token = self.lock().remove(&(session.key(), id));
+		if let Some(token) = token {
+			token.cancel();
+		}
+	}
+}
+
+impl fmt::Debug for InFlight {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("InFlight").field("calls", &self.lock().len()).finish()
+	}
+}
+
+/// Removes the call from `InFlight` once dropped.
+pub(crate) struct Registration {
+	in_flight: Arc<InFlight>,
+	key: (usize, Id),
+	token: CancellationToken,
+}
+
+impl Drop for Registration {
+	fn drop(&mut self) {
+		let mut calls = self.in_flight.lock();
+		// Ids may be reused by the client, only remove our own entry.
+		if matches!(calls.get(&self.key), Some(token) if token.key() == self.token.key()) {
+			calls.remove(&self.key);
+		}
+	}
+}
+
+/// Params of the `$/cancelRequest` notification.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CancelParams {
+	pub id: Id,
+}
+
+/// Resolves to `Error::request_cancelled` if the token is cancelled before given future completes.
+///
+/// The future is dropped as soon as the token is cancelled.
+pub(crate) fn with_cancellation(
+	method: BoxFuture<crate::Result<Value>>,
+	token: CancellationToken,
+	registration: Registration,
+) -> BoxFuture<crate::Result<Value>> {
+	Box::pin(async move {
+		let _registration = registration;
+		match future::select(method, token.cancelled()).await {
+			Either::Left((result, _)) => result,
+			Either::Right(((), _)) => Err(Error::request_cancelled()),
+		}
+	})
+}
+
+/// Ends given stream with `Error::request_cancelled` if the token is cancelled before it ends.
+pub(crate) fn with_stream_cancellation(
+	items: ResultStream,
+	token: CancellationToken,
+	registration: Registration,
+) -> ResultStream {
+	let cancelled = async move {
+		let _registration = registration;
+		token.cancelled().await
+	};
+	streaming::interrupt(items, cancelled, Error::request_cancelled())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	#[test]
+	fn should_run_hooks_once() {
+		let token = CancellationToken::new();
+		let called = Arc::new(AtomicUsize::new(0));
+		let c = called.clone();
+		token.on_cancel(move || {
+			c.fetch_add(1, Ordering::SeqCst);
+		});
+
+		token.cancel();
+		token.cancel();
+
+		assert!(token.is_cancelled());
+		assert_eq!(called.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn should_cancel_children_with_parent() {
+		let parent = CancellationToken::new();
+		let child = parent.child_token();
+		let other = parent.child_token();
+
+		other.cancel();
+		assert!(!parent.is_cancelled());
+		assert!(!child.is_cancelled());
+
+		parent.cancel();
+		assert!(child.is_cancelled());
+		assert!(parent.child_token().is_cancelled());
+	}
+
+	#[test]
+	fn should_not_leak_hooks_of_dropped_children() {
+		let parent = CancellationToken::new();
+		let child = parent.child_token();
+		let cancelled = child.cancelled();
+		futures_executor::block_on(future::select(Box::pin(cancelled), future::ready(())));
+
+		assert_eq!(parent.inner.lock().hooks.len(), 1);
+		drop(child);
+		assert_eq!(parent.inner.lock().hooks.len(), 0);
+	}
+}
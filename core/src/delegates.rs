@@ -3,8 +3,13 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::calls::{Metadata, RemoteProcedure, RpcMethod, RpcNotification};
+use crate::deprecation;
+use crate::discover::{self, MethodDescription};
+use crate::timeout;
+use crate::types::raw::{AnyParams, RawParams};
 use crate::types::{Error, Params, Value};
 use crate::BoxFuture;
 
@@ -82,6 +87,53 @@ where
 	}
 }
 
+struct DelegateAnyParamsMethod<T, F> {
+	delegate: Arc<T>,
+	closure: F,
+}
+
+impl<T, M, F, I> RpcMethod<M> for DelegateAnyParamsMethod<T, F>
+where
+	M: Metadata,
+	F: Fn(&T, AnyParams, M) -> I,
+	I: Future<Output = Result<Value, Error>> + Send + 'static,
+	T: Send + Sync + 'static,
+	F: Send + Sync + 'static,
+{
+	fn call(&self, params: Params, meta: M) -> BoxFuture<crate::Result<Value>> {
+		let closure = &self.closure;
+		Box::pin(closure(&self.delegate, AnyParams::Parsed(params), meta))
+	}
+
+	fn call_raw(&self, params: RawParams, meta: M) -> BoxFuture<crate::Result<Value>> {
+		let closure = &self.closure;
+		Box::pin(closure(&self.delegate, AnyParams::Raw(params), meta))
+	}
+}
+
+struct DelegateAnyParamsNotification<T, F> {
+	delegate: Arc<T>,
+	closure: F,
+}
+
+impl<T, M, F> RpcNotification<M> for DelegateAnyParamsNotification<T, F>
+where
+	M: Metadata,
+	F: Fn(&T, AnyParams, M) + 'static,
+	F: Send + Sync + 'static,
+	T: Send + Sync + 'static,
+{
+	fn execute(&self, params: Params, meta: M) {
+		let closure = &self.closure;
+		closure(&self.delegate, AnyParams::Parsed(params), meta)
+	}
+
+	fn execute_raw(&self, params: RawParams, meta: M) {
+		let closure = &self.closure;
+		closure(&self.delegate, AnyParams::Raw(params), meta)
+	}
+}
+
 /// A set of RPC methods and notifications tied to single `delegate` struct.
 pub struct IoDelegate<T, M = ()>
 where
@@ -172,6 +224,85 @@ where
 			})),
 		);
 	}
+
+	/// Adds async method accepting either parsed or raw params to the delegate.
+	///
+	/// Params of requests handled with `MetaIoHandler::handle_raw_request` are passed
+	/// unparsed, so the method can deserialize them directly into the expected types.
+	pub fn add_any_params_method<F, I>(&mut self, name: &str, method: F)
+	where
+		F: Fn(&T, AnyParams) -> I,
+		I: Future<Output = Result<Value, Error>> + Send + 'static,
+		F: Send + Sync + 'static,
+	{
+		self.add_any_params_method_with_meta(name, move |delegate, params, _meta| method(delegate, params))
+	}
+
+	/// Adds async method with metadata accepting either parsed or raw params to the delegate.
+	pub fn add_any_params_method_with_meta<F, I>(&mut self, name: &str, method: F)
+	where
+		F: Fn(&T, AnyParams, M) -> I,
+		I: Future<Output = Result<Value, Error>> + Send + 'static,
+		F: Send + Sync + 'static,
+	{
+		self.methods.insert(
+			name.into(),
+			RemoteProcedure::Method(Arc::new(DelegateAnyParamsMethod {
+				delegate: self.delegate.clone(),
+				closure: method,
+			})),
+		);
+	}
+
+	/// Adds notification accepting either parsed or raw params to the delegate.
+	pub fn add_any_params_notification<F>(&mut self, name: &str, notification: F)
+	where
+		F: Fn(&T, AnyParams),
+		F: Send + Sync + 'static,
+	{
+		self.add_any_params_notification_with_meta(name, move |delegate, params, _meta| notification(delegate, params))
+	}
+
+	/// Adds notification with metadata accepting either parsed or raw params to the delegate.
+	pub fn add_any_params_notification_with_meta<F>(&mut self, name: &str, notification: F)
+	where
+		F: Fn(&T, AnyParams, M),
+		F: Send + Sync + 'static,
+	{
+		self.methods.insert(
+			name.into(),
+			RemoteProcedure::Notification(Arc::new(DelegateAnyParamsNotification {
+				delegate: self.delegate.clone(),
+				closure: notification,
+			})),
+		);
+	}
+
+	/// Attaches a description to a previously added method or notification.
+	pub fn describe_method(&mut self, name: &str, description: MethodDescription) {
+		if let Some(procedure) = self.methods.get_mut(name) {
+			discover::describe(procedure, description);
+		}
+	}
+
+	/// Marks a previously added method or notification as deprecated.
+	///
+	/// Calls log a rate-limited warning and the deprecation note is exposed via `rpc.discover`.
+	pub fn deprecate_method(&mut self, name: &str, note: &str) {
+		if let Some(procedure) = self.methods.get_mut(name) {
+			deprecation::set_deprecated(procedure, name, note);
+		}
+	}
+
+	/// Sets maximal time a previously added method is allowed to run.
+	///
+	/// Takes precedence over handler-wide timeout, but not over a timeout
+	/// set for this method directly on `MetaIoHandler`.
+	pub fn set_method_timeout(&mut self, name: &str, timeout: Duration) {
+		if let Some(procedure) = self.methods.get_mut(name) {
+			timeout::set_timeout(procedure, timeout);
+		}
+	}
 }
 
 impl<T, M> crate::io::IoHandlerExtension<M> for IoDelegate<T, M>
@@ -0,0 +1,208 @@
+//! Deprecated methods.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::calls::{Metadata, RemoteProcedure, RpcMethod, RpcNotification};
+use crate::discover::MethodDescription;
+use crate::streaming::{ResultStream, RpcStreamMethod};
+use crate::types::raw::RawParams;
+use crate::types::{Params, Value};
+use crate::BoxFuture;
+
+/// Minimal time between two warnings about calls to the same deprecated method.
+const WARNING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Marks a procedure as deprecated.
+///
+/// Calls are still executed, but each one logs a warning (at most once per `WARNING_INTERVAL`)
+/// and the deprecation note is exposed in the method description.
+pub(crate) fn set_deprecated<T: Metadata>(procedure: &mut RemoteProcedure<T>, name: &str, note: &str) {
+	deprecate(procedure, Arc::new(Warning::new(name, note)), note);
+}
+
+/// Marks a streaming method as deprecated.
+///
+/// The warning is shared with the procedure collecting its items, so the calls are rate-limited together.
+pub(crate) fn set_deprecated_stream<T: Metadata>(
+	procedure: &mut RemoteProcedure<T>,
+	stream: &mut Arc<dyn RpcStreamMethod<T>>,
+	name: &str,
+	note: &str,
+) {
+	let warning = Arc::new(Warning::new(name, note));
+	deprecate(procedure, warning.clone(), note);
+	*stream = Arc::new(DeprecatedStreamMethod {
+		method: stream.clone(),
+		warning,
+	});
+}
+
+fn deprecate<T: Metadata>(procedure: &mut RemoteProcedure<T>, warning: Arc<Warning>, note: &str) {
+	match *procedure {
+		RemoteProcedure::Method(ref mut method) => {
+			*method = Arc::new(DeprecatedMethod {
+				description: deprecated_description(method.description(), note),
+				method: method.clone(),
+				warning,
+			});
+		}
+		RemoteProcedure::Notification(ref mut notification) => {
+			*notification = Arc::new(DeprecatedNotification {
+				description: deprecated_description(notification.description(), note),
+				notification: notification.clone(),
+				warning,
+			});
+		}
+		RemoteProcedure::Alias(_) => {}
+	}
+}
+
+fn deprecated_description(description: Option<&MethodDescription>, note: &str) -> MethodDescription {
+	MethodDescription {
+		deprecated: true,
+		deprecation: Some(note.into()),
+		..description.cloned().unwrap_or_default()
+	}
+}
+
+/// Rate-limited warning about calls to a deprecated method.
+struct Warning {
+	name: String,
+	note: String,
+	last_logged: Mutex<Option<Instant>>,
+	suppressed: AtomicUsize,
+}
+
+impl Warning {
+	fn new(name: &str, note: &str) -> Self {
+		Warning {
+			name: name.into(),
+			note: note.into(),
+			last_logged: Mutex::new(None),
+			suppressed: AtomicUsize::new(0),
+		}
+	}
+
+	fn log(&self) {
+		let now = Instant::now();
+		let mut last_logged = self.last_logged.lock().expect("Warning lock is never poisoned; qed");
+		match *last_logged {
+			Some(last) if now.duration_since(last) < WARNING_INTERVAL => {
+				self.suppressed.fetch_add(1, Ordering::Relaxed);
+			}
+			_ => {
+				*last_logged = Some(now);
+				let suppressed = self.suppressed.swap(0, Ordering::Relaxed);
+				if suppressed > 0 {
+					warn!(
+						target: "rpc",
+						"Deprecated method {} was called ({} more calls since last warning): {}",
+						self.name,
+						suppressed,
+						self.note
+					);
+				} else {
+					warn!(target: "rpc", "Deprecated method {} was called: {}", self.name, self.note);
+				}
+			}
+		}
+	}
+}
+
+struct DeprecatedMethod<T> {
+	method: Arc<dyn RpcMethod<T>>,
+	description: MethodDescription,
+	warning: Arc<Warning>,
+}
+
+impl<T: Metadata> RpcMethod<T> for DeprecatedMethod<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.warning.log();
+		self.method.call(params, meta)
+	}
+
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.warning.log();
+		self.method.call_raw(params, meta)
+	}
+
+	fn description(&self) -> Option<&MethodDescription> {
+		Some(&self.description)
+	}
+
+	fn timeout(&self) -> Option<Duration> {
+		self.method.timeout()
+	}
+}
+
+struct DeprecatedNotification<T> {
+	notification: Arc<dyn RpcNotification<T>>,
+	description: MethodDescription,
+	warning: Arc<Warning>,
+}
+
+impl<T: Metadata> RpcNotification<T> for DeprecatedNotification<T> {
+	fn execute(&self, params: Params, meta: T) {
+		self.warning.log();
+		self.notification.execute(params, meta)
+	}
+
+	fn execute_raw(&self, params: RawParams, meta: T) {
+		self.warning.log();
+		self.notification.execute_raw(params, meta)
+	}
+
+	fn description(&self) -> Option<&MethodDescription> {
+		Some(&self.description)
+	}
+}
+
+struct DeprecatedStreamMethod<T> {
+	method: Arc<dyn RpcStreamMethod<T>>,
+	warning: Arc<Warning>,
+}
+
+impl<T: Metadata> RpcStreamMethod<T> for DeprecatedStreamMethod<T> {
+	fn call(&self, params: Params, meta: T) -> ResultStream {
+		self.warning.log();
+		self.method.call(params, meta)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{DeprecatedStreamMethod, Warning};
+	use crate::streaming::RpcStreamMethod;
+	use crate::types::{Params, Value};
+	use futures_util::stream;
+	use std::sync::atomic::Ordering;
+	use std::sync::Arc;
+
+	#[test]
+	fn should_count_suppressed_warnings() {
+		let warning = Warning::new("old_method", "Use new_method instead");
+
+		warning.log();
+		warning.log();
+		warning.log();
+
+		assert!(warning.last_logged.lock().unwrap().is_some());
+		assert_eq!(warning.suppressed.load(Ordering::Relaxed), 2);
+	}
+
+	#[test]
+	fn should_log_warnings_for_streaming_calls() {
+		let warning = Arc::new(Warning::new("numbers", "Use count instead"));
+		let method = DeprecatedStreamMethod::<()> {
+			method: Arc::new(|_, _| stream::iter(vec![Ok(Value::from(1))])),
+			warning: warning.clone(),
+		};
+
+		let _ = method.call(Params::None, ());
+		assert!(warning.last_logged.lock().unwrap().is_some());
+		let _ = method.call(Params::None, ());
+		assert_eq!(warning.suppressed.load(Ordering::Relaxed), 1);
+	}
+}
@@ -0,0 +1,511 @@
+//! Service discovery (`rpc.discover`)
+//!
+//! Methods registered on `MetaIoHandler` may carry a `MethodDescription`.
+//! Those descriptions are used to build an [OpenRPC](https://spec.open-rpc.org)
+//! document listing everything the handler exposes.
+//!
+//! With the `schemars` feature, schemas can be derived from the Rust types themselves
+//! (see `ContentDescriptor::for_type`), which is what `#[rpc(open_rpc)]` traits use
+//! to build their `open_rpc_spec()` at compile time and to describe their methods.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::calls::{Metadata, RemoteProcedure, RpcMethod, RpcNotification};
+use crate::types::raw::RawParams;
+use crate::types::{Params, Value};
+use crate::BoxFuture;
+use serde_json::json;
+
+/// Name of the built-in service discovery method.
+pub const DISCOVER_METHOD: &str = "rpc.discover";
+
+/// Version of the OpenRPC specification the documents conform to.
+pub const OPENRPC_VERSION: &str = "1.3.2";
+
+/// Describes the service as a whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Info {
+	/// Title of the service.
+	pub title: String,
+	/// Version of the service API.
+	pub version: String,
+	/// Verbose description of the service.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+}
+
+impl Info {
+	/// Creates new `Info` with given title and version.
+	pub fn new<T: Into<String>, V: Into<String>>(title: T, version: V) -> Self {
+		Info {
+			title: title.into(),
+			version: version.into(),
+			description: None,
+		}
+	}
+}
+
+/// Describes a single parameter or a result of a method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentDescriptor {
+	/// Name of the content.
+	pub name: String,
+	/// Verbose description of the content.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	/// Whether the content is required.
+	#[serde(default)]
+	pub required: bool,
+	/// JSON Schema of the content.
+	pub schema: Value,
+}
+
+impl ContentDescriptor {
+	/// Creates a required content descriptor with schema derived from given Rust type name.
+	pub fn new<N: Into<String>>(name: N, rust_type: &str) -> Self {
+		ContentDescriptor {
+			name: name.into(),
+			description: None,
+			required: !is_option(rust_type),
+			schema: schema_for_type(rust_type),
+		}
+	}
+}
+
+#[cfg(feature = "schemars")]
+impl ContentDescriptor {
+	/// Creates a content descriptor with JSON Schema of given type, generated by `schemars`.
+	///
+	/// Schemas of named types are collected by the generator, to be included in `Components`.
+	pub fn for_type<T: schemars::JsonSchema>(
+		name: &str,
+		required: bool,
+		generator: &mut schemars::gen::SchemaGenerator,
+	) -> Self {
+		ContentDescriptor {
+			name: name.into(),
+			description: None,
+			required,
+			schema: serde_json::to_value(generator.subschema_for::<T>()).expect("Schemas are always serializable; qed"),
+		}
+	}
+}
+
+/// Returns a schema generator referencing named types from `#/components/schemas/`.
+#[cfg(feature = "schemars")]
+pub fn schema_generator() -> schemars::gen::SchemaGenerator {
+	schemars::gen::SchemaSettings::draft07()
+		.with(|settings| settings.definitions_path = "#/components/schemas/".into())
+		.into_generator()
+}
+
+/// Returns a schema generator inlining schemas of named types.
+///
+/// Used for method descriptions, which are served by `rpc.discover` without `Components`.
+/// Recursive types are still referenced.
+#[cfg(feature = "schemars")]
+pub fn inline_schema_generator() -> schemars::gen::SchemaGenerator {
+	schemars::gen::SchemaSettings::draft07()
+		.with(|settings| settings.inline_subschemas = true)
+		.into_generator()
+}
+
+/// Description of a single RPC method, notification or subscription.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MethodDescription {
+	/// Short summary of what the method does.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub summary: Option<String>,
+	/// Verbose explanation of the method behaviour.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	/// Method parameters.
+	#[serde(default)]
+	pub params: Vec<ContentDescriptor>,
+	/// Method result (none for notifications).
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub result: Option<ContentDescriptor>,
+	/// Name of the subscription this method subscribes to.
+	#[serde(rename = "x-subscribe", default, skip_serializing_if = "Option::is_none")]
+	pub subscribe: Option<String>,
+	/// Name of the subscription this method unsubscribes from.
+	#[serde(rename = "x-unsubscribe", default, skip_serializing_if = "Option::is_none")]
+	pub unsubscribe: Option<String>,
+	/// Whether the method is deprecated.
+	#[serde(default, skip_serializing_if = "is_false")]
+	pub deprecated: bool,
+	/// Explanation of the deprecation, e.g. what to use instead.
+	#[serde(rename = "x-deprecation", default, skip_serializing_if = "Option::is_none")]
+	pub deprecation: Option<String>,
+}
+
+impl MethodDescription {
+	/// Creates a description from documentation text.
+	///
+	/// The first line becomes the summary, the whole text becomes the description.
+	pub fn from_docs(docs: &str) -> Self {
+		let docs = docs.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+		let docs = docs.trim();
+		if docs.is_empty() {
+			return Default::default();
+		}
+
+		MethodDescription {
+			summary: docs.lines().next().map(Into::into),
+			description: Some(docs.to_owned()),
+			..Default::default()
+		}
+	}
+}
+
+/// OpenRPC method object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Method {
+	/// Name of the method.
+	pub name: String,
+	/// Description of the method.
+	#[serde(flatten)]
+	pub description: MethodDescription,
+	/// Aliases the method can also be called with.
+	#[serde(rename = "x-aliases", default, skip_serializing_if = "Vec::is_empty")]
+	pub aliases: Vec<String>,
+	/// Whether the procedure is a notification (does not produce a response).
+	#[serde(rename = "x-notification", default, skip_serializing_if = "is_false")]
+	pub notification: bool,
+}
+
+/// Definitions referenced from other parts of the document.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Components {
+	/// JSON Schemas by name.
+	#[serde(default)]
+	pub schemas: BTreeMap<String, Value>,
+}
+
+/// OpenRPC service description document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenRpc {
+	/// OpenRPC specification version.
+	pub openrpc: String,
+	/// Service information.
+	pub info: Info,
+	/// Methods exposed by the service.
+	pub methods: Vec<Method>,
+	/// Definitions referenced by the methods.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub components: Option<Components>,
+}
+
+impl OpenRpc {
+	/// Builds the document from registered procedures.
+	pub fn new<'a, T, I>(info: Info, procedures: I) -> Self
+	where
+		T: Metadata,
+		I: IntoIterator<Item = (&'a String, &'a RemoteProcedure<T>)>,
+	{
+		let mut methods = BTreeMap::new();
+		let mut aliases = Vec::new();
+		for (name, procedure) in procedures {
+			let (description, notification) = match *procedure {
+				RemoteProcedure::Method(ref method) => (method.description(), false),
+				RemoteProcedure::Notification(ref notification) => (notification.description(), true),
+				RemoteProcedure::Alias(ref target) => {
+					aliases.push((name.clone(), target.clone()));
+					continue;
+				}
+			};
+			if name == DISCOVER_METHOD {
+				continue;
+			}
+			methods.insert(
+				name.clone(),
+				Method {
+					name: name.clone(),
+					description: description.cloned().unwrap_or_default(),
+					aliases: Vec::new(),
+					notification,
+				},
+			);
+		}
+
+		aliases.sort();
+		for (alias, target) in aliases {
+			if let Some(method) = methods.get_mut(&target) {
+				method.aliases.push(alias);
+			}
+		}
+
+		OpenRpc {
+			openrpc: OPENRPC_VERSION.into(),
+			info,
+			methods: methods.into_values().collect(),
+			components: None,
+		}
+	}
+
+	/// Builds the document from method descriptions (sorted by name) and schemas collected while creating them.
+	#[cfg(feature = "schemars")]
+	pub fn with_schemas(info: Info, mut methods: Vec<Method>, mut generator: schemars::gen::SchemaGenerator) -> Self {
+		methods.sort_by(|a, b| a.name.cmp(&b.name));
+		let schemas = generator
+			.take_definitions()
+			.into_iter()
+			.map(|(name, schema)| {
+				let schema = serde_json::to_value(schema).expect("Schemas are always serializable; qed");
+				(name, schema)
+			})
+			.collect();
+
+		OpenRpc {
+			openrpc: OPENRPC_VERSION.into(),
+			info,
+			methods,
+			components: Some(Components { schemas }),
+		}
+	}
+}
+
+/// Attaches given description to a procedure.
+///
+/// Aliases cannot be described, they are listed alongside the method they point to.
+/// Deprecation of the procedure is kept, unless the new description deprecates it itself.
+pub(crate) fn describe<T: Metadata>(procedure: &mut RemoteProcedure<T>, mut description: MethodDescription) {
+	let previous = match *procedure {
+		RemoteProcedure::Method(ref method) => method.description(),
+		RemoteProcedure::Notification(ref notification) => notification.description(),
+		RemoteProcedure::Alias(_) => None,
+	};
+	if let Some(previous) = previous.filter(|previous| previous.deprecated && !description.deprecated) {
+		description.deprecated = true;
+		description.deprecation = previous.deprecation.clone();
+	}
+
+	match *procedure {
+		RemoteProcedure::Method(ref mut method) => {
+			*method = Arc::new(DescribedMethod {
+				method: method.clone(),
+				description,
+			});
+		}
+		RemoteProcedure::Notification(ref mut notification) => {
+			*notification = Arc::new(DescribedNotification {
+				notification: notification.clone(),
+				description,
+			});
+		}
+		RemoteProcedure::Alias(_) => {}
+	}
+}
+
+struct DescribedMethod<T> {
+	method: Arc<dyn RpcMethod<T>>,
+	description: MethodDescription,
+}
+
+impl<T: Metadata> RpcMethod<T> for DescribedMethod<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.method.call(params, meta)
+	}
+
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.method.call_raw(params, meta)
+	}
+
+	fn description(&self) -> Option<&MethodDescription> {
+		Some(&self.description)
+	}
+
+	fn timeout(&self) -> Option<Duration> {
+		self.method.timeout()
+	}
+}
+
+struct DescribedNotification<T> {
+	notification: Arc<dyn RpcNotification<T>>,
+	description: MethodDescription,
+}
+
+impl<T: Metadata> RpcNotification<T> for DescribedNotification<T> {
+	fn execute(&self, params: Params, meta: T) {
+		self.notification.execute(params, meta)
+	}
+
+	fn execute_raw(&self, params: RawParams, meta: T) {
+		self.notification.execute_raw(params, meta)
+	}
+
+	fn description(&self) -> Option<&MethodDescription> {
+		Some(&self.description)
+	}
+}
+
+/// Returns a JSON Schema for given Rust type name.
+///
+/// Common primitive and collection types are mapped to their JSON representation,
+/// other types are described by their name only.
+pub fn schema_for_type(rust_type: &str) -> Value {
+	let rust_type: String = rust_type.chars().filter(|c| !c.is_whitespace()).collect();
+	let rust_type = rust_type.trim_start_matches('&');
+	let (name, args) = split_generics(rust_type);
+
+	match name {
+		"" | "()" => json!({ "type": "null" }),
+		"bool" => json!({ "type": "boolean" }),
+		"u8" | "u16" | "u32" | "u64" | "u128" | "usize" => json!({ "type": "integer", "minimum": 0 }),
+		"i8" | "i16" | "i32" | "i64" | "i128" | "isize" => json!({ "type": "integer" }),
+		"f32" | "f64" => json!({ "type": "number" }),
+		"char" | "str" | "String" => json!({ "type": "string" }),
+		"Value" | "Params" => json!({}),
+		"Option" if args.len() == 1 => json!({ "oneOf": [schema_for_type(args[0]), { "type": "null" }] }),
+		"Vec" | "VecDeque" | "HashSet" | "BTreeSet" if args.len() == 1 => {
+			json!({ "type": "array", "items": schema_for_type(args[0]) })
+		}
+		"HashMap" | "BTreeMap" if args.len() == 2 => {
+			json!({ "type": "object", "additionalProperties": schema_for_type(args[1]) })
+		}
+		"Box" | "Arc" | "Rc" if args.len() == 1 => schema_for_type(args[0]),
+		_ => match rust_type.strip_prefix('[').and_then(|inner| inner.strip_suffix(']')) {
+			Some(inner) => {
+				let item = inner.split(';').next().unwrap_or_default();
+				json!({ "type": "array", "items": schema_for_type(item) })
+			}
+			None => json!({ "title": rust_type }),
+		},
+	}
+}
+
+/// Returns `true` if given Rust type name denotes an `Option`.
+fn is_option(rust_type: &str) -> bool {
+	let rust_type: String = rust_type.chars().filter(|c| !c.is_whitespace()).collect();
+	split_generics(&rust_type).0 == "Option"
+}
+
+/// Splits a type name into its last path segment and top-level generic arguments.
+fn split_generics(rust_type: &str) -> (&str, Vec<&str>) {
+	let (path, args) = match rust_type.find('<') {
+		Some(start) if rust_type.ends_with('>') => (&rust_type[..start], &rust_type[start + 1..rust_type.len() - 1]),
+		_ => return (rust_type.rsplit("::").next().unwrap_or(rust_type), Vec::new()),
+	};
+
+	let mut result = Vec::new();
+	let mut depth = 0;
+	let mut last = 0;
+	for (idx, c) in args.char_indices() {
+		match c {
+			'<' | '(' | '[' => depth += 1,
+			'>' | ')' | ']' => depth -= 1,
+			',' if depth == 0 => {
+				result.push(&args[last..idx]);
+				last = idx + 1;
+			}
+			_ => {}
+		}
+	}
+	result.push(&args[last..]);
+
+	(path.rsplit("::").next().unwrap_or(path), result)
+}
+
+fn is_false(value: &bool) -> bool {
+	!*value
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_map_rust_types_to_schemas() {
+		assert_eq!(schema_for_type("u64"), json!({ "type": "integer", "minimum": 0 }));
+		assert_eq!(schema_for_type("String"), json!({ "type": "string" }));
+		assert_eq!(
+			schema_for_type("Vec < Option < bool > >"),
+			json!({ "type": "array", "items": { "oneOf": [{ "type": "boolean" }, { "type": "null" }] } })
+		);
+		assert_eq!(
+			schema_for_type("std::collections::HashMap<String, Vec<u8>>"),
+			json!({ "type": "object", "additionalProperties": { "type": "array", "items": { "type": "integer", "minimum": 0 } } })
+		);
+		assert_eq!(schema_for_type("MyStruct"), json!({ "title": "MyStruct" }));
+	}
+
+	#[test]
+	fn should_handle_slices_and_malformed_types() {
+		let bytes = json!({ "type": "array", "items": { "type": "integer", "minimum": 0 } });
+		assert_eq!(schema_for_type("[u8; 32]"), bytes);
+		assert_eq!(schema_for_type("&[u8]"), bytes);
+		assert_eq!(schema_for_type("["), json!({ "title": "[" }));
+		assert_eq!(schema_for_type("[u8"), json!({ "title": "[u8" }));
+		assert_eq!(schema_for_type("]"), json!({ "title": "]" }));
+		assert_eq!(schema_for_type("<"), json!({ "title": "<" }));
+	}
+
+	#[test]
+	fn should_mark_options_as_not_required() {
+		assert!(ContentDescriptor::new("a", "u64").required);
+		assert!(!ContentDescriptor::new("a", "Option<u64>").required);
+	}
+
+	#[cfg(feature = "schemars")]
+	#[test]
+	fn should_collect_schemas_of_named_types() {
+		#[derive(schemars::JsonSchema)]
+		#[allow(dead_code)]
+		struct Transfer {
+			to: String,
+			amount: Option<u64>,
+		}
+
+		let mut generator = schema_generator();
+		let params = vec![
+			ContentDescriptor::for_type::<Transfer>("transfer", true, &mut generator),
+			ContentDescriptor::for_type::<Option<bool>>("dry_run", false, &mut generator),
+		];
+		let method = Method {
+			name: "transfer".into(),
+			description: MethodDescription {
+				params,
+				..Default::default()
+			},
+			aliases: Vec::new(),
+			notification: true,
+		};
+		let document = OpenRpc::with_schemas(Info::new("test", "1.0.0"), vec![method], generator);
+
+		let params = &document.methods[0].description.params;
+		assert_eq!(params[0].schema, json!({ "$ref": "#/components/schemas/Transfer" }));
+		assert!(params[0].required);
+		assert!(!params[1].required);
+		let schemas = document.components.unwrap().schemas;
+		assert_eq!(schemas["Transfer"]["required"], json!(["to"]));
+	}
+
+	#[cfg(feature = "schemars")]
+	#[test]
+	fn should_inline_schemas_of_named_types() {
+		#[derive(schemars::JsonSchema)]
+		#[allow(dead_code)]
+		struct Transfer {
+			to: String,
+		}
+
+		let mut generator = inline_schema_generator();
+		let descriptor = ContentDescriptor::for_type::<Transfer>("transfer", true, &mut generator);
+
+		assert_eq!(descriptor.schema["type"], json!("object"));
+		assert_eq!(descriptor.schema["required"], json!(["to"]));
+	}
+
+	#[test]
+	fn should_split_docs_into_summary_and_description() {
+		let description = MethodDescription::from_docs(" Adds two numbers.\n\n Overflows are reported as errors.");
+
+		assert_eq!(description.summary, Some("Adds two numbers.".into()));
+		assert_eq!(
+			description.description,
+			Some("Adds two numbers.\n\nOverflows are reported as errors.".into())
+		);
+	}
+}
@@ -0,0 +1,298 @@
+//! Wire encodings of requests and responses.
+//!
+//! JSON text is always available, MessagePack and CBOR require `msgpack` and `cbor` features.
+//! Both binary encodings carry exactly the same messages as JSON (maps with `jsonrpc`, `method`,
+//! `params` and `id` keys), only the representation differs.
+//! See `MetaIoHandler::handle_encoded_request`.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::types::{Error, Request, Response, Value};
+
+/// Content type of JSON text messages.
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+/// Content type of MessagePack messages.
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+/// Content type of CBOR messages.
+pub const CBOR_CONTENT_TYPE: &str = "application/cbor";
+
+/// Encoding used to (de)serialize messages exchanged with the peer.
+pub trait Encoding: fmt::Debug + Send + Sync + 'static {
+	/// MIME type of encoded messages, e.g. `application/json`.
+	fn content_type(&self) -> &'static str;
+
+	/// Returns `true` if encoded messages are UTF-8 text.
+	///
+	/// Stream transports delimit text messages with separators
+	/// and prefix binary messages with their length.
+	fn is_text(&self) -> bool {
+		false
+	}
+
+	/// Decodes a request, fails with `ParseError` if the message is malformed.
+	fn decode_request(&self, request: &[u8]) -> Result<Request, Error>;
+
+	/// Encodes a response.
+	fn encode_response(&self, response: &Response) -> Vec<u8>;
+
+	/// Decodes an arbitrary message, fails with `ParseError` if it's malformed.
+	fn decode_value(&self, message: &[u8]) -> Result<Value, Error>;
+
+	/// Encodes an arbitrary message.
+	fn encode_value(&self, message: &Value) -> Vec<u8>;
+
+	/// Re-encodes a JSON text message, e.g. a notification produced by `jsonrpc-pubsub`.
+	fn encode_json(&self, message: &str) -> Result<Vec<u8>, Error> {
+		let value: Value = serde_json::from_str(message).map_err(|_| Error::parse_error())?;
+		Ok(self.encode_value(&value))
+	}
+
+	/// Converts an encoded message to JSON text.
+	fn decode_json(&self, message: &[u8]) -> Result<String, Error> {
+		self.decode_value(message).map(|value| value.to_string())
+	}
+}
+
+/// JSON text encoding.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Json;
+
+impl Encoding for Json {
+	fn content_type(&self) -> &'static str {
+		JSON_CONTENT_TYPE
+	}
+
+	fn is_text(&self) -> bool {
+		true
+	}
+
+	fn decode_request(&self, request: &[u8]) -> Result<Request, Error> {
+		let request = std::str::from_utf8(request).map_err(|_| Error::parse_error())?;
+		crate::serde_from_str(request).map_err(|_| Error::parse_error())
+	}
+
+	fn encode_response(&self, response: &Response) -> Vec<u8> {
+		// this should never fail
+		serde_json::to_vec(response).unwrap()
+	}
+
+	fn decode_value(&self, message: &[u8]) -> Result<Value, Error> {
+		serde_json::from_slice(message).map_err(|_| Error::parse_error())
+	}
+
+	fn encode_value(&self, message: &Value) -> Vec<u8> {
+		// this should never fail
+		serde_json::to_vec(message).unwrap()
+	}
+
+	fn encode_json(&self, message: &str) -> Result<Vec<u8>, Error> {
+		Ok(message.as_bytes().to_vec())
+	}
+
+	fn decode_json(&self, message: &[u8]) -> Result<String, Error> {
+		String::from_utf8(message.to_vec()).map_err(|_| Error::parse_error())
+	}
+}
+
+/// MessagePack encoding, structs are encoded as maps.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Encoding for MessagePack {
+	fn content_type(&self) -> &'static str {
+		MSGPACK_CONTENT_TYPE
+	}
+
+	fn decode_request(&self, request: &[u8]) -> Result<Request, Error> {
+		rmp_serde::from_slice(request).map_err(|_| Error::parse_error())
+	}
+
+	fn encode_response(&self, response: &Response) -> Vec<u8> {
+		self.encode_value(&response_to_value(response))
+	}
+
+	fn decode_value(&self, message: &[u8]) -> Result<Value, Error> {
+		rmp_serde::from_slice(message).map_err(|_| Error::parse_error())
+	}
+
+	fn encode_value(&self, message: &Value) -> Vec<u8> {
+		// this should never fail
+		rmp_serde::to_vec_named(&BinaryValue(message)).unwrap()
+	}
+}
+
+/// CBOR encoding.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Encoding for Cbor {
+	fn content_type(&self) -> &'static str {
+		CBOR_CONTENT_TYPE
+	}
+
+	fn decode_request(&self, request: &[u8]) -> Result<Request, Error> {
+		ciborium::de::from_reader(request).map_err(|_| Error::parse_error())
+	}
+
+	fn encode_response(&self, response: &Response) -> Vec<u8> {
+		self.encode_value(&response_to_value(response))
+	}
+
+	fn decode_value(&self, message: &[u8]) -> Result<Value, Error> {
+		ciborium::de::from_reader(message).map_err(|_| Error::parse_error())
+	}
+
+	fn encode_value(&self, message: &Value) -> Vec<u8> {
+		let mut message_bytes = Vec::new();
+		// this should never fail
+		ciborium::ser::into_writer(&BinaryValue(message), &mut message_bytes).unwrap();
+		message_bytes
+	}
+}
+
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+fn response_to_value(response: &Response) -> Value {
+	// this should never fail
+	serde_json::to_value(response).unwrap()
+}
+
+/// Serializes a JSON value with numbers written as native numbers of the binary encoding.
+///
+/// With `arbitrary_precision`, `serde_json` serializes numbers as maps with a private key,
+/// which other formats would keep as they are. Numbers not representable as `u64`, `i64`
+/// or a finite `f64` are written as strings.
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+struct BinaryValue<'a>(&'a Value);
+
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+impl serde::Serialize for BinaryValue<'_> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match *self.0 {
+			Value::Null => serializer.serialize_unit(),
+			Value::Bool(value) => serializer.serialize_bool(value),
+			Value::Number(ref number) => {
+				if let Some(number) = number.as_u64() {
+					serializer.serialize_u64(number)
+				} else if let Some(number) = number.as_i64() {
+					serializer.serialize_i64(number)
+				} else if let Some(number) = number.as_f64() {
+					serializer.serialize_f64(number)
+				} else {
+					serializer.serialize_str(&number.to_string())
+				}
+			}
+			Value::String(ref value) => serializer.serialize_str(value),
+			Value::Array(ref values) => serializer.collect_seq(values.iter().map(BinaryValue)),
+			Value::Object(ref values) => {
+				serializer.collect_map(values.iter().map(|(key, value)| (key, BinaryValue(value))))
+			}
+		}
+	}
+}
+
+/// Returns all encodings enabled in this build, JSON first.
+pub fn available() -> Vec<Arc<dyn Encoding>> {
+	#[allow(unused_mut)]
+	let mut encodings: Vec<Arc<dyn Encoding>> = vec![Arc::new(Json)];
+	#[cfg(feature = "msgpack")]
+	encodings.push(Arc::new(MessagePack));
+	#[cfg(feature = "cbor")]
+	encodings.push(Arc::new(Cbor));
+	encodings
+}
+
+/// Finds an enabled encoding by its content type, ignoring parameters (e.g. `charset`).
+pub fn from_content_type(content_type: &str) -> Option<Arc<dyn Encoding>> {
+	let mime = content_type.split(';').next().unwrap_or_default().trim();
+	available()
+		.into_iter()
+		.find(|encoding| encoding.content_type().eq_ignore_ascii_case(mime))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::{Call, Id, MethodCall, Output, Params, Success, Version};
+
+	fn request() -> Request {
+		Request::Single(Call::MethodCall(MethodCall {
+			jsonrpc: Some(Version::V2),
+			method: "add".into(),
+			params: Params::Array(vec![Value::from(1), Value::from("a")]),
+			id: Id::Num(1),
+		}))
+	}
+
+	fn response() -> Response {
+		Response::Single(Output::Success(Success {
+			jsonrpc: Some(Version::V2),
+			result: Value::from(5),
+			id: Id::Str("x".into()),
+		}))
+	}
+
+	fn round_trip(encoding: &dyn Encoding) {
+		let json = serde_json::to_string(&request()).unwrap();
+		let encoded = encoding.encode_json(&json).unwrap();
+		assert_eq!(encoding.decode_request(&encoded), Ok(request()));
+
+		let encoded = encoding.encode_response(&response());
+		let decoded: Response = serde_json::from_str(&encoding.decode_json(&encoded).unwrap()).unwrap();
+		assert_eq!(decoded, response());
+
+		assert_eq!(encoding.decode_request(b"\xc1"), Err(Error::parse_error()));
+	}
+
+	#[test]
+	fn should_round_trip_json() {
+		round_trip(&Json);
+	}
+
+	#[cfg(feature = "msgpack")]
+	#[test]
+	fn should_round_trip_msgpack() {
+		round_trip(&MessagePack);
+	}
+
+	#[cfg(feature = "cbor")]
+	#[test]
+	fn should_round_trip_cbor() {
+		round_trip(&Cbor);
+	}
+
+	#[cfg(any(feature = "msgpack", feature = "cbor"))]
+	fn round_trip_numbers(encoding: &dyn Encoding) {
+		let json = r#"{"jsonrpc":"2.0","method":"add","params":[1,-2,1.5,18446744073709551615],"id":1}"#;
+		let encoded = encoding.encode_json(json).unwrap();
+		let decoded: Value = serde_json::from_str(&encoding.decode_json(&encoded).unwrap()).unwrap();
+		assert_eq!(decoded, serde_json::from_str::<Value>(json).unwrap());
+		match encoding.decode_request(&encoded) {
+			Ok(Request::Single(Call::MethodCall(call))) => assert_eq!(call.id, Id::Num(1)),
+			other => panic!("Unexpected request: {:?}", other),
+		}
+	}
+
+	#[cfg(feature = "msgpack")]
+	#[test]
+	fn should_encode_numbers_natively_in_msgpack() {
+		round_trip_numbers(&MessagePack);
+	}
+
+	#[cfg(feature = "cbor")]
+	#[test]
+	fn should_encode_numbers_natively_in_cbor() {
+		round_trip_numbers(&Cbor);
+	}
+
+	#[test]
+	fn should_find_encoding_by_content_type() {
+		let encoding = from_content_type("Application/JSON; charset=utf-8").unwrap();
+		assert_eq!(encoding.content_type(), JSON_CONTENT_TYPE);
+		assert!(from_content_type("text/plain").is_none());
+	}
+}
@@ -0,0 +1,83 @@
+//! Handlers of calls to methods which are not registered explicitly.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::access;
+use crate::calls::{Metadata, RpcFallback, RpcMethod};
+use crate::types::{Params, Value};
+use crate::BoxFuture;
+
+/// Fallback handlers ordered from the most specific pattern.
+pub(crate) struct Fallbacks<T> {
+	patterns: Vec<(String, Arc<dyn RpcFallback<T>>)>,
+}
+
+impl<T: Metadata> Fallbacks<T> {
+	/// Registers a handler for given pattern, replacing previous handler of the same pattern.
+	///
+	/// Pattern is a method name, `*` (matching any method) or a prefix followed by `*`, e.g. `eth_*`.
+	/// Method names are matched first, then prefixes from the longest one.
+	pub(crate) fn insert(&mut self, pattern: &str, fallback: Arc<dyn RpcFallback<T>>) {
+		self.patterns.retain(|(existing, _)| existing != pattern);
+		self.patterns.push((pattern.to_owned(), fallback));
+		self.patterns
+			.sort_by_key(|(pattern, _)| (pattern.ends_with('*'), std::cmp::Reverse(pattern.len())));
+	}
+
+	/// Returns a handler for given method name, if any pattern matches.
+	pub(crate) fn find(&self, method: &str) -> Option<&Arc<dyn RpcFallback<T>>> {
+		self.patterns
+			.iter()
+			.find(|(pattern, _)| access::matches(pattern, method))
+			.map(|(_, fallback)| fallback)
+	}
+
+	/// Moves handlers from `other`, handlers already registered for the same patterns are replaced.
+	pub(crate) fn extend(&mut self, other: Fallbacks<T>) {
+		for (pattern, fallback) in other.patterns {
+			self.insert(&pattern, fallback);
+		}
+	}
+}
+
+impl<T> Default for Fallbacks<T> {
+	fn default() -> Self {
+		Fallbacks { patterns: Vec::new() }
+	}
+}
+
+impl<T> Clone for Fallbacks<T> {
+	fn clone(&self) -> Self {
+		Fallbacks {
+			patterns: self.patterns.clone(),
+		}
+	}
+}
+
+impl<T> fmt::Debug for Fallbacks<T> {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_list()
+			.entries(self.patterns.iter().map(|(pattern, _)| pattern))
+			.finish()
+	}
+}
+
+/// Exposes a fallback handler as a method with given name.
+pub(crate) fn method<T: Metadata>(name: &str, fallback: &Arc<dyn RpcFallback<T>>) -> Arc<dyn RpcMethod<T>> {
+	Arc::new(FallbackMethod {
+		name: name.to_owned(),
+		fallback: fallback.clone(),
+	})
+}
+
+struct FallbackMethod<T> {
+	name: String,
+	fallback: Arc<dyn RpcFallback<T>>,
+}
+
+impl<T: Metadata> RpcMethod<T> for FallbackMethod<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.fallback.call(self.name.clone(), params, meta)
+	}
+}
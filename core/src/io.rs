@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::collections::{
 	hash_map::{IntoIter, Iter},
 	HashMap,
@@ -6,15 +7,31 @@ use std::future::Future;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
-use futures_util::{self, future, FutureExt};
+use futures_util::{self, future, stream, FutureExt, StreamExt};
 
+use crate::access::{AccessControl, AccessPolicy};
 use crate::calls::{
-	Metadata, RemoteProcedure, RpcMethod, RpcMethodSimple, RpcMethodSync, RpcNotification, RpcNotificationSimple,
+	Metadata, RemoteProcedure, RpcFallback, RpcMethod, RpcMethodSimple, RpcMethodSync, RpcNotification,
+	RpcNotificationSimple,
 };
-use crate::middleware::{self, Middleware};
-use crate::types::{Call, Output, Request, Response};
-use crate::types::{Error, ErrorCode, Version};
+use crate::cancellation;
+use crate::deprecation;
+use crate::discover::{self, MethodDescription, OpenRpc};
+use crate::encoding::Encoding;
+use crate::fallback;
+use crate::limits::Limits;
+use crate::middleware::{self, CallKind, Middleware};
+use crate::panics::PanicHook;
+use crate::registry::SharedIoHandler;
+use crate::scheduler::{Priority, Scheduler};
+use crate::streaming::{self, RpcStreamMethod, StreamingResponse};
+use crate::timeout;
+use crate::types::raw::{AnyParams, RawCall, RawMethodCall, RawNotification, RawRequest};
+use crate::types::{Call, Id, MethodCall, Notification, Output, Params, Request, Response};
+use crate::types::{Error, ErrorCode, Value, Version};
+use crate::BoxFuture;
 
 /// A type representing middleware or RPC response before serialization.
 pub type FutureResponse = Pin<Box<dyn Future<Output = Option<Response>> + Send>>;
@@ -31,13 +48,13 @@ pub type FutureResult<F, G> = future::Map<
 /// A type representing a result of a single method call.
 pub type FutureRpcOutput<F> = future::Either<F, future::Either<FutureOutput, future::Ready<Option<Output>>>>;
 
+/// A type representing a result of a single method call, before middleware is applied.
+type CallOutput = future::Either<FutureOutput, future::Ready<Option<Output>>>;
+
 /// A type representing an optional `Response` for RPC `Request`.
 pub type FutureRpcResult<F, G> = future::Either<
 	F,
-	future::Either<
-		future::Map<FutureRpcOutput<G>, fn(Option<Output>) -> Option<Response>>,
-		future::Map<future::JoinAll<FutureRpcOutput<G>>, fn(Vec<Option<Output>>) -> Option<Response>>,
-	>,
+	future::Either<future::Map<FutureRpcOutput<G>, fn(Option<Output>) -> Option<Response>>, FutureResponse>,
 >;
 
 /// `IoHandler` json-rpc protocol compatibility
@@ -49,6 +66,11 @@ pub enum Compatibility {
 	V2,
 	/// Compatible with both
 	Both,
+	/// Compatible only with JSON-RPC 1.0, following it strictly
+	///
+	/// Calls with `null` id are notifications, params have to be positional
+	/// and responses have both `result` and `error` fields.
+	V1Strict,
 }
 
 impl Default for Compatibility {
@@ -61,7 +83,11 @@ impl Compatibility {
 	fn is_version_valid(self, version: Option<Version>) -> bool {
 		matches!(
 			(self, version),
-			(Compatibility::V1, None) | (Compatibility::V2, Some(Version::V2)) | (Compatibility::Both, _)
+			(Compatibility::V1, None)
+				| (Compatibility::V2, Some(Version::V2))
+				| (Compatibility::Both, _)
+				| (Compatibility::V1Strict, None)
+				| (Compatibility::V1Strict, Some(Version::V1))
 		)
 	}
 
@@ -69,6 +95,90 @@ impl Compatibility {
 		match self {
 			Compatibility::V1 => None,
 			Compatibility::V2 | Compatibility::Both => Some(Version::V2),
+			Compatibility::V1Strict => Some(Version::V1),
+		}
+	}
+
+	/// Fails with a parse error if a call is marked as JSON-RPC 1.0, unless compatible only with 1.0.
+	///
+	/// Other modes don't recognize `"jsonrpc": "1.0"`, the same as before `Version::V1` was added.
+	fn check_versions(self, mut versions: impl Iterator<Item = Option<Version>>) -> Result<(), Error> {
+		match self {
+			Compatibility::V1Strict => Ok(()),
+			_ if versions.any(|version| version == Some(Version::V1)) => Err(Error::parse_error()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Reads a call the way JSON-RPC 1.0 peers send it, if compatible only with 1.0.
+	///
+	/// Calls are marked with `Version::V1`, so they are answered in 1.0 format.
+	/// Other modes never answer in 1.0 format, calls marked with `Version::V1` are invalid there.
+	fn normalize_call(self, call: Call) -> Call {
+		match (self, call) {
+			(Compatibility::V1Strict, Call::MethodCall(call)) if self.is_version_valid(call.jsonrpc) => {
+				let jsonrpc = Some(Version::V1);
+				match call {
+					MethodCall {
+						params: Params::Map(_),
+						id,
+						..
+					} => Call::Invalid { id },
+					MethodCall {
+						method,
+						params,
+						id: Id::Null,
+						..
+					} => Call::Notification(Notification {
+						jsonrpc,
+						method,
+						params,
+					}),
+					call => Call::MethodCall(MethodCall { jsonrpc, ..call }),
+				}
+			}
+			(
+				_,
+				Call::MethodCall(MethodCall {
+					jsonrpc: Some(Version::V1),
+					id,
+					..
+				}),
+			) => Call::Invalid { id },
+			(_, call) => call,
+		}
+	}
+
+	/// Same as `normalize_call`, for calls with unparsed params.
+	fn normalize_raw_call(self, call: RawCall) -> RawCall {
+		match (self, call) {
+			(Compatibility::V1Strict, RawCall::MethodCall(call)) if self.is_version_valid(call.jsonrpc) => {
+				let jsonrpc = Some(Version::V1);
+				let named = matches!(call.params.get(), Some(params) if params.trim_start().starts_with('{'));
+				match call {
+					RawMethodCall { id, .. } if named => RawCall::Invalid { id },
+					RawMethodCall {
+						method,
+						params,
+						id: Id::Null,
+						..
+					} => RawCall::Notification(RawNotification {
+						jsonrpc,
+						method,
+						params,
+					}),
+					call => RawCall::MethodCall(RawMethodCall { jsonrpc, ..call }),
+				}
+			}
+			(
+				_,
+				RawCall::MethodCall(RawMethodCall {
+					jsonrpc: Some(Version::V1),
+					id,
+					..
+				}),
+			) => RawCall::Invalid { id },
+			(_, call) => call,
 		}
 	}
 }
@@ -76,11 +186,28 @@ impl Compatibility {
 /// Request handler
 ///
 /// By default compatible only with jsonrpc v2
+///
+/// Transports handle requests with `handle_request` (or `handle_request_streaming`), which runs the middleware.
+/// `handle_raw_request` is an opt-in alternative that skips parsing of params, but it **bypasses the middleware**
+/// completely, so it must not be used when the middleware enforces anything (authentication, rate limits, caching).
 #[derive(Clone, Debug)]
 pub struct MetaIoHandler<T: Metadata, S: Middleware<T> = middleware::Noop> {
 	middleware: S,
 	compatibility: Compatibility,
 	methods: HashMap<String, RemoteProcedure<T>>,
+	streams: HashMap<String, Arc<dyn RpcStreamMethod<T>>>,
+	discovery: Option<discover::Info>,
+	timeout: Option<Duration>,
+	method_timeouts: HashMap<String, Duration>,
+	in_flight: Arc<cancellation::InFlight>,
+	max_batch_size: Option<usize>,
+	batch_concurrency: Option<usize>,
+	fallbacks: fallback::Fallbacks<T>,
+	panic_hook: PanicHook,
+	access: AccessControl<T>,
+	scheduler: Scheduler,
+	limits: Limits,
+	shared: Option<SharedIoHandler<T, S>>,
 }
 
 impl<T: Metadata> Default for MetaIoHandler<T> {
@@ -114,6 +241,19 @@ impl<T: Metadata> MetaIoHandler<T> {
 			compatibility,
 			middleware: Default::default(),
 			methods: Default::default(),
+			streams: Default::default(),
+			discovery: None,
+			timeout: None,
+			method_timeouts: Default::default(),
+			in_flight: Default::default(),
+			max_batch_size: None,
+			batch_concurrency: None,
+			fallbacks: Default::default(),
+			panic_hook: Default::default(),
+			access: Default::default(),
+			scheduler: Default::default(),
+			limits: Default::default(),
+			shared: None,
 		}
 	}
 }
@@ -125,6 +265,19 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 			compatibility,
 			middleware,
 			methods: Default::default(),
+			streams: Default::default(),
+			discovery: None,
+			timeout: None,
+			method_timeouts: Default::default(),
+			in_flight: Default::default(),
+			max_batch_size: None,
+			batch_concurrency: None,
+			fallbacks: Default::default(),
+			panic_hook: Default::default(),
+			access: Default::default(),
+			scheduler: Default::default(),
+			limits: Default::default(),
+			shared: None,
 		}
 	}
 
@@ -134,12 +287,49 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 			compatibility: Default::default(),
 			middleware,
 			methods: Default::default(),
+			streams: Default::default(),
+			discovery: None,
+			timeout: None,
+			method_timeouts: Default::default(),
+			in_flight: Default::default(),
+			max_batch_size: None,
+			batch_concurrency: None,
+			fallbacks: Default::default(),
+			panic_hook: Default::default(),
+			access: Default::default(),
+			scheduler: Default::default(),
+			limits: Default::default(),
+			shared: None,
 		}
 	}
 
+	/// Creates new `MetaIoHandler` forwarding all requests to the current version of a shared handler.
+	pub(crate) fn shared(shared: SharedIoHandler<T, S>, middleware: S) -> Self {
+		MetaIoHandler {
+			shared: Some(shared),
+			..MetaIoHandler::new(Default::default(), middleware)
+		}
+	}
+
+	/// Returns the middleware of this handler.
+	pub(crate) fn middleware(&self) -> &S {
+		&self.middleware
+	}
+
+	/// Returns current version of the shared handler this one forwards requests to, if any.
+	fn shared_handler(&self) -> Option<Arc<Self>> {
+		self.shared.as_ref().map(SharedIoHandler::load)
+	}
+
+	/// Registers a procedure, replacing any previous one of the same name (including a streaming method).
+	fn insert_method(&mut self, name: String, procedure: RemoteProcedure<T>) {
+		self.streams.remove(&name);
+		self.methods.insert(name, procedure);
+	}
+
 	/// Adds an alias to a method.
 	pub fn add_alias(&mut self, alias: &str, other: &str) {
-		self.methods.insert(alias.into(), RemoteProcedure::Alias(other.into()));
+		self.insert_method(alias.into(), RemoteProcedure::Alias(other.into()));
 	}
 
 	/// Adds new supported synchronous method.
@@ -173,8 +363,7 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 	where
 		F: RpcMethod<T>,
 	{
-		self.methods
-			.insert(name.into(), RemoteProcedure::Method(Arc::new(method)));
+		self.insert_method(name.into(), RemoteProcedure::Method(Arc::new(method)));
 	}
 
 	/// Adds new supported notification with metadata support.
@@ -182,8 +371,220 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 	where
 		F: RpcNotification<T>,
 	{
-		self.methods
-			.insert(name.into(), RemoteProcedure::Notification(Arc::new(notification)));
+		self.insert_method(name.into(), RemoteProcedure::Notification(Arc::new(notification)));
+	}
+
+	/// Adds new supported method streaming its result, with metadata support.
+	///
+	/// Items of the stream are serialized as they are produced when the request is handled
+	/// with `handle_request_streaming`, otherwise they are collected into an array.
+	pub fn add_stream_method<F>(&mut self, name: &str, method: F)
+	where
+		F: RpcStreamMethod<T>,
+	{
+		let method: Arc<dyn RpcStreamMethod<T>> = Arc::new(method);
+		self.insert_method(name.into(), RemoteProcedure::Method(streaming::collect(method.clone())));
+		self.streams.insert(name.into(), method);
+	}
+
+	/// Adds a handler of calls to methods which are not registered explicitly.
+	///
+	/// The `pattern` is a method name, `*` matching all method names, or a prefix followed by `*`
+	/// (e.g. `eth_*`), as in `require_permission`. When several patterns match, a handler of the method name
+	/// is used first, then the handler with the longest prefix.
+	/// The handler receives notifications as well, its result is discarded in that case.
+	pub fn add_fallback<F>(&mut self, pattern: &str, fallback: F)
+	where
+		F: RpcFallback<T>,
+	{
+		self.fallbacks.insert(pattern, Arc::new(fallback));
+	}
+
+	/// Attaches a description to a previously added method or notification.
+	///
+	/// Descriptions are exposed via `rpc.discover` (see `enable_discovery`).
+	pub fn describe_method(&mut self, name: &str, description: MethodDescription) {
+		if let Some(procedure) = self.methods.get_mut(name) {
+			discover::describe(procedure, description);
+		}
+	}
+
+	/// Marks a previously added method, streaming method or notification as deprecated.
+	///
+	/// Calls are still executed, but log a warning (at most once a minute per method)
+	/// and the deprecation note is exposed via `rpc.discover`.
+	pub fn deprecate_method(&mut self, name: &str, note: &str) {
+		match (self.methods.get_mut(name), self.streams.get_mut(name)) {
+			(Some(procedure), Some(stream)) => deprecation::set_deprecated_stream(procedure, stream, name, note),
+			(Some(procedure), None) => deprecation::set_deprecated(procedure, name, note),
+			(None, _) => {}
+		}
+	}
+
+	/// Enables built-in `rpc.discover` method returning OpenRPC document
+	/// describing all methods registered in this handler.
+	///
+	/// A method explicitly registered as `rpc.discover` takes precedence.
+	pub fn enable_discovery(&mut self, info: discover::Info) {
+		self.discovery = Some(info);
+	}
+
+	/// Returns OpenRPC document describing all registered methods.
+	pub fn openrpc_document(&self, info: discover::Info) -> OpenRpc {
+		if let Some(handler) = self.shared_handler() {
+			return handler.openrpc_document(info);
+		}
+		OpenRpc::new(info, &self.methods)
+	}
+
+	/// Sets maximal time any method call is allowed to run.
+	///
+	/// Calls which do not complete in time resolve to `Error::request_timeout`.
+	pub fn set_timeout(&mut self, timeout: Duration) {
+		self.timeout = Some(timeout);
+	}
+
+	/// Sets maximal time calls to given method are allowed to run.
+	///
+	/// Overrides both the handler-wide timeout and the timeout the method was registered with.
+	pub fn set_method_timeout(&mut self, name: &str, timeout: Duration) {
+		self.method_timeouts.insert(name.into(), timeout);
+	}
+
+	/// Sets maximal number of calls in a single batch request.
+	///
+	/// Larger batches are rejected as a whole with `Error::batch_too_large`.
+	pub fn set_max_batch_size(&mut self, max_batch_size: usize) {
+		self.max_batch_size = Some(max_batch_size);
+	}
+
+	/// Sets maximal size of a request, in bytes.
+	///
+	/// Larger requests are rejected with `Error::request_too_large` before they are parsed.
+	/// Transports which frame requests themselves (e.g. http with `max_request_body_size`)
+	/// may have limits of their own. The tcp and ipc servers stop reading a larger request as soon as
+	/// the limit is exceeded and close the connection, the stdio server skips the rest of the line.
+	/// Those servers read the limit for every new connection (stdio once, when the server is built),
+	/// so changes made through `SharedIoHandler` don't affect connections already open.
+	pub fn set_max_request_size(&mut self, max_size: usize) {
+		self.limits.max_size = Some(max_size);
+	}
+
+	/// Returns maximal size of a request, in bytes, if set.
+	///
+	/// Stream transports use it to stop buffering oversized requests early.
+	pub fn max_request_size(&self) -> Option<usize> {
+		match self.shared_handler() {
+			Some(handler) => handler.max_request_size(),
+			None => self.limits.max_size,
+		}
+	}
+
+	/// Sets maximal nesting depth of arrays and objects in a request, including the request object itself.
+	///
+	/// Deeper requests are rejected with `Error::request_too_deep` before they are parsed.
+	pub fn set_max_depth(&mut self, max_depth: usize) {
+		self.limits.max_depth = Some(max_depth);
+	}
+
+	/// Sets maximal length of strings (including object keys) in a request, in bytes.
+	///
+	/// Requests with longer strings are rejected with `Error::string_too_long`.
+	/// Length of JSON text strings is counted before unescaping.
+	pub fn set_max_string_length(&mut self, max_length: usize) {
+		self.limits.max_string_length = Some(max_length);
+	}
+
+	/// Sets maximal number of positional params of a call.
+	///
+	/// Calls with more params fail with `Error::too_many_params`, notifications are dropped.
+	pub fn set_max_params(&mut self, max_params: usize) {
+		self.limits.max_params = Some(max_params);
+	}
+
+	/// Sets how many calls of a single batch request are executed concurrently.
+	///
+	/// By default all calls of a batch run concurrently. With a limit set, methods
+	/// are invoked only once a slot is free, so `1` executes the batch sequentially.
+	/// Responses are always returned in the order of calls.
+	pub fn set_batch_concurrency(&mut self, batch_concurrency: usize) {
+		assert!(batch_concurrency > 0, "Batch concurrency must be greater than zero.");
+		self.batch_concurrency = Some(batch_concurrency);
+	}
+
+	/// Sets maximal number of method calls executing concurrently.
+	///
+	/// Calls beyond the limit wait in a queue (see `set_max_queued`) and are started in order
+	/// of priority of their methods. The limit covers calls of all requests and transports
+	/// sharing this handler (or its clones), time spent in the queue counts towards call timeouts.
+	/// Calls of a batch with limited concurrency are queued only once they are started,
+	/// streaming calls hold their slot until the whole result is produced.
+	pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+		assert!(max_concurrency > 0, "Concurrency must be greater than zero.");
+		self.scheduler.set_max_concurrency(max_concurrency);
+	}
+
+	/// Sets maximal number of concurrently executing calls of a single method.
+	///
+	/// Calls waiting for this limit don't hold up calls of other methods.
+	pub fn set_method_concurrency(&mut self, name: &str, max_concurrency: usize) {
+		assert!(max_concurrency > 0, "Concurrency must be greater than zero.");
+		self.scheduler.set_method_concurrency(name, max_concurrency);
+	}
+
+	/// Sets maximal number of calls waiting for a free slot.
+	///
+	/// Calls arriving when the queue is full fail with `Error::server_overloaded`.
+	/// By default the queue is unbounded.
+	pub fn set_max_queued(&mut self, max_queued: usize) {
+		self.scheduler.set_max_queued(max_queued);
+	}
+
+	/// Sets priority of queued calls of a method, `Priority::Normal` by default.
+	pub fn set_method_priority(&mut self, name: &str, priority: Priority) {
+		self.scheduler.set_method_priority(name, priority);
+	}
+
+	/// Requires callers of methods matching `pattern` to have given permission.
+	///
+	/// The `pattern` is either a method name or a prefix followed by `*` (e.g. `admin_*`).
+	/// Permissions are checked by the policy set with `set_access_policy`, calls it denies
+	/// fail with `Error::access_denied` and notifications are dropped. Calls to aliases
+	/// require permissions of both the alias and the method it points to.
+	pub fn require_permission(&mut self, pattern: &str, permission: &str) {
+		self.access.require(pattern, permission);
+	}
+
+	/// Sets the policy deciding whether the caller (described by request metadata) has a permission.
+	///
+	/// Without a policy, all methods which require a permission are denied.
+	pub fn set_access_policy<P>(&mut self, policy: P)
+	where
+		P: AccessPolicy<T>,
+	{
+		self.access.set_policy(Arc::new(policy));
+	}
+
+	/// Sets a hook invoked with method name and payload of each panic caught in a handler.
+	///
+	/// Panicking methods always respond with `InternalError`, by default the panic is only logged.
+	pub fn set_panic_hook<F>(&mut self, hook: F)
+	where
+		F: Fn(&str, &(dyn Any + Send)) + Send + Sync + 'static,
+	{
+		self.panic_hook = PanicHook::new(hook);
+	}
+
+	/// Removes a method, notification or alias, returning it if it was registered.
+	pub fn remove_method(&mut self, name: &str) -> Option<RemoteProcedure<T>> {
+		self.streams.remove(name);
+		self.methods.remove(name)
+	}
+
+	/// Removes all methods, notifications and aliases with names starting with given prefix (e.g. a whole namespace).
+	pub fn remove_prefix(&mut self, prefix: &str) {
+		self.streams.retain(|name, _| !name.starts_with(prefix));
+		self.methods.retain(|name, _| !name.starts_with(prefix));
 	}
 
 	/// Extend this `MetaIoHandler` with methods defined elsewhere.
@@ -191,7 +592,9 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 	where
 		F: IntoIterator<Item = (String, RemoteProcedure<T>)>,
 	{
-		self.methods.extend(methods)
+		for (name, procedure) in methods {
+			self.insert_method(name, procedure);
+		}
 	}
 
 	/// Handle given request synchronously - will block until response is available.
@@ -202,17 +605,26 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 		futures_executor::block_on(self.handle_request(request, meta))
 	}
 
+	/// Handle given request synchronously, keeping params unparsed until they reach the method.
+	/// See `handle_raw_request` for details, the middleware is bypassed.
+	#[cfg(feature = "futures-executor")]
+	pub fn handle_raw_request_sync(&self, request: &str, meta: T) -> Option<String> {
+		futures_executor::block_on(self.handle_raw_request(request, meta))
+	}
+
 	/// Handle given request asynchronously.
 	pub fn handle_request(&self, request: &str, meta: T) -> FutureResult<S::Future, S::CallFuture> {
 		use self::future::Either::{Left, Right};
-		fn as_string(response: Option<Response>) -> Option<String> {
-			let res = response.map(write_response);
-			debug!(target: "rpc", "Response: {}.", res.as_ref().unwrap_or(&"None".to_string()));
-			res
+
+		if let Some(handler) = self.shared_handler() {
+			return handler.handle_request(request, meta);
 		}
 
 		trace!(target: "rpc", "Request: {}.", request);
-		let request = read_request(request);
+		let request = self
+			.limits
+			.check_json(request.as_bytes())
+			.and_then(|()| read_request(request, self.compatibility));
 		let result = match request {
 			Err(error) => Left(future::ready(Some(Response::from(
 				error,
@@ -221,102 +633,466 @@ impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
 			Ok(request) => Right(self.handle_rpc_request(request, meta)),
 		};
 
-		result.map(as_string)
+		result.map(response_as_string)
 	}
 
-	/// Handle deserialized RPC request.
-	pub fn handle_rpc_request(&self, request: Request, meta: T) -> FutureRpcResult<S::Future, S::CallFuture> {
-		use self::future::Either::{Left, Right};
+	/// Handle given request asynchronously, keeping params unparsed until they reach the method.
+	///
+	/// Params are deserialized only once, directly into the type expected by the method
+	/// (see `RpcMethod::call_raw`). None of the transports use it, it has to be called explicitly.
+	///
+	/// # Middleware
+	///
+	/// The middleware is **not** invoked for requests handled this way, neither `on_request` nor `on_call`.
+	/// Limits, access control, timeouts and scheduling still apply. Use `handle_request` if the middleware
+	/// has to see every request.
+	pub fn handle_raw_request(&self, request: &str, meta: T) -> BoxFuture<Option<String>> {
+		if let Some(handler) = self.shared_handler() {
+			return handler.handle_raw_request(request, meta);
+		}
+		trace!(target: "rpc", "Request: {}.", request);
+		let request = self
+			.limits
+			.check_json(request.as_bytes())
+			.and_then(|()| RawRequest::from_json(request))
+			.and_then(|request| {
+				let calls = match request {
+					RawRequest::Single(ref call) => std::slice::from_ref(call),
+					RawRequest::Batch(ref calls) => calls,
+				};
+				let versions = calls.iter().map(|call| match call {
+					RawCall::MethodCall(call) => call.jsonrpc,
+					RawCall::Notification(notification) => notification.jsonrpc,
+					RawCall::Invalid { .. } => None,
+				});
+				self.compatibility.check_versions(versions)?;
+				Ok(request)
+			});
+		let response: FutureResponse = match request {
+			Err(error) => Box::pin(future::ready(Some(Response::from(
+				error,
+				self.compatibility.default_version(),
+			)))),
+			Ok(RawRequest::Single(call)) => Box::pin(self.handle_raw_call(call, meta, false).map(output_as_response)),
+			Ok(RawRequest::Batch(calls)) => {
+				self.handle_batch(calls, move |call, lazy| self.handle_raw_call(call, meta.clone(), lazy))
+			}
+		};
+
+		Box::pin(response.map(response_as_string))
+	}
 
-		fn output_as_response(output: Option<Output>) -> Option<Response> {
-			output.map(Response::Single)
+	/// Handle given request asynchronously, streaming results of streaming methods.
+	///
+	/// A single call of a method added with `add_stream_method` is answered with
+	/// `StreamingResponse::Chunked`, serializing the result as its items are produced.
+	/// Such calls are scheduled, timed out and cancelled like other calls (a timeout or cancellation
+	/// ends the stream with an error), but they don't pass through the middleware.
+	/// Other requests are handled as with `handle_request` and answered with `StreamingResponse::Complete`.
+	pub fn handle_request_streaming(&self, request: &str, meta: T) -> BoxFuture<Option<StreamingResponse>> {
+		if let Some(handler) = self.shared_handler() {
+			return handler.handle_request_streaming(request, meta);
 		}
+		trace!(target: "rpc", "Request: {}.", request);
+		let complete = |response: Option<Response>| response_as_string(response).map(StreamingResponse::Complete);
+		let request = self
+			.limits
+			.check_json(request.as_bytes())
+			.and_then(|()| read_request(request, self.compatibility));
+		let call = match request {
+			Err(error) => {
+				let response = Response::from(error, self.compatibility.default_version());
+				return Box::pin(future::ready(complete(Some(response))));
+			}
+			Ok(Request::Single(call)) => match self.compatibility.normalize_call(call) {
+				Call::MethodCall(call) => call,
+				call => return Box::pin(self.handle_rpc_request(Request::Single(call), meta).map(complete)),
+			},
+			Ok(request) => return Box::pin(self.handle_rpc_request(request, meta).map(complete)),
+		};
 
-		fn outputs_as_batch(outs: Vec<Option<Output>>) -> Option<Response> {
-			let outs: Vec<_> = outs.into_iter().flatten().collect();
-			if outs.is_empty() {
-				None
-			} else {
-				Some(Response::Batch(outs))
+		let target = self.alias_target(&call.method).to_owned();
+		let method = match self.streams.get(&target) {
+			Some(method)
+				if self.compatibility.is_version_valid(call.jsonrpc)
+					&& self.access.is_allowed(&[&call.method, &target], &meta) =>
+			{
+				method.clone()
+			}
+			_ => {
+				let request = Request::Single(Call::MethodCall(call));
+				return Box::pin(self.handle_rpc_request(request, meta).map(complete));
 			}
+		};
+
+		let MethodCall {
+			method: name,
+			params,
+			id,
+			jsonrpc,
+		} = call;
+		let params = AnyParams::Parsed(params);
+		if let Err(error) = self.limits.check_params(&params) {
+			let response = Response::Single(Output::from(Err(error), id, jsonrpc));
+			return Box::pin(future::ready(complete(Some(response))));
+		}
+		let params = params.into_params().expect("Params are parsed already; qed");
+
+		// the same deadline and cancellation as of a call collecting the items
+		let timeout = self
+			.method_timeouts
+			.get(&target)
+			.copied()
+			.or_else(|| match self.methods.get(&target) {
+				Some(RemoteProcedure::Method(method)) => method.timeout(),
+				_ => None,
+			});
+		let timeout = timeout.or(self.timeout);
+		let mut meta = meta;
+		let cancellation = meta.cancellation_token().map(|session| {
+			let (token, registration) = self.in_flight.register(&session, id.clone());
+			meta.set_cancellation_token(token.clone());
+			(token, registration)
+		});
+
+		let panic_hook = self.panic_hook.clone();
+		let items = self.scheduler.schedule_stream(&target, move || {
+			panic_hook.isolate_stream(&name, || method.call(params, meta))
+		});
+		let items = match timeout {
+			Some(timeout) => timeout::with_stream_timeout(items, timeout),
+			None => items,
+		};
+		let items = match cancellation {
+			Some((token, registration)) => cancellation::with_stream_cancellation(items, token, registration),
+			None => items,
+		};
+		Box::pin(streaming::respond(items, id, jsonrpc).map(Some))
+	}
+
+	/// Handle given request encoded with given encoding asynchronously.
+	///
+	/// The response is encoded with the same encoding.
+	pub fn handle_encoded_request(
+		&self,
+		request: &[u8],
+		encoding: Arc<dyn Encoding>,
+		meta: T,
+	) -> BoxFuture<Option<Vec<u8>>> {
+		if let Some(handler) = self.shared_handler() {
+			return handler.handle_encoded_request(request, encoding, meta);
+		}
+		let request = if encoding.is_text() {
+			self.limits
+				.check_json(request)
+				.and_then(|()| encoding.decode_request(request))
+		} else {
+			self.limits.check_size(request.len()).and_then(|()| {
+				let request = encoding.decode_request(request)?;
+				self.limits.check_request(&request)?;
+				Ok(request)
+			})
+		};
+		let response: FutureResponse = match request {
+			Err(error) => Box::pin(future::ready(Some(Response::from(
+				error,
+				self.compatibility.default_version(),
+			)))),
+			Ok(request) => Box::pin(self.handle_rpc_request(request, meta)),
+		};
+
+		Box::pin(response.map(move |response| response.map(|response| encoding.encode_response(&response))))
+	}
+
+	/// Handle deserialized RPC request.
+	pub fn handle_rpc_request(&self, request: Request, meta: T) -> FutureRpcResult<S::Future, S::CallFuture> {
+		use self::future::Either::{Left, Right};
+
+		if let Some(handler) = self.shared_handler() {
+			return handler.handle_rpc_request(request, meta);
 		}
 
 		self.middleware
 			.on_request(request, meta, |request, meta| match request {
 				Request::Single(call) => Left(
-					self.handle_call(call, meta)
+					middleware::with_call_kind(CallKind::Single, || self.handle_call(call, meta))
 						.map(output_as_response as fn(Option<Output>) -> Option<Response>),
 				),
-				Request::Batch(calls) => {
-					let futures: Vec<_> = calls
-						.into_iter()
-						.map(move |call| self.handle_call(call, meta.clone()))
-						.collect();
-					Right(
-						future::join_all(futures).map(outputs_as_batch as fn(Vec<Option<Output>>) -> Option<Response>),
-					)
-				}
+				Request::Batch(calls) => Right(middleware::with_call_kind(CallKind::Batch, || {
+					self.handle_batch(calls, move |call, lazy| {
+						self.handle_call_inner(call, meta.clone(), lazy)
+					})
+				})),
 			})
 	}
 
 	/// Handle single call asynchronously.
 	pub fn handle_call(&self, call: Call, meta: T) -> FutureRpcOutput<S::CallFuture> {
-		use self::future::Either::{Left, Right};
-
-		self.middleware.on_call(call, meta, |call, meta| match call {
-			Call::MethodCall(method) => {
-				let params = method.params;
-				let id = method.id;
-				let jsonrpc = method.jsonrpc;
-				let valid_version = self.compatibility.is_version_valid(jsonrpc);
-
-				let call_method = |method: &Arc<dyn RpcMethod<T>>| method.call(params, meta);
-
-				let result = match (valid_version, self.methods.get(&method.method)) {
-					(false, _) => Err(Error::invalid_version()),
-					(true, Some(&RemoteProcedure::Method(ref method))) => Ok(call_method(method)),
-					(true, Some(&RemoteProcedure::Alias(ref alias))) => match self.methods.get(alias) {
-						Some(&RemoteProcedure::Method(ref method)) => Ok(call_method(method)),
-						_ => Err(Error::method_not_found()),
-					},
-					(true, _) => Err(Error::method_not_found()),
-				};
+		if let Some(handler) = self.shared_handler() {
+			return handler.handle_call(call, meta);
+		}
+		self.handle_call_inner(call, meta, false)
+	}
 
-				match result {
-					Ok(result) => Left(Box::pin(
-						result.then(move |result| future::ready(Some(Output::from(result, id, jsonrpc)))),
-					) as _),
-					Err(err) => Right(future::ready(Some(Output::from(Err(err), id, jsonrpc)))),
-				}
+	/// Handle calls of a batch request, respecting configured batch limits.
+	fn handle_batch<C, F, X>(&self, calls: Vec<C>, handle_call: F) -> FutureResponse
+	where
+		F: Fn(C, bool) -> X,
+		X: Future<Output = Option<Output>> + Send + 'static,
+	{
+		match (self.max_batch_size, self.batch_concurrency) {
+			(Some(max), _) if calls.len() > max => Box::pin(future::ready(Some(Response::from(
+				Error::batch_too_large(max),
+				self.compatibility.default_version(),
+			)))),
+			(_, None) => {
+				let futures: Vec<_> = calls.into_iter().map(|call| handle_call(call, false)).collect();
+				Box::pin(future::join_all(futures).map(outputs_as_batch))
 			}
-			Call::Notification(notification) => {
-				let params = notification.params;
-				let jsonrpc = notification.jsonrpc;
-				if !self.compatibility.is_version_valid(jsonrpc) {
-					return Right(future::ready(None));
-				}
+			(_, Some(concurrency)) => {
+				let futures: Vec<_> = calls.into_iter().map(|call| handle_call(call, true)).collect();
+				let outputs = stream::iter(futures).buffered(concurrency).collect::<Vec<_>>();
+				Box::pin(outputs.map(outputs_as_batch))
+			}
+		}
+	}
 
-				match self.methods.get(&notification.method) {
-					Some(&RemoteProcedure::Notification(ref notification)) => {
-						notification.execute(params, meta);
-					}
-					Some(&RemoteProcedure::Alias(ref alias)) => {
-						if let Some(&RemoteProcedure::Notification(ref notification)) = self.methods.get(alias) {
-							notification.execute(params, meta);
-						}
-					}
-					_ => {}
-				}
+	/// Handle single call, deferring the method invocation until the future is polled if `lazy` is set.
+	fn handle_call_inner(&self, call: Call, meta: T, lazy: bool) -> FutureRpcOutput<S::CallFuture> {
+		let call = self.compatibility.normalize_call(call);
+		// denied calls never reach the middleware, so e.g. cached results are not leaked
+		if let Err(output) = self.check_access(&call, &meta) {
+			return future::Either::Right(future::Either::Right(future::ready(output)));
+		}
+		let registered = match call {
+			Call::MethodCall(ref call) => self.is_registered(&call.method),
+			Call::Notification(ref notification) => self.is_registered(&notification.method),
+			Call::Invalid { .. } => false,
+		};
+		middleware::with_registered_call(registered, || self.handle_call_with_middleware(call, meta, lazy))
+	}
 
-				Right(future::ready(None))
-			}
-			Call::Invalid { id } => Right(future::ready(Some(Output::invalid_request(
+	fn handle_call_with_middleware(&self, call: Call, meta: T, lazy: bool) -> FutureRpcOutput<S::CallFuture> {
+		self.middleware.on_call(call, meta, |call, meta| match call {
+			Call::MethodCall(method) => self.call_method(
+				method.method,
+				AnyParams::Parsed(method.params),
+				method.id,
+				method.jsonrpc,
+				meta,
+				lazy,
+			),
+			Call::Notification(notification) => self.notify(
+				notification.method,
+				AnyParams::Parsed(notification.params),
+				notification.jsonrpc,
+				meta,
+			),
+			Call::Invalid { id } => future::Either::Right(future::ready(Some(Output::invalid_request(
 				id,
 				self.compatibility.default_version(),
 			)))),
 		})
 	}
 
+	/// Fails with the output to respond with if the caller has no permission to make given call.
+	fn check_access(&self, call: &Call, meta: &T) -> Result<(), Option<Output>> {
+		let (name, id, jsonrpc) = match call {
+			Call::MethodCall(call) => (&call.method, Some(&call.id), call.jsonrpc),
+			Call::Notification(notification) if notification.method != cancellation::CANCEL_REQUEST_METHOD => {
+				(&notification.method, None, notification.jsonrpc)
+			}
+			_ => return Ok(()),
+		};
+		if !self.compatibility.is_version_valid(jsonrpc)
+			|| self.access.is_allowed(&[name, self.alias_target(name)], meta)
+		{
+			return Ok(());
+		}
+		Err(id.map(|id| Output::from(Err(Error::access_denied()), id.clone(), jsonrpc)))
+	}
+
+	/// Handle single call with unparsed params.
+	fn handle_raw_call(&self, call: RawCall, meta: T, lazy: bool) -> CallOutput {
+		match self.compatibility.normalize_raw_call(call) {
+			RawCall::MethodCall(method) => self.call_method(
+				method.method,
+				AnyParams::Raw(method.params),
+				method.id,
+				method.jsonrpc,
+				meta,
+				lazy,
+			),
+			RawCall::Notification(notification) => self.notify(
+				notification.method,
+				AnyParams::Raw(notification.params),
+				notification.jsonrpc,
+				meta,
+			),
+			RawCall::Invalid { id } => future::Either::Right(future::ready(Some(Output::invalid_request(
+				id,
+				self.compatibility.default_version(),
+			)))),
+		}
+	}
+
+	fn call_method(
+		&self,
+		name: String,
+		params: AnyParams,
+		id: Id,
+		jsonrpc: Option<Version>,
+		meta: T,
+		lazy: bool,
+	) -> CallOutput {
+		use self::future::Either::{Left, Right};
+
+		if let Err(err) = self.limits.check_params(&params) {
+			return Right(future::ready(Some(Output::from(Err(err), id, jsonrpc))));
+		}
+
+		let valid_version = self.compatibility.is_version_valid(jsonrpc);
+		let allowed = self.access.is_allowed(&[&name, self.alias_target(&name)], &meta);
+
+		// Calls made within a cancellable session get their own token,
+		// so they can be aborted individually with `$/cancelRequest`.
+		let mut meta = meta;
+		let cancellation = meta.cancellation_token().map(|session| {
+			let (token, registration) = self.in_flight.register(&session, id.clone());
+			meta.set_cancellation_token(token.clone());
+			(token, registration)
+		});
+
+		let call_method = |name: &str, method: &Arc<dyn RpcMethod<T>>| {
+			let timeout = self
+				.method_timeouts
+				.get(name)
+				.copied()
+				.or_else(|| method.timeout())
+				.or(self.timeout);
+			let panic_hook = self.panic_hook.clone();
+			let method = method.clone();
+			let method_name = name.to_owned();
+			let run = move || {
+				panic_hook.isolate(
+					&method_name,
+					|| invoke(&*method, params, meta),
+					|| Err(Error::internal_error()),
+				)
+			};
+			// lazy calls take a slot (or a place in the queue) only once they are polled
+			let result = if lazy {
+				let scheduler = self.scheduler.clone();
+				let name = name.to_owned();
+				Box::pin(async move { scheduler.schedule(&name, run).await })
+			} else {
+				self.scheduler.schedule(name, run)
+			};
+			let result = match timeout {
+				Some(timeout) => timeout::with_timeout(result, timeout),
+				None => result,
+			};
+			match cancellation {
+				Some((token, registration)) => cancellation::with_cancellation(result, token, registration),
+				None => result,
+			}
+		};
+
+		let result = match (valid_version, self.methods.get(&name)) {
+			(false, _) => Err(Error::invalid_version()),
+			(true, _) if !allowed => Err(Error::access_denied()),
+			(true, Some(&RemoteProcedure::Method(ref method_impl))) => Ok(call_method(&name, method_impl)),
+			(true, Some(&RemoteProcedure::Alias(ref alias))) => match self.methods.get(alias) {
+				Some(&RemoteProcedure::Method(ref method)) => Ok(call_method(alias, method)),
+				_ => Err(Error::method_not_found()),
+			},
+			(true, None) if name == discover::DISCOVER_METHOD && self.discovery.is_some() => {
+				let info = self.discovery.clone().expect("Checked in the guard; qed");
+				let document = serde_json::to_value(self.openrpc_document(info));
+				Ok(Box::pin(future::ready(document.map_err(|_| Error::internal_error()))) as _)
+			}
+			(true, None) => match self.fallbacks.find(&name) {
+				Some(fallback) => Ok(call_method(&name, &fallback::method(&name, fallback))),
+				None => Err(Error::method_not_found()),
+			},
+			(true, _) => Err(Error::method_not_found()),
+		};
+
+		match result {
+			Ok(result) => {
+				Left(Box::pin(result.then(move |result| {
+					future::ready(Some(Output::from(result, id, jsonrpc)))
+				})))
+			}
+			Err(err) => Right(future::ready(Some(Output::from(Err(err), id, jsonrpc)))),
+		}
+	}
+
+	fn notify(&self, name: String, params: AnyParams, jsonrpc: Option<Version>, meta: T) -> CallOutput {
+		use self::future::Either::{Left, Right};
+
+		if !self.compatibility.is_version_valid(jsonrpc) || self.limits.check_params(&params).is_err() {
+			return Right(future::ready(None));
+		}
+
+		if name == cancellation::CANCEL_REQUEST_METHOD && !self.methods.contains_key(&name) {
+			let session = meta.cancellation_token();
+			if let (Some(session), Ok(cancel)) = (session, params.parse::<cancellation::CancelParams>()) {
+				self.in_flight.cancel(&session, cancel.id);
+			}
+			return Right(future::ready(None));
+		}
+
+		if !self.access.is_allowed(&[&name, self.alias_target(&name)], &meta) {
+			return Right(future::ready(None));
+		}
+
+		let notification = match self.methods.get(&name) {
+			Some(&RemoteProcedure::Notification(ref notification)) => Some(notification),
+			Some(&RemoteProcedure::Alias(ref alias)) => match self.methods.get(alias) {
+				Some(&RemoteProcedure::Notification(ref notification)) => Some(notification),
+				_ => None,
+			},
+			_ => None,
+		};
+
+		match (notification, params) {
+			(Some(notification), params) => self.panic_hook.catch(&name, || match params {
+				AnyParams::Parsed(params) => notification.execute(params, meta),
+				AnyParams::Raw(params) => notification.execute_raw(params, meta),
+			}),
+			(None, params) if !self.methods.contains_key(&name) => {
+				if let Some(fallback) = self.fallbacks.find(&name) {
+					if let Ok(params) = params.into_params() {
+						let result =
+							self.panic_hook
+								.isolate(&name, || fallback.notify(name.clone(), params, meta), || ());
+						return Left(Box::pin(result.map(|()| None)));
+					}
+				}
+			}
+			(None, _) => {}
+		}
+
+		Right(future::ready(None))
+	}
+
+	/// Returns `true` if given name is a procedure (or alias) registered on this handler.
+	fn is_registered(&self, name: &str) -> bool {
+		self.methods.contains_key(name)
+			|| self.streams.contains_key(name)
+			|| (name == discover::DISCOVER_METHOD && self.discovery.is_some())
+			|| name == cancellation::CANCEL_REQUEST_METHOD
+	}
+
+	/// Returns the name an alias points to, or the name itself if it's not an alias.
+	fn alias_target<'a>(&'a self, name: &'a str) -> &'a str {
+		match self.methods.get(name) {
+			Some(RemoteProcedure::Alias(alias)) => alias,
+			_ => name,
+		}
+	}
+
 	/// Returns an iterator visiting all methods in arbitrary order.
 	pub fn iter(&self) -> impl Iterator<Item = (&String, &RemoteProcedure<T>)> {
 		self.methods.iter()
@@ -370,19 +1146,30 @@ impl_io_handler_extension!(A, B, C, D, E, F, G, H, I, J, K, L,);
 
 impl<M: Metadata> IoHandlerExtension<M> for Vec<(String, RemoteProcedure<M>)> {
 	fn augment<S: Middleware<M>>(self, handler: &mut MetaIoHandler<M, S>) {
-		handler.methods.extend(self)
+		handler.extend_with(self)
 	}
 }
 
 impl<M: Metadata> IoHandlerExtension<M> for HashMap<String, RemoteProcedure<M>> {
 	fn augment<S: Middleware<M>>(self, handler: &mut MetaIoHandler<M, S>) {
-		handler.methods.extend(self)
+		handler.extend_with(self)
 	}
 }
 
+/// Adds methods of this handler to another one, together with their settings: timeouts, deprecations,
+/// required permissions, concurrency limits and priorities. Fallbacks are added as well.
+///
+/// Settings of the whole handler (the access policy, global concurrency limits) are taken over
+/// only if `handler` has none, others (middleware, discovery, default timeout, batch and request limits)
+/// are not.
 impl<M: Metadata, S2: Middleware<M>> IoHandlerExtension<M> for MetaIoHandler<M, S2> {
 	fn augment<S: Middleware<M>>(self, handler: &mut MetaIoHandler<M, S>) {
-		handler.methods.extend(self.methods)
+		handler.extend_with(self.methods);
+		handler.streams.extend(self.streams);
+		handler.method_timeouts.extend(self.method_timeouts);
+		handler.fallbacks.extend(self.fallbacks);
+		handler.access.extend(self.access);
+		handler.scheduler.extend(&self.scheduler);
 	}
 }
 
@@ -426,6 +1213,11 @@ impl<M: Metadata + Default> IoHandler<M> {
 		self.0.handle_request(request, M::default())
 	}
 
+	/// Handle given string request asynchronously, streaming results of streaming methods.
+	pub fn handle_request_streaming(&self, request: &str) -> BoxFuture<Option<StreamingResponse>> {
+		self.0.handle_request_streaming(request, M::default())
+	}
+
 	/// Handle deserialized RPC request asynchronously.
 	pub fn handle_rpc_request(&self, request: Request) -> FutureRpcResult<FutureResponse, FutureOutput> {
 		self.0.handle_rpc_request(request, M::default())
@@ -443,6 +1235,19 @@ impl<M: Metadata + Default> IoHandler<M> {
 	pub fn handle_request_sync(&self, request: &str) -> Option<String> {
 		self.0.handle_request_sync(request, M::default())
 	}
+
+	/// Handle given request asynchronously, keeping params unparsed until they reach the method.
+	/// Bypasses the middleware, see `MetaIoHandler::handle_raw_request`.
+	pub fn handle_raw_request(&self, request: &str) -> BoxFuture<Option<String>> {
+		self.0.handle_raw_request(request, M::default())
+	}
+
+	/// Handle given request synchronously, keeping params unparsed until they reach the method.
+	/// Bypasses the middleware, see `MetaIoHandler::handle_raw_request`.
+	#[cfg(feature = "futures-executor")]
+	pub fn handle_raw_request_sync(&self, request: &str) -> Option<String> {
+		self.0.handle_raw_request_sync(request, M::default())
+	}
 }
 
 impl<M: Metadata> Deref for IoHandler<M> {
@@ -467,12 +1272,50 @@ impl From<IoHandler> for MetaIoHandler<()> {
 
 impl<M: Metadata> IoHandlerExtension<M> for IoHandler<M> {
 	fn augment<S: Middleware<M>>(self, handler: &mut MetaIoHandler<M, S>) {
-		handler.methods.extend(self.0.methods)
+		self.0.augment(handler)
+	}
+}
+
+/// Invokes the method with either parsed or raw params.
+fn invoke<T: Metadata>(method: &dyn RpcMethod<T>, params: AnyParams, meta: T) -> BoxFuture<crate::Result<Value>> {
+	match params {
+		AnyParams::Parsed(params) => method.call(params, meta),
+		AnyParams::Raw(params) => method.call_raw(params, meta),
+	}
+}
+
+fn output_as_response(output: Option<Output>) -> Option<Response> {
+	output.map(Response::Single)
+}
+
+fn outputs_as_batch(outs: Vec<Option<Output>>) -> Option<Response> {
+	let outs: Vec<_> = outs.into_iter().flatten().collect();
+	if outs.is_empty() {
+		None
+	} else {
+		Some(Response::Batch(outs))
 	}
 }
 
-fn read_request(request_str: &str) -> Result<Request, Error> {
-	crate::serde_from_str(request_str).map_err(|_| Error::new(ErrorCode::ParseError))
+fn response_as_string(response: Option<Response>) -> Option<String> {
+	let res = response.map(write_response);
+	debug!(target: "rpc", "Response: {}.", res.as_ref().unwrap_or(&"None".to_string()));
+	res
+}
+
+fn read_request(request_str: &str, compatibility: Compatibility) -> Result<Request, Error> {
+	let request = crate::serde_from_str(request_str).map_err(|_| Error::new(ErrorCode::ParseError))?;
+	let calls = match request {
+		Request::Single(ref call) => std::slice::from_ref(call),
+		Request::Batch(ref calls) => calls,
+	};
+	let versions = calls.iter().map(|call| match call {
+		Call::MethodCall(call) => call.jsonrpc,
+		Call::Notification(notification) => notification.jsonrpc,
+		Call::Invalid { .. } => None,
+	});
+	compatibility.check_versions(versions)?;
+	Ok(request)
 }
 
 fn write_response(response: Response) -> String {
@@ -483,7 +1326,7 @@ fn write_response(response: Response) -> String {
 #[cfg(test)]
 mod tests {
 	use super::{Compatibility, IoHandler};
-	use crate::types::Value;
+	use crate::types::{Params, Value};
 
 	#[test]
 	fn test_io_handler() {
@@ -509,6 +1352,120 @@ mod tests {
 		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
 	}
 
+	#[test]
+	fn test_io_handler_1dot0_strict() {
+		use std::sync::atomic;
+		use std::sync::Arc;
+
+		let mut io = IoHandler::with_compatibility(Compatibility::V1Strict);
+		let called = Arc::new(atomic::AtomicBool::new(false));
+		let c = called.clone();
+		io.add_sync_method("say_hello", |_| Ok(Value::String("hello".to_string())));
+		io.add_notification("print", move |_| c.store(true, atomic::Ordering::SeqCst));
+
+		let request = r#"{"method": "say_hello", "params": [42, 23], "id": 1}"#;
+		let response = r#"{"result":"hello","error":null,"id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(
+			futures_executor::block_on(io.handle_raw_request(request)),
+			Some(response.to_string())
+		);
+
+		let request = r#"{"jsonrpc": "1.0", "method": "say_hi", "params": [], "id": "x"}"#;
+		let response = r#"{"result":null,"error":{"code":-32601,"message":"Method not found"},"id":"x"}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"method": "say_hello", "params": {"a": 1}, "id": 2}"#;
+		let response = r#"{"result":null,"error":{"code":-32600,"message":"Invalid request"},"id":2}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(
+			futures_executor::block_on(io.handle_raw_request(request)),
+			Some(response.to_string())
+		);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [], "id": 3}"#;
+		let response =
+			r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Unsupported JSON-RPC protocol version"},"id":3}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"method": "print", "params": [], "id": null}"#;
+		assert_eq!(io.handle_request_sync(request), None);
+		assert!(called.load(atomic::Ordering::SeqCst));
+	}
+
+	#[test]
+	fn test_1dot0_version_outside_strict_mode() {
+		use crate::types::{Call, Id, MethodCall, Request, Version};
+
+		let parse_error = r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+		for compatibility in [Compatibility::V2, Compatibility::Both] {
+			let mut io = IoHandler::with_compatibility(compatibility);
+			io.add_sync_method("say_hello", |_| Ok(Value::String("hello".to_string())));
+
+			let request = r#"{"jsonrpc": "1.0", "method": "say_hello", "params": [], "id": 1}"#;
+			assert_eq!(io.handle_request_sync(request), Some(parse_error.to_string()));
+			assert_eq!(io.handle_raw_request_sync(request), Some(parse_error.to_string()));
+
+			let request =
+				r#"[{"jsonrpc": "2.0", "method": "say_hello", "id": 1}, {"jsonrpc": "1.0", "method": "say_hello"}]"#;
+			assert_eq!(io.handle_request_sync(request), Some(parse_error.to_string()));
+
+			// calls marked as 1.0 are never answered in 1.0 format
+			let request = Request::Single(Call::MethodCall(MethodCall {
+				jsonrpc: Some(Version::V1),
+				method: "say_hello".into(),
+				params: Params::None,
+				id: Id::Num(2),
+			}));
+			let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":2}"#;
+			let output = futures_executor::block_on(io.handle_rpc_request(request))
+				.map(|response| serde_json::to_string(&response).unwrap());
+			assert_eq!(output, Some(response.to_string()));
+		}
+	}
+
+	#[test]
+	fn test_request_limits() {
+		let mut io = IoHandler::new();
+		io.add_sync_method("say_hello", |_| Ok(Value::String("hello".to_string())));
+		io.set_max_request_size(256);
+		io.set_max_depth(3);
+		io.set_max_string_length(16);
+		io.set_max_params(2);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [[42], "x"], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = format!(
+			r#"{{"jsonrpc": "2.0", "method": "say_hello", "id": 1}}{}"#,
+			" ".repeat(256)
+		);
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Request too large, at most 256 bytes are allowed"},"id":null}"#;
+		assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [[[42]]], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Request nested too deep, at most 3 levels are allowed"},"id":null}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(
+			futures_executor::block_on(io.handle_raw_request(request)),
+			Some(response.to_string())
+		);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": ["hello world, hello"], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"String too long, at most 16 bytes are allowed"},"id":null}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"[{"jsonrpc": "2.0", "method": "say_hello", "params": [1, 2, 3], "id": 1},
+			{"jsonrpc": "2.0", "method": "say_hello", "params": [1, 2], "id": 2}]"#;
+		let response = r#"[{"jsonrpc":"2.0","error":{"code":-32600,"message":"Too many params, at most 2 are allowed"},"id":1},{"jsonrpc":"2.0","result":"hello","id":2}]"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(
+			futures_executor::block_on(io.handle_raw_request(request)),
+			Some(response.to_string())
+		);
+	}
+
 	#[test]
 	fn test_async_io_handler() {
 		let mut io = IoHandler::new();
@@ -598,6 +1555,582 @@ mod tests {
 		assert_eq!(called.load(atomic::Ordering::SeqCst), true);
 	}
 
+	#[test]
+	fn test_discovery() {
+		use crate::discover::{ContentDescriptor, Info, MethodDescription};
+
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| async { Ok(Value::String("hello".to_string())) });
+		io.add_alias("say_hello_alias", "say_hello");
+		io.add_notification("ping", |_| {});
+		io.describe_method(
+			"say_hello",
+			MethodDescription {
+				summary: Some("Says hello".into()),
+				params: vec![ContentDescriptor::new("name", "Option<String>")],
+				result: Some(ContentDescriptor::new("greeting", "String")),
+				..Default::default()
+			},
+		);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "rpc.discover", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		io.enable_discovery(Info::new("test", "1.0.0"));
+		let response: crate::Response = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+		let expected = serde_json::json!({
+			"openrpc": "1.3.2",
+			"info": { "title": "test", "version": "1.0.0" },
+			"methods": [{
+				"name": "ping",
+				"params": [],
+				"x-notification": true
+			}, {
+				"name": "say_hello",
+				"summary": "Says hello",
+				"params": [{
+					"name": "name",
+					"required": false,
+					"schema": { "oneOf": [{ "type": "string" }, { "type": "null" }] }
+				}],
+				"result": { "name": "greeting", "required": true, "schema": { "type": "string" } },
+				"x-aliases": ["say_hello_alias"]
+			}]
+		});
+		assert_eq!(
+			response,
+			crate::Response::Single(crate::Output::from(
+				Ok(expected),
+				crate::Id::Num(1),
+				Some(crate::Version::V2)
+			))
+		);
+	}
+
+	#[test]
+	fn test_deprecated_methods() {
+		use crate::discover::{Info, MethodDescription};
+
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| async { Ok(Value::String("hello".to_string())) });
+		io.add_notification("ping", |_| {});
+		io.deprecate_method("say_hello", "Use `greet` instead");
+		io.describe_method("say_hello", MethodDescription::from_docs("Says hello"));
+		io.deprecate_method("ping", "Not needed anymore");
+		io.enable_discovery(Info::new("test", "1.0.0"));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(io.handle_raw_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "rpc.discover", "id": 1}"#;
+		let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+		let methods = &response["result"]["methods"];
+		assert_eq!(methods[0]["name"], "ping");
+		assert_eq!(methods[0]["deprecated"], true);
+		assert_eq!(methods[0]["x-deprecation"], "Not needed anymore");
+		assert_eq!(methods[1]["name"], "say_hello");
+		assert_eq!(methods[1]["summary"], "Says hello");
+		assert_eq!(methods[1]["deprecated"], true);
+		assert_eq!(methods[1]["x-deprecation"], "Use `greet` instead");
+	}
+
+	#[test]
+	fn test_timeouts() {
+		use crate::futures_util::future;
+		use std::time::Duration;
+
+		let mut io = IoHandler::new();
+		io.add_method("hang", |_| future::pending());
+		io.add_method("hang_longer", |_| future::pending());
+		io.add_method("say_hello", |_| async { Ok(Value::String("hello".to_string())) });
+		io.set_timeout(Duration::from_millis(10));
+		io.set_method_timeout("hang_longer", Duration::from_millis(50));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "hang", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32001,"message":"Request timed out"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "hang_longer", "id": 1}"#;
+		let start = std::time::Instant::now();
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert!(start.elapsed() >= Duration::from_millis(50));
+	}
+
+	#[test]
+	fn test_encoded_requests() {
+		use crate::encoding::{Encoding, Json};
+		use std::sync::Arc;
+
+		let mut io = IoHandler::new();
+		io.add_sync_method("say_hello", |_| Ok(Value::String("hello".into())));
+		let encoding: Arc<dyn Encoding> = Arc::new(Json);
+
+		let request = br#"{"jsonrpc": "2.0", "method": "say_hello", "params": [42, 23], "id": 1}"#;
+		let response = futures_executor::block_on(io.handle_encoded_request(request, encoding.clone(), ()));
+		assert_eq!(response, Some(br#"{"jsonrpc":"2.0","result":"hello","id":1}"#.to_vec()));
+
+		let response = futures_executor::block_on(io.handle_encoded_request(b"\xff", encoding, ()));
+		let error = br#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+		assert_eq!(response, Some(error.to_vec()));
+	}
+
+	#[test]
+	fn test_raw_requests() {
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |params: Params| async move {
+			let (name,): (String,) = params.parse()?;
+			Ok(Value::String(format!("hello {}", name)))
+		});
+		io.add_fallback("fallback_*", |method: String, params: Params, _meta: ()| async move {
+			Ok(Value::String(format!("{} {:?}", method, params)))
+		});
+
+		let request = r#"[
+			{"jsonrpc": "2.0", "method": "say_hello", "params": ["world"], "id": 1},
+			{"jsonrpc": "2.0", "method": "say_hello", "params": [5], "id": 2},
+			{"jsonrpc": "2.0", "method": "fallback_x", "params": [1], "id": 3},
+			{"jsonrpc": "2.0", "method": "say_hello", "params": ["nobody"]},
+			{"jsonrpc": "2.0", "method": "missing", "id": 4}
+		]"#;
+		assert_eq!(io.handle_raw_request_sync(request), io.handle_request_sync(request),);
+
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+		assert_eq!(io.handle_raw_request_sync("{"), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_fallbacks() {
+		use std::sync::{atomic, Arc};
+
+		let notified = Arc::new(atomic::AtomicBool::new(false));
+		let n = notified.clone();
+		let mut io = IoHandler::new();
+		io.add_method("eth_exact", |_| async { Ok(Value::String("exact".into())) });
+		io.add_fallback("*", move |method: String, _params, _meta| {
+			n.store(true, atomic::Ordering::SeqCst);
+			async move { Ok(Value::String(format!("any {}", method))) }
+		});
+		io.add_fallback("eth_*", |method: String, params: Params, _meta| async move {
+			let params: Value = params.into();
+			Ok(Value::String(format!("eth {} {}", method, params)))
+		});
+		io.add_fallback("eth_chainId", |_method: String, _params, _meta| async {
+			Ok(Value::String("chain".into()))
+		});
+
+		let request = r#"{"jsonrpc": "2.0", "method": "eth_chainId", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"chain","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "eth_chainIdX", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"eth eth_chainIdX null","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "eth_exact", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"exact","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "eth_call", "params": [1], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"eth eth_call [1]","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "net_version", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"any net_version","id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		notified.store(false, atomic::Ordering::SeqCst);
+
+		let request = r#"{"jsonrpc": "2.0", "method": "net_notify"}"#;
+		assert_eq!(io.handle_request_sync(request), None);
+		assert!(notified.load(atomic::Ordering::SeqCst));
+	}
+
+	#[test]
+	fn test_batch_limits() {
+		use std::sync::{Arc, Mutex};
+
+		let log = Arc::new(Mutex::new(Vec::new()));
+		let l = log.clone();
+		let mut io = IoHandler::new();
+		io.add_method("log", move |params: Params| {
+			let name: (String,) = params.parse().unwrap();
+			l.lock().unwrap().push(format!("start {}", name.0));
+			let l = l.clone();
+			async move {
+				l.lock().unwrap().push(format!("end {}", name.0));
+				Ok(Value::String(name.0))
+			}
+		});
+		let request = r#"[
+			{"jsonrpc": "2.0", "method": "log", "params": ["a"], "id": 1},
+			{"jsonrpc": "2.0", "method": "log", "params": ["b"], "id": 2}
+		]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":"a","id":1},{"jsonrpc":"2.0","result":"b","id":2}]"#;
+
+		// all calls are invoked at once by default
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(*log.lock().unwrap(), vec!["start a", "start b", "end a", "end b"]);
+		log.lock().unwrap().clear();
+
+		io.set_batch_concurrency(1);
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert_eq!(*log.lock().unwrap(), vec!["start a", "end a", "start b", "end b"]);
+		log.lock().unwrap().clear();
+
+		io.set_max_batch_size(1);
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Batch too large, at most 1 calls are allowed"},"id":null}"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		assert!(log.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_cancellation() {
+		use super::MetaIoHandler;
+		use crate::futures_util::future;
+		use crate::CancellationToken;
+		use std::sync::{atomic, Arc};
+
+		let cancelled = Arc::new(atomic::AtomicUsize::new(0));
+		let c = cancelled.clone();
+		let mut io = MetaIoHandler::<CancellationToken>::default();
+		io.add_method_with_meta("hang", move |_params, meta: CancellationToken| {
+			let c = c.clone();
+			meta.on_cancel(move || {
+				c.fetch_add(1, atomic::Ordering::SeqCst);
+			});
+			future::pending()
+		});
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32800,"message":"Request cancelled"},"id":1}"#;
+
+		// cancelled by the client
+		let session = CancellationToken::new();
+		let request = r#"{"jsonrpc": "2.0", "method": "hang", "id": 1}"#;
+		let call = io.handle_request(request, session.clone());
+		let request = r#"{"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 2}}"#;
+		assert_eq!(io.handle_request_sync(request, session.clone()), None);
+		assert_eq!(cancelled.load(atomic::Ordering::SeqCst), 0);
+		let request = r#"{"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 1}}"#;
+		assert_eq!(io.handle_request_sync(request, session.clone()), None);
+		assert_eq!(cancelled.load(atomic::Ordering::SeqCst), 1);
+		assert_eq!(futures::executor::block_on(call), Some(response.to_string()));
+		assert!(!session.is_cancelled());
+
+		// cancelled with the session
+		let request = r#"{"jsonrpc": "2.0", "method": "hang", "id": 1}"#;
+		let call = io.handle_request(request, session.clone());
+		session.cancel();
+		assert_eq!(cancelled.load(atomic::Ordering::SeqCst), 2);
+		assert_eq!(futures::executor::block_on(call), Some(response.to_string()));
+	}
+
+	#[test]
+	fn test_panic_isolation() {
+		use std::sync::{Arc, Mutex};
+
+		let panics = Arc::new(Mutex::new(Vec::new()));
+		let p = panics.clone();
+		let mut io = IoHandler::new();
+		io.add_sync_method("hello", |_| Ok(Value::String("world".into())));
+		io.add_sync_method("panic_sync", |_| -> crate::Result<Value> { panic!("sync") });
+		io.add_method("panic_async", |_| async { panic!("async {}", 1) });
+		io.add_notification("panic_notify", |_| panic!("notify"));
+		io.set_panic_hook(move |method, payload| {
+			let message = payload
+				.downcast_ref::<&str>()
+				.map(|message| message.to_string())
+				.or_else(|| payload.downcast_ref::<String>().cloned());
+			p.lock().unwrap().push((method.to_owned(), message.unwrap()));
+		});
+
+		let request = r#"[
+			{"jsonrpc": "2.0", "method": "panic_sync", "id": 1},
+			{"jsonrpc": "2.0", "method": "panic_async", "id": 2},
+			{"jsonrpc": "2.0", "method": "panic_notify"},
+			{"jsonrpc": "2.0", "method": "hello", "id": 3}
+		]"#;
+		let response = r#"[{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":1},{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":2},{"jsonrpc":"2.0","result":"world","id":3}]"#;
+
+		assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+		let mut panics = panics.lock().unwrap().clone();
+		panics.sort();
+		assert_eq!(
+			panics,
+			vec![
+				("panic_async".to_owned(), "async 1".to_owned()),
+				("panic_notify".to_owned(), "notify".to_owned()),
+				("panic_sync".to_owned(), "sync".to_owned()),
+			]
+		);
+	}
+
+	#[test]
+	fn test_streaming() {
+		use crate::streaming::StreamingResponse;
+		use futures_executor::block_on;
+		use futures_util::{stream, StreamExt};
+
+		let mut io = IoHandler::new();
+		io.add_stream_method("numbers", |params: Params, _| {
+			let (count,): (u64,) = params.parse().unwrap();
+			stream::iter((0..count).map(|i| Ok(Value::from(i))))
+		});
+		io.add_stream_method("failing", |params: Params, _| {
+			let (after,): (usize,) = params.parse().unwrap();
+			let items = vec![Ok(Value::from(1)), Ok(Value::from(2))].into_iter().take(after);
+			stream::iter(items.chain(Some(Err(crate::Error::internal_error()))))
+		});
+		io.add_alias("count", "numbers");
+
+		let chunks = |request: &str| match block_on(io.handle_request_streaming(request)) {
+			Some(StreamingResponse::Chunked(chunks)) => Ok(block_on(chunks.collect::<Vec<_>>())),
+			Some(StreamingResponse::Complete(response)) => Err(Some(response)),
+			None => Err(None),
+		};
+
+		let request = r#"{"jsonrpc": "2.0", "method": "count", "params": [3], "id": "a"}"#;
+		let response = r#"{"jsonrpc":"2.0","result":[0,1,2],"id":"a"}"#;
+		assert_eq!(
+			chunks(request),
+			Ok(vec![
+				Ok(r#"{"jsonrpc":"2.0","result":[0"#.to_owned()),
+				Ok(",1".to_owned()),
+				Ok(",2".to_owned()),
+				Ok(r#"],"id":"a"}"#.to_owned()),
+			])
+		);
+		assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+		// empty and failing streams are answered at once
+		let request = r#"{"jsonrpc": "2.0", "method": "numbers", "params": [0], "id": 1}"#;
+		assert_eq!(
+			chunks(request),
+			Err(Some(r#"{"jsonrpc":"2.0","result":[],"id":1}"#.to_owned()))
+		);
+		let request = r#"{"jsonrpc": "2.0", "method": "failing", "params": [0], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":1}"#;
+		assert_eq!(chunks(request), Err(Some(response.to_owned())));
+
+		// failures after the first item abort the response
+		let request = r#"{"jsonrpc": "2.0", "method": "failing", "params": [2], "id": 1}"#;
+		assert_eq!(
+			chunks(request),
+			Ok(vec![
+				Ok(r#"{"jsonrpc":"2.0","result":[1"#.to_owned()),
+				Ok(",2".to_owned()),
+				Err(crate::Error::internal_error()),
+			])
+		);
+		assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+		// batches are collected
+		let request = r#"[{"jsonrpc": "2.0", "method": "numbers", "params": [2], "id": 1}]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":[0,1],"id":1}]"#;
+		assert_eq!(chunks(request), Err(Some(response.to_owned())));
+		assert_eq!(chunks(r#"{"jsonrpc": "2.0", "method": "numbers"}"#), Err(None));
+
+		// limits apply to streaming methods as well
+		io.set_max_params(1);
+		let request = r#"{"jsonrpc": "2.0", "method": "numbers", "params": [2, 3], "id": 1}"#;
+		let response =
+			r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Too many params, at most 1 are allowed"},"id":1}"#;
+		match block_on(io.handle_request_streaming(request)) {
+			Some(StreamingResponse::Complete(complete)) => assert_eq!(complete, response),
+			other => panic!("Unexpected response: {:?}", other),
+		}
+
+		// deprecated streaming methods are still streamed
+		io.deprecate_method("numbers", "Use `count` instead");
+		let request = r#"{"jsonrpc": "2.0", "method": "numbers", "params": [1], "id": 1}"#;
+		match block_on(io.handle_request_streaming(request)) {
+			Some(StreamingResponse::Chunked(chunks)) => assert_eq!(
+				block_on(chunks.collect::<Vec<_>>()),
+				vec![
+					Ok(r#"{"jsonrpc":"2.0","result":[0"#.to_owned()),
+					Ok(r#"],"id":1}"#.to_owned()),
+				]
+			),
+			other => panic!("Unexpected response: {:?}", other),
+		}
+
+		// replaced streaming methods are not streamed anymore
+		io.add_sync_method("numbers", |_| Ok(Value::from("replaced")));
+		let request = r#"{"jsonrpc": "2.0", "method": "numbers", "params": [2], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"replaced","id":1}"#;
+		match block_on(io.handle_request_streaming(request)) {
+			Some(StreamingResponse::Complete(complete)) => assert_eq!(complete, response),
+			other => panic!("Unexpected response: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn test_streaming_timeouts_and_cancellation() {
+		use super::MetaIoHandler;
+		use crate::streaming::StreamingResponse;
+		use crate::CancellationToken;
+		use futures_executor::block_on;
+		use futures_util::{stream, StreamExt};
+		use std::time::Duration;
+
+		let mut io = MetaIoHandler::<CancellationToken>::default();
+		io.add_stream_method("hang", |_, _| {
+			stream::once(async { Ok(Value::from(1)) }).chain(stream::pending())
+		});
+		io.set_method_timeout("hang", Duration::from_millis(10));
+		let chunks = |response| match response {
+			Some(StreamingResponse::Chunked(chunks)) => block_on(chunks.collect::<Vec<_>>()),
+			other => panic!("Unexpected response: {:?}", other),
+		};
+
+		let request = r#"{"jsonrpc": "2.0", "method": "hang", "id": 1}"#;
+		let response = block_on(io.handle_request_streaming(request, Default::default()));
+		assert_eq!(
+			chunks(response),
+			vec![
+				Ok(r#"{"jsonrpc":"2.0","result":[1"#.to_owned()),
+				Err(crate::Error::request_timeout())
+			]
+		);
+
+		io.set_method_timeout("hang", Duration::from_secs(60));
+		let session = CancellationToken::new();
+		let response = block_on(io.handle_request_streaming(request, session.clone()));
+		session.cancel();
+		assert_eq!(
+			chunks(response),
+			vec![
+				Ok(r#"{"jsonrpc":"2.0","result":[1"#.to_owned()),
+				Err(crate::Error::request_cancelled())
+			]
+		);
+	}
+
+	#[test]
+	fn test_access_control() {
+		use super::MetaIoHandler;
+		use crate::Metadata;
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		use std::sync::Arc;
+
+		#[derive(Clone, Default)]
+		struct Meta(Vec<&'static str>);
+		impl Metadata for Meta {}
+
+		let notified = Arc::new(AtomicUsize::new(0));
+		let n = notified.clone();
+		let mut io = MetaIoHandler::<Meta>::default();
+		io.add_sync_method("safe_hello", |_| Ok(Value::String("hello".into())));
+		io.add_sync_method("admin_stop", |_| Ok(Value::Bool(true)));
+		io.add_notification("admin_notify", move |_| {
+			n.fetch_add(1, Ordering::SeqCst);
+		});
+		io.add_alias("stop", "admin_stop");
+		io.require_permission("admin_*", "admin");
+		io.require_permission("admin_stop", "stop");
+
+		let hello = r#"{"jsonrpc": "2.0", "method": "safe_hello", "id": 1}"#;
+		let stop = r#"{"jsonrpc": "2.0", "method": "admin_stop", "id": 1}"#;
+		let alias = r#"{"jsonrpc": "2.0", "method": "stop", "id": 1}"#;
+		let notify = r#"{"jsonrpc": "2.0", "method": "admin_notify"}"#;
+		let denied = Some(r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Access denied"},"id":1}"#.to_owned());
+		let stopped = Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.to_owned());
+
+		// without a policy protected methods are denied
+		assert_eq!(
+			io.handle_request_sync(hello, Meta::default()),
+			Some(r#"{"jsonrpc":"2.0","result":"hello","id":1}"#.to_owned())
+		);
+		assert_eq!(io.handle_request_sync(stop, Meta(vec!["admin", "stop"])), denied);
+
+		io.set_access_policy(|meta: &Meta, permission: &str| meta.0.contains(&permission));
+
+		assert_eq!(io.handle_request_sync(stop, Meta(vec!["admin"])), denied);
+		assert_eq!(io.handle_request_sync(alias, Meta(vec!["admin"])), denied);
+		assert_eq!(io.handle_request_sync(stop, Meta(vec!["admin", "stop"])), stopped);
+		assert_eq!(io.handle_request_sync(alias, Meta(vec!["admin", "stop"])), stopped);
+
+		assert_eq!(io.handle_request_sync(notify, Meta::default()), None);
+		assert_eq!(notified.load(Ordering::SeqCst), 0);
+		assert_eq!(io.handle_request_sync(notify, Meta(vec!["admin"])), None);
+		assert_eq!(notified.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn test_augmenting_keeps_settings() {
+		use super::{IoHandlerExtension, MetaIoHandler};
+		use crate::Metadata;
+
+		#[derive(Clone, Default)]
+		struct Meta(bool);
+		impl Metadata for Meta {}
+
+		let mut other = MetaIoHandler::<Meta>::default();
+		other.add_sync_method("admin_stop", |_| Ok(Value::Bool(true)));
+		other.add_method("busy", |_| futures_util::future::pending());
+		other.require_permission("admin_*", "admin");
+		other.set_access_policy(|meta: &Meta, _permission: &str| meta.0);
+		other.set_method_concurrency("busy", 1);
+		other.set_max_queued(0);
+
+		let mut io = MetaIoHandler::<Meta>::default();
+		other.augment(&mut io);
+
+		let stop = r#"{"jsonrpc": "2.0", "method": "admin_stop", "id": 1}"#;
+		let denied = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Access denied"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(stop, Meta(false)), Some(denied.to_owned()));
+		assert_eq!(
+			io.handle_request_sync(stop, Meta(true)),
+			Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.to_owned())
+		);
+
+		let busy = r#"{"jsonrpc": "2.0", "method": "busy", "id": 1}"#;
+		let overloaded = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Server is overloaded"},"id":1}"#;
+		let _running = io.handle_request(busy, Meta(true));
+		assert_eq!(io.handle_request_sync(busy, Meta(true)), Some(overloaded.to_owned()));
+	}
+
+	#[test]
+	fn test_scheduling() {
+		use crate::streaming::StreamingResponse;
+		use futures_executor::block_on;
+		use futures_util::stream;
+
+		let mut io = IoHandler::new();
+		io.add_sync_method("hello", |_| Ok(Value::String("world".into())));
+		io.add_stream_method("numbers", |_, _| stream::iter((0..3).map(|i| Ok(Value::from(i)))));
+		io.set_max_concurrency(1);
+		io.set_max_queued(0);
+		io.set_batch_concurrency(1);
+
+		// calls of a batch take a slot only once they are started
+		let request = r#"[
+			{"jsonrpc": "2.0", "method": "hello", "id": 1},
+			{"jsonrpc": "2.0", "method": "hello", "id": 2},
+			{"jsonrpc": "2.0", "method": "hello", "id": 3}
+		]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":"world","id":1},{"jsonrpc":"2.0","result":"world","id":2},{"jsonrpc":"2.0","result":"world","id":3}]"#;
+		assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+		// streaming calls hold their slot until the response is complete
+		let request = r#"{"jsonrpc": "2.0", "method": "numbers", "id": 1}"#;
+		let chunks = match block_on(io.handle_request_streaming(request)) {
+			Some(StreamingResponse::Chunked(chunks)) => chunks,
+			other => panic!("Unexpected response: {:?}", other),
+		};
+		let overloaded = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Server is overloaded"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request), Some(overloaded.to_owned()));
+		drop(chunks);
+		assert_eq!(
+			io.handle_request_sync(request),
+			Some(r#"{"jsonrpc":"2.0","result":[0,1,2],"id":1}"#.to_owned())
+		);
+	}
+
 	#[test]
 	fn test_send_sync() {
 		fn is_send_sync<T>(_obj: T) -> bool
@@ -15,6 +15,10 @@
 //!
 //! assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
 //! ```
+//!
+//! Requests can also be handled with `handle_raw_request`, which passes params to the methods unparsed.
+//! Note that it bypasses the middleware entirely, so it's only suitable for handlers whose middleware
+//! doesn't need to see every request.
 
 #![deny(missing_docs)]
 
@@ -35,12 +39,29 @@ pub use futures_util;
 pub extern crate serde;
 #[doc(hidden)]
 pub extern crate serde_json;
+#[cfg(feature = "schemars")]
+pub use schemars;
 
 mod calls;
+mod deprecation;
+mod fallback;
 mod io;
+mod limits;
+mod panics;
+mod timeout;
 
+pub mod access;
+pub mod cancellation;
 pub mod delegates;
+pub mod discover;
+pub mod encoding;
 pub mod middleware;
+pub mod registry;
+pub mod scheduler;
+pub mod state;
+pub mod streaming;
+#[cfg(feature = "tower")]
+pub mod tower;
 pub mod types;
 
 /// A Result type.
@@ -49,16 +70,19 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// A `Future` trait object.
 pub type BoxFuture<T> = Pin<Box<dyn std::future::Future<Output = T> + Send>>;
 
+pub use crate::access::AccessPolicy;
 pub use crate::calls::{
-	Metadata, RemoteProcedure, RpcMethod, RpcMethodSimple, RpcMethodSync, RpcNotification, RpcNotificationSimple,
-	WrapFuture,
+	Metadata, RemoteProcedure, RpcFallback, RpcMethod, RpcMethodSimple, RpcMethodSync, RpcNotification,
+	RpcNotificationSimple, WrapFuture,
 };
+pub use crate::cancellation::CancellationToken;
 pub use crate::delegates::IoDelegate;
 pub use crate::io::{
 	Compatibility, FutureOutput, FutureResponse, FutureResult, FutureRpcResult, IoHandler, IoHandlerExtension,
 	MetaIoHandler,
 };
 pub use crate::middleware::{Middleware, Noop as NoopMiddleware};
+pub use crate::scheduler::Priority;
 pub use crate::types::*;
 
 use serde_json::Error as SerdeError;
@@ -0,0 +1,207 @@
+//! Limits of request size and shape, enforced before requests are dispatched.
+
+use crate::types::raw::AnyParams;
+use crate::types::{Call, Error, Params, Request, Value};
+
+/// Limits configured on `MetaIoHandler`, all disabled by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Limits {
+	pub max_size: Option<usize>,
+	pub max_depth: Option<usize>,
+	pub max_string_length: Option<usize>,
+	pub max_params: Option<usize>,
+}
+
+impl Limits {
+	/// Checks size, nesting depth and length of strings of a JSON text request.
+	///
+	/// The text is scanned without parsing it, so that oversized requests are rejected cheaply.
+	/// Malformed JSON passes the check and fails to parse later on.
+	pub fn check_json(&self, request: &[u8]) -> Result<(), Error> {
+		self.check_size(request.len())?;
+		if self.max_depth.is_none() && self.max_string_length.is_none() {
+			return Ok(());
+		}
+
+		let max_depth = self.max_depth.unwrap_or(usize::MAX);
+		let max_string_length = self.max_string_length.unwrap_or(usize::MAX);
+		let mut depth = 0usize;
+		// length of the string being scanned, in bytes and with escapes, if inside of one
+		let mut string: Option<usize> = None;
+		let mut escaped = false;
+		for byte in request {
+			match (string, *byte) {
+				(Some(_), b'"') if !escaped => string = None,
+				(Some(length), _) if length >= max_string_length => {
+					return Err(Error::string_too_long(max_string_length))
+				}
+				(Some(length), byte) => {
+					escaped = !escaped && byte == b'\\';
+					string = Some(length + 1);
+				}
+				(None, b'"') => string = Some(0),
+				(None, b'[') | (None, b'{') if depth >= max_depth => return Err(Error::request_too_deep(max_depth)),
+				(None, b'[') | (None, b'{') => depth += 1,
+				(None, b']') | (None, b'}') => depth = depth.saturating_sub(1),
+				_ => {}
+			}
+		}
+		Ok(())
+	}
+
+	/// Checks nesting depth and length of strings of a decoded request.
+	///
+	/// Used for binary encodings, counts the levels the same way as `check_json`.
+	/// Strings are already unescaped, so they are measured as decoded.
+	pub fn check_request(&self, request: &Request) -> Result<(), Error> {
+		if self.max_depth.is_none() && self.max_string_length.is_none() {
+			return Ok(());
+		}
+
+		let (calls, depth) = match request {
+			Request::Single(call) => (std::slice::from_ref(call), 1),
+			Request::Batch(calls) => (&calls[..], 2),
+		};
+		calls
+			.iter()
+			.filter_map(|call| match call {
+				Call::MethodCall(call) => Some((&call.method, &call.params)),
+				Call::Notification(notification) => Some((&notification.method, &notification.params)),
+				Call::Invalid { .. } => None,
+			})
+			.try_for_each(|(method, params)| {
+				self.check_string(method)?;
+				match params {
+					Params::None => Ok(()),
+					Params::Array(values) => self.check_array(values, depth + 1),
+					Params::Map(map) => self.check_object(map, depth + 1),
+				}
+			})
+	}
+
+	/// Checks number of positional params of a call.
+	pub fn check_params(&self, params: &AnyParams) -> Result<(), Error> {
+		match self.max_params {
+			Some(max) if params.array_len().unwrap_or(0) > max => Err(Error::too_many_params(max)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Checks size of a request, in bytes.
+	pub fn check_size(&self, size: usize) -> Result<(), Error> {
+		match self.max_size {
+			Some(max) if size > max => Err(Error::request_too_large(max)),
+			_ => Ok(()),
+		}
+	}
+
+	fn check_string(&self, string: &str) -> Result<(), Error> {
+		match self.max_string_length {
+			Some(max) if string.len() > max => Err(Error::string_too_long(max)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Checks a value nested at given depth, with the request object at depth `1`.
+	fn check_value(&self, value: &Value, depth: usize) -> Result<(), Error> {
+		match value {
+			Value::String(string) => self.check_string(string),
+			Value::Array(values) => self.check_array(values, depth),
+			Value::Object(map) => self.check_object(map, depth),
+			_ => Ok(()),
+		}
+	}
+
+	fn check_array(&self, values: &[Value], depth: usize) -> Result<(), Error> {
+		self.check_depth(depth)?;
+		values.iter().try_for_each(|value| self.check_value(value, depth + 1))
+	}
+
+	fn check_object(&self, map: &serde_json::Map<String, Value>, depth: usize) -> Result<(), Error> {
+		self.check_depth(depth)?;
+		map.iter().try_for_each(|(key, value)| {
+			self.check_string(key)?;
+			self.check_value(value, depth + 1)
+		})
+	}
+
+	fn check_depth(&self, depth: usize) -> Result<(), Error> {
+		match self.max_depth {
+			Some(max) if depth > max => Err(Error::request_too_deep(max)),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn limits() -> Limits {
+		Limits {
+			max_size: Some(64),
+			max_depth: Some(3),
+			max_string_length: Some(8),
+			max_params: Some(2),
+		}
+	}
+
+	#[test]
+	fn should_check_json_text() {
+		let limits = limits();
+
+		assert_eq!(limits.check_json(br#"{"a":[{"b":"\"\"x"}]}"#), Ok(()));
+		assert_eq!(
+			limits.check_json(br#"{"a":[[{"b":1}]]}"#),
+			Err(Error::request_too_deep(3))
+		);
+		assert_eq!(limits.check_json(br#"{"a":"[[[[[["}"#), Ok(()));
+		assert_eq!(limits.check_json(br#"{"abcdefghi":1}"#), Err(Error::string_too_long(8)));
+		assert_eq!(limits.check_json(&[b' '; 65]), Err(Error::request_too_large(64)));
+	}
+
+	#[test]
+	fn should_count_escapes_in_json_strings() {
+		let limits = limits();
+
+		assert_eq!(limits.check_json(br#"{"a":"\\\\\\\\"}"#), Ok(()));
+		assert_eq!(
+			limits.check_json(br#"{"a":"\\\\\\\\\\"}"#),
+			Err(Error::string_too_long(8))
+		);
+		assert_eq!(
+			limits.check_json(br#"{"a":"\u0041\u0042"}"#),
+			Err(Error::string_too_long(8))
+		);
+		assert_eq!(limits.check_json(br#"{"a":"\"\"\"\"","b":1}"#), Ok(()));
+	}
+
+	#[test]
+	fn should_check_decoded_requests_the_same_way() {
+		let limits = limits();
+		let check = |json: &str| {
+			let request: Request = serde_json::from_str(json).unwrap();
+			let decoded = limits.check_request(&request);
+			assert_eq!(decoded, limits.check_json(json.as_bytes()), "{}", json);
+			decoded
+		};
+
+		assert_eq!(check(r#"{"id":1,"method":"m","params":[[1]]}"#), Ok(()));
+		assert_eq!(
+			check(r#"{"id":1,"method":"m","params":[[[1]]]}"#),
+			Err(Error::request_too_deep(3))
+		);
+		assert_eq!(
+			check(r#"[{"id":1,"method":"m","params":[[1]]}]"#),
+			Err(Error::request_too_deep(3))
+		);
+		assert_eq!(
+			check(r#"{"id":1,"method":"m","params":{"a":"abcdefghi"}}"#),
+			Err(Error::string_too_long(8))
+		);
+		let params = |json: &str| AnyParams::Parsed(serde_json::from_str(json).unwrap());
+		assert_eq!(limits.check_params(&params("[1,2]")), Ok(()));
+		assert_eq!(limits.check_params(&params(r#"{"a":1,"b":2,"c":3}"#)), Ok(()));
+		assert_eq!(limits.check_params(&params("[1,2,3]")), Err(Error::too_many_params(2)));
+	}
+}
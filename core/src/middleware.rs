@@ -3,9 +3,17 @@
 use crate::calls::Metadata;
 use crate::types::{Call, Output, Request, Response};
 use futures_util::future::Either;
+use std::cell::Cell;
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 
+pub mod cache;
+pub mod metrics;
+pub mod recording;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+
 /// RPC middleware
 pub trait Middleware<M: Metadata>: Send + Sync + 'static {
 	/// A returned request future.
@@ -142,6 +150,93 @@ impl<M: Metadata, A: Middleware<M>, B: Middleware<M>, C: Middleware<M>, D: Middl
 	}
 }
 
+/// Kind of request a call was part of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CallKind {
+	/// The call was the only one in the request.
+	Single,
+	/// The call was part of a batch request.
+	Batch,
+}
+
+impl CallKind {
+	/// Returns kind of calls in given request.
+	pub fn of(request: &Request) -> Self {
+		match request {
+			Request::Single(_) => CallKind::Single,
+			Request::Batch(_) => CallKind::Batch,
+		}
+	}
+
+	/// Returns kind of the request being dispatched.
+	///
+	/// Meant to be used in `Middleware::on_call`, calls made outside of a request are `Single`.
+	pub fn current() -> Self {
+		CALL_KIND.with(Cell::get).unwrap_or(CallKind::Single)
+	}
+
+	/// Returns the kind as a lowercase string.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			CallKind::Single => "single",
+			CallKind::Batch => "batch",
+		}
+	}
+}
+
+impl fmt::Display for CallKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// Returns `true` if the call being dispatched is made to a method or notification registered on the handler.
+///
+/// Meant to be used in `Middleware::on_call`, e.g. to keep names chosen by clients out of metric labels.
+/// Calls handled by fallbacks are not registered, calls made outside of a handler are.
+pub fn is_registered_call() -> bool {
+	REGISTERED_CALL.with(Cell::get).unwrap_or(true)
+}
+
+thread_local! {
+	static CALL_KIND: Cell<Option<CallKind>> = const { Cell::new(None) };
+	static REGISTERED_CALL: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Dispatches calls of a request (invoking `on_call` for each) with `CallKind::current` set.
+pub(crate) fn with_call_kind<R>(kind: CallKind, dispatch: impl FnOnce() -> R) -> R {
+	with_thread_local(&CALL_KIND, Some(kind), dispatch)
+}
+
+/// Dispatches a call (invoking `on_call`) with `is_registered_call` set.
+pub(crate) fn with_registered_call<R>(registered: bool, dispatch: impl FnOnce() -> R) -> R {
+	with_thread_local(&REGISTERED_CALL, Some(registered), dispatch)
+}
+
+/// Sets a thread-local for the duration of `dispatch`, restoring the previous value even if it panics.
+fn with_thread_local<T: Copy + 'static, R>(
+	key: &'static std::thread::LocalKey<Cell<T>>,
+	value: T,
+	dispatch: impl FnOnce() -> R,
+) -> R {
+	struct Restore<T: Copy + 'static> {
+		key: &'static std::thread::LocalKey<Cell<T>>,
+		previous: T,
+	}
+
+	impl<T: Copy + 'static> Drop for Restore<T> {
+		fn drop(&mut self) {
+			self.key.with(|current| current.set(self.previous));
+		}
+	}
+
+	let _restore = Restore {
+		key,
+		previous: key.with(|current| current.replace(value)),
+	};
+	dispatch()
+}
+
 #[inline(always)]
 fn repack<A, B, X>(result: Either<A, Either<B, X>>) -> Either<Either<A, B>, X> {
 	match result {
@@ -150,3 +245,16 @@ fn repack<A, B, X>(result: Either<A, Either<B, X>>) -> Either<Either<A, B>, X> {
 		Either::Right(Either::Right(x)) => Either::Right(x),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_restore_call_kind_after_panic() {
+		let result = std::panic::catch_unwind(|| with_call_kind(CallKind::Batch, || panic!("dispatch failed")));
+
+		assert!(result.is_err());
+		assert_eq!(CallKind::current(), CallKind::Single);
+	}
+}
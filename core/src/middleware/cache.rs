@@ -0,0 +1,397 @@
+//! Middleware caching responses of idempotent methods.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use jsonrpc_core::middleware::cache::CacheMiddleware;
+//! use jsonrpc_core::{MetaIoHandler, Value};
+//!
+//! let cache = CacheMiddleware::new(1024).cache_method("chain_constants", Duration::from_secs(60));
+//! let mut io = MetaIoHandler::<(), _>::with_middleware(cache.clone());
+//! io.add_sync_method("chain_constants", |_| Ok(Value::from(42)));
+//!
+//! let request = r#"{"jsonrpc": "2.0", "method": "chain_constants", "id": 1}"#;
+//! io.handle_request_sync(request, ());
+//! io.handle_request_sync(request, ());
+//!
+//! let stats = cache.stats();
+//! assert_eq!((stats.hits, stats.misses), (1, 1));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use futures_util::future::{self, Either, FutureExt};
+
+use super::{Middleware, NoopFuture};
+use crate::calls::Metadata;
+use crate::io::FutureOutput;
+use crate::types::{Call, Output, Params, Success, Value};
+
+/// Method name and canonicalized params.
+type Key = (String, String);
+
+/// Statistics of a cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+	/// Number of calls answered from the cache.
+	pub hits: u64,
+	/// Number of calls to cacheable methods which had to be executed.
+	pub misses: u64,
+	/// Number of responses currently held, including expired ones not evicted yet.
+	pub entries: usize,
+}
+
+/// Middleware answering calls to cacheable methods with results of earlier calls.
+///
+/// Calls are keyed by method name and params, with object keys sorted,
+/// so `{"a":1,"b":2}` and `{"b":2,"a":1}` share a response. Only successful results are cached.
+/// The least recently used responses are evicted once the cache is full.
+///
+/// Calls denied by access rules (see `MetaIoHandler::require_permission`) never reach the middleware,
+/// but cached responses are shared between all other callers regardless of metadata,
+/// so methods whose result depends on the caller should not be marked cacheable.
+///
+/// Clones share cached responses and statistics.
+#[derive(Clone, Debug)]
+pub struct CacheMiddleware {
+	ttls: Arc<HashMap<String, Duration>>,
+	cache: Arc<Mutex<Lru>>,
+}
+
+impl CacheMiddleware {
+	/// Creates new middleware holding at most `capacity` responses.
+	///
+	/// No methods are cached until marked with `cache_method`.
+	pub fn new(capacity: usize) -> Self {
+		CacheMiddleware {
+			ttls: Default::default(),
+			cache: Arc::new(Mutex::new(Lru::new(capacity))),
+		}
+	}
+
+	/// Marks a method as cacheable, its results are reused for `ttl`.
+	pub fn cache_method<N: Into<String>>(mut self, method: N, ttl: Duration) -> Self {
+		Arc::make_mut(&mut self.ttls).insert(method.into(), ttl);
+		self
+	}
+
+	/// Returns statistics of the cache.
+	pub fn stats(&self) -> CacheStats {
+		let cache = self.lock();
+		CacheStats {
+			hits: cache.hits,
+			misses: cache.misses,
+			entries: cache.entries.len(),
+		}
+	}
+
+	/// Removes all cached responses.
+	pub fn clear(&self) {
+		let mut cache = self.lock();
+		cache.entries.clear();
+		cache.recency.clear();
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Lru> {
+		self.cache.lock().expect("Cache is never poisoned; qed")
+	}
+}
+
+impl<M: Metadata> Middleware<M> for CacheMiddleware {
+	type Future = NoopFuture;
+	type CallFuture = FutureOutput;
+
+	fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+	where
+		F: Fn(Call, M) -> X + Send + Sync,
+		X: Future<Output = Option<Output>> + Send + 'static,
+	{
+		let cacheable = match call {
+			Call::MethodCall(ref call) => self
+				.ttls
+				.get(&call.method)
+				.map(|ttl| ((call.method.clone(), canonicalize_params(&call.params)), *ttl)),
+			_ => None,
+		};
+		let (key, ttl) = match cacheable {
+			Some(cacheable) => cacheable,
+			None => return Either::Right(next(call, meta)),
+		};
+
+		if let Some(result) = self.lock().get(&key, Instant::now()) {
+			let (jsonrpc, id) = match call {
+				Call::MethodCall(call) => (call.jsonrpc, call.id),
+				_ => unreachable!("Only method calls are cached; qed"),
+			};
+			let output = Output::Success(Success { jsonrpc, result, id });
+			return Either::Left(Box::pin(future::ready(Some(output))));
+		}
+
+		let cache = self.cache.clone();
+		Either::Left(Box::pin(next(call, meta).map(move |output| {
+			if let Some(Output::Success(ref success)) = output {
+				let expires = Instant::now() + ttl;
+				cache
+					.lock()
+					.expect("Cache is never poisoned; qed")
+					.insert(key, success.result.clone(), expires);
+			}
+			output
+		})))
+	}
+}
+
+#[derive(Debug)]
+struct Entry {
+	result: Value,
+	expires: Instant,
+	last_used: u64,
+}
+
+/// Least recently used cache of results.
+#[derive(Debug)]
+struct Lru {
+	capacity: usize,
+	entries: HashMap<Key, Entry>,
+	// keys by the tick they were last used at
+	recency: BTreeMap<u64, Key>,
+	tick: u64,
+	hits: u64,
+	misses: u64,
+}
+
+impl Lru {
+	fn new(capacity: usize) -> Self {
+		Lru {
+			capacity,
+			entries: Default::default(),
+			recency: Default::default(),
+			tick: 0,
+			hits: 0,
+			misses: 0,
+		}
+	}
+
+	fn get(&mut self, key: &Key, now: Instant) -> Option<Value> {
+		let expired = match self.entries.get_mut(key) {
+			Some(entry) if entry.expires > now => {
+				self.tick += 1;
+				self.recency.remove(&entry.last_used);
+				self.recency.insert(self.tick, key.clone());
+				entry.last_used = self.tick;
+				self.hits += 1;
+				return Some(entry.result.clone());
+			}
+			Some(_) => true,
+			None => false,
+		};
+
+		if expired {
+			self.remove(key);
+		}
+		self.misses += 1;
+		None
+	}
+
+	fn insert(&mut self, key: Key, result: Value, expires: Instant) {
+		if self.capacity == 0 {
+			return;
+		}
+		self.remove(&key);
+		while self.entries.len() >= self.capacity {
+			let oldest = match self.recency.keys().next() {
+				Some(tick) => *tick,
+				None => break,
+			};
+			if let Some(key) = self.recency.remove(&oldest) {
+				self.entries.remove(&key);
+			}
+		}
+
+		self.tick += 1;
+		self.recency.insert(self.tick, key.clone());
+		self.entries.insert(
+			key,
+			Entry {
+				result,
+				expires,
+				last_used: self.tick,
+			},
+		);
+	}
+
+	fn remove(&mut self, key: &Key) {
+		if let Some(entry) = self.entries.remove(key) {
+			self.recency.remove(&entry.last_used);
+		}
+	}
+}
+
+/// Serializes params with object keys sorted.
+fn canonicalize_params(params: &Params) -> String {
+	let mut out = String::new();
+	match params {
+		Params::None => {}
+		Params::Array(items) => canonicalize_array(items, &mut out),
+		Params::Map(map) => canonicalize_object(map, &mut out),
+	}
+	out
+}
+
+fn canonicalize(value: &Value, out: &mut String) {
+	match value {
+		Value::Array(items) => canonicalize_array(items, out),
+		Value::Object(map) => canonicalize_object(map, out),
+		value => out.push_str(&value.to_string()),
+	}
+}
+
+fn canonicalize_array(items: &[Value], out: &mut String) {
+	out.push('[');
+	for (i, item) in items.iter().enumerate() {
+		if i > 0 {
+			out.push(',');
+		}
+		canonicalize(item, out);
+	}
+	out.push(']');
+}
+
+fn canonicalize_object(map: &serde_json::Map<String, Value>, out: &mut String) {
+	let mut entries: Vec<_> = map.iter().collect();
+	entries.sort_by(|a, b| a.0.cmp(b.0));
+	out.push('{');
+	for (i, (key, value)) in entries.into_iter().enumerate() {
+		if i > 0 {
+			out.push(',');
+		}
+		out.push_str(&Value::String(key.clone()).to_string());
+		out.push(':');
+		canonicalize(value, out);
+	}
+	out.push('}');
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Error, MetaIoHandler};
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	fn io(cache: CacheMiddleware) -> (MetaIoHandler<(), CacheMiddleware>, Arc<AtomicUsize>) {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let mut io = MetaIoHandler::with_middleware(cache);
+		let counter = calls.clone();
+		io.add_sync_method("echo", move |params: Params| {
+			counter.fetch_add(1, Ordering::SeqCst);
+			Ok(Value::String(canonicalize_params(&params)))
+		});
+		let counter = calls.clone();
+		io.add_sync_method("fail", move |_| -> crate::Result<Value> {
+			counter.fetch_add(1, Ordering::SeqCst);
+			Err(Error::internal_error())
+		});
+		let counter = calls.clone();
+		io.add_sync_method("uncached", move |_| {
+			counter.fetch_add(1, Ordering::SeqCst);
+			Ok(Value::Null)
+		});
+		(io, calls)
+	}
+
+	fn call(io: &MetaIoHandler<(), CacheMiddleware>, method: &str, params: &str, id: u64) -> String {
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":{},"id":{}}}"#,
+			method, params, id
+		);
+		io.handle_request_sync(&request, ()).unwrap()
+	}
+
+	#[test]
+	fn should_answer_calls_with_equal_params_from_cache() {
+		let cache = CacheMiddleware::new(16)
+			.cache_method("echo", Duration::from_secs(60))
+			.cache_method("fail", Duration::from_secs(60));
+		let (io, calls) = io(cache.clone());
+
+		assert_eq!(
+			call(&io, "echo", r#"{"b":[1,{"d":2,"c":3}],"a":"x"}"#, 1),
+			r#"{"jsonrpc":"2.0","result":"{\"a\":\"x\",\"b\":[1,{\"c\":3,\"d\":2}]}","id":1}"#
+		);
+		assert_eq!(
+			call(&io, "echo", r#"{"a":"x","b":[1,{"c":3,"d":2}]}"#, 2),
+			r#"{"jsonrpc":"2.0","result":"{\"a\":\"x\",\"b\":[1,{\"c\":3,\"d\":2}]}","id":2}"#
+		);
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+		call(&io, "echo", r#"[1]"#, 3);
+		call(&io, "fail", r#"[]"#, 4);
+		call(&io, "fail", r#"[]"#, 5);
+		call(&io, "uncached", r#"[]"#, 6);
+		call(&io, "uncached", r#"[]"#, 7);
+		assert_eq!(calls.load(Ordering::SeqCst), 6);
+
+		assert_eq!(
+			cache.stats(),
+			CacheStats {
+				hits: 1,
+				misses: 4,
+				entries: 2,
+			}
+		);
+		cache.clear();
+		assert_eq!(cache.stats().entries, 0);
+	}
+
+	#[test]
+	fn should_not_answer_denied_calls_from_cache() {
+		#[derive(Clone, Default)]
+		struct Meta(bool);
+		impl Metadata for Meta {}
+
+		let cache = CacheMiddleware::new(16).cache_method("admin_secret", Duration::from_secs(60));
+		let mut io = MetaIoHandler::<Meta, _>::with_middleware(cache.clone());
+		io.add_sync_method("admin_secret", |_| Ok(Value::from(42)));
+		io.require_permission("admin_*", "admin");
+		io.set_access_policy(|meta: &Meta, _permission: &str| meta.0);
+
+		let request = r#"{"jsonrpc":"2.0","method":"admin_secret","id":1}"#;
+		let secret = r#"{"jsonrpc":"2.0","result":42,"id":1}"#;
+		let denied = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Access denied"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, Meta(true)), Some(secret.to_owned()));
+		assert_eq!(io.handle_request_sync(request, Meta(false)), Some(denied.to_owned()));
+		assert_eq!(cache.stats().hits, 0);
+	}
+
+	#[test]
+	fn should_not_reuse_expired_results() {
+		let cache = CacheMiddleware::new(16).cache_method("echo", Duration::from_secs(0));
+		let (io, calls) = io(cache.clone());
+
+		call(&io, "echo", r#"[]"#, 1);
+		call(&io, "echo", r#"[]"#, 2);
+
+		assert_eq!(calls.load(Ordering::SeqCst), 2);
+		assert_eq!(cache.stats().hits, 0);
+	}
+
+	#[test]
+	fn should_evict_least_recently_used_results() {
+		let cache = CacheMiddleware::new(2).cache_method("echo", Duration::from_secs(60));
+		let (io, calls) = io(cache.clone());
+
+		call(&io, "echo", r#"[1]"#, 1);
+		call(&io, "echo", r#"[2]"#, 2);
+		// makes `[2]` the least recently used
+		call(&io, "echo", r#"[1]"#, 3);
+		call(&io, "echo", r#"[3]"#, 4);
+		assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+		call(&io, "echo", r#"[1]"#, 5);
+		call(&io, "echo", r#"[2]"#, 6);
+		assert_eq!(calls.load(Ordering::SeqCst), 4);
+		assert_eq!(cache.stats().entries, 2);
+	}
+}
@@ -0,0 +1,327 @@
+//! Middleware collecting per-method call metrics.
+//!
+//! ```rust
+//! use jsonrpc_core::middleware::metrics::MetricsMiddleware;
+//! use jsonrpc_core::{MetaIoHandler, Value};
+//!
+//! let metrics = MetricsMiddleware::new();
+//! let mut io = MetaIoHandler::<(), _>::with_middleware(metrics.clone());
+//! io.add_sync_method("say_hello", |_| Ok(Value::String("Hello World!".into())));
+//!
+//! io.handle_request_sync(r#"{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#, ());
+//!
+//! let prometheus = metrics.prometheus();
+//! assert!(prometheus.contains(r#"jsonrpc_calls_total{method="say_hello",kind="single"} 1"#));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::future::{Either, FutureExt};
+
+use super::{CallKind, Middleware, NoopFuture};
+use crate::calls::Metadata;
+use crate::io::FutureOutput;
+use crate::types::{Call, ErrorCode, Output};
+
+/// Default upper bounds of call duration buckets, in seconds.
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Method label of calls to methods which are not registered (including ones handled by fallbacks).
+///
+/// Keeps the number of distinct labels bounded regardless of what clients send.
+pub const UNKNOWN_METHOD: &str = "<unknown>";
+
+/// Histogram of call durations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+	/// Upper bounds of buckets (in seconds) and number of observations not exceeding them.
+	pub buckets: Vec<(f64, u64)>,
+	/// Total duration of all observations, in seconds.
+	pub sum: f64,
+	/// Number of observations.
+	pub count: u64,
+}
+
+impl Histogram {
+	fn new(bounds: &[f64]) -> Self {
+		Histogram {
+			buckets: bounds.iter().map(|bound| (*bound, 0)).collect(),
+			sum: 0.0,
+			count: 0,
+		}
+	}
+
+	fn observe(&mut self, duration: Duration) {
+		let seconds = duration.as_secs_f64();
+		for (bound, count) in &mut self.buckets {
+			if seconds <= *bound {
+				*count += 1;
+			}
+		}
+		self.sum += seconds;
+		self.count += 1;
+	}
+}
+
+/// Metrics of calls to a single method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MethodMetrics {
+	/// Number of completed calls, including notifications.
+	pub calls: u64,
+	/// Number of failed calls by error code.
+	pub errors: BTreeMap<i64, u64>,
+	/// Durations of completed calls.
+	pub duration: Histogram,
+}
+
+/// Middleware counting calls, errors and call durations per method and `CallKind`.
+///
+/// Clones share collected metrics, so a clone can be kept
+/// to export metrics of the handler the middleware was moved into.
+#[derive(Clone, Debug)]
+pub struct MetricsMiddleware {
+	buckets: Arc<Vec<f64>>,
+	methods: Arc<Mutex<BTreeMap<(String, CallKind), MethodMetrics>>>,
+}
+
+impl Default for MetricsMiddleware {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MetricsMiddleware {
+	/// Creates new middleware with `DEFAULT_BUCKETS`.
+	pub fn new() -> Self {
+		Self::with_buckets(DEFAULT_BUCKETS.to_vec())
+	}
+
+	/// Creates new middleware with given upper bounds of duration buckets, in seconds.
+	pub fn with_buckets(mut buckets: Vec<f64>) -> Self {
+		buckets.sort_by(|a, b| a.partial_cmp(b).expect("Bucket bounds can't be NaN"));
+		MetricsMiddleware {
+			buckets: Arc::new(buckets),
+			methods: Default::default(),
+		}
+	}
+
+	/// Returns metrics collected so far, keyed by method name and kind of the request.
+	pub fn snapshot(&self) -> BTreeMap<(String, CallKind), MethodMetrics> {
+		self.methods.lock().expect("Metrics are never poisoned; qed").clone()
+	}
+
+	/// Returns metrics collected so far in Prometheus text format.
+	pub fn prometheus(&self) -> String {
+		let methods = self.snapshot();
+		let mut out = String::new();
+
+		// writing to a `String` never fails
+		let _ = writeln!(out, "# HELP jsonrpc_calls_total Number of completed calls.");
+		let _ = writeln!(out, "# TYPE jsonrpc_calls_total counter");
+		for ((method, kind), metrics) in &methods {
+			let labels = labels(method, *kind);
+			let _ = writeln!(out, "jsonrpc_calls_total{{{}}} {}", labels, metrics.calls);
+		}
+
+		let _ = writeln!(
+			out,
+			"# HELP jsonrpc_call_errors_total Number of failed calls by error code."
+		);
+		let _ = writeln!(out, "# TYPE jsonrpc_call_errors_total counter");
+		for ((method, kind), metrics) in &methods {
+			let labels = labels(method, *kind);
+			for (code, count) in &metrics.errors {
+				let _ = writeln!(
+					out,
+					"jsonrpc_call_errors_total{{{},code=\"{}\"}} {}",
+					labels, code, count
+				);
+			}
+		}
+
+		let _ = writeln!(out, "# HELP jsonrpc_call_duration_seconds Duration of completed calls.");
+		let _ = writeln!(out, "# TYPE jsonrpc_call_duration_seconds histogram");
+		for ((method, kind), metrics) in &methods {
+			let labels = labels(method, *kind);
+			let duration = &metrics.duration;
+			for (bound, count) in &duration.buckets {
+				let _ = writeln!(
+					out,
+					"jsonrpc_call_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+					labels, bound, count
+				);
+			}
+			let _ = writeln!(
+				out,
+				"jsonrpc_call_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+				labels, duration.count
+			);
+			let _ = writeln!(out, "jsonrpc_call_duration_seconds_sum{{{}}} {}", labels, duration.sum);
+			let _ = writeln!(
+				out,
+				"jsonrpc_call_duration_seconds_count{{{}}} {}",
+				labels, duration.count
+			);
+		}
+
+		out
+	}
+
+	fn record(&self, method: String, kind: CallKind, duration: Duration, error: Option<ErrorCode>) {
+		let mut methods = self.methods.lock().expect("Metrics are never poisoned; qed");
+		let metrics = methods.entry((method, kind)).or_insert_with(|| MethodMetrics {
+			calls: 0,
+			errors: Default::default(),
+			duration: Histogram::new(&self.buckets),
+		});
+		metrics.calls += 1;
+		if let Some(code) = error {
+			*metrics.errors.entry(code.code()).or_insert(0) += 1;
+		}
+		metrics.duration.observe(duration);
+	}
+}
+
+impl<M: Metadata> Middleware<M> for MetricsMiddleware {
+	type Future = NoopFuture;
+	type CallFuture = FutureOutput;
+
+	fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+	where
+		F: Fn(Call, M) -> X + Send + Sync,
+		X: Future<Output = Option<Output>> + Send + 'static,
+	{
+		let method = match call {
+			Call::Invalid { .. } => return Either::Right(next(call, meta)),
+			_ if !super::is_registered_call() => UNKNOWN_METHOD.to_owned(),
+			Call::MethodCall(ref call) => call.method.clone(),
+			Call::Notification(ref notification) => notification.method.clone(),
+		};
+		let kind = CallKind::current();
+		let start = Instant::now();
+		let metrics = self.clone();
+
+		Either::Left(Box::pin(next(call, meta).map(move |output| {
+			let error = match output {
+				Some(Output::Failure(ref failure)) => Some(failure.error.code.clone()),
+				_ => None,
+			};
+			metrics.record(method, kind, start.elapsed(), error);
+			output
+		})))
+	}
+}
+
+/// Formats labels of a method, escaping the method name.
+fn labels(method: &str, kind: CallKind) -> String {
+	let mut method_escaped = String::with_capacity(method.len());
+	for c in method.chars() {
+		match c {
+			'\\' => method_escaped.push_str("\\\\"),
+			'"' => method_escaped.push_str("\\\""),
+			'\n' => method_escaped.push_str("\\n"),
+			c => method_escaped.push(c),
+		}
+	}
+	format!("method=\"{}\",kind=\"{}\"", method_escaped, kind)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Error, MetaIoHandler, Params, Value};
+
+	fn io() -> (MetaIoHandler<(), MetricsMiddleware>, MetricsMiddleware) {
+		let metrics = MetricsMiddleware::with_buckets(vec![60.0, 0.0]);
+		let mut io = MetaIoHandler::with_middleware(metrics.clone());
+		io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into())));
+		io.add_sync_method("fail", |_: Params| -> crate::Result<Value> {
+			Err(Error::invalid_params("x"))
+		});
+		io.add_notification("notify", |_: Params| {});
+		(io, metrics)
+	}
+
+	#[test]
+	fn should_attribute_calls_to_single_and_batch_requests() {
+		let (io, metrics) = io();
+
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"hello","id":1}"#, ());
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"fail","id":1}"#, ());
+		io.handle_request_sync(
+			r#"[
+				{"jsonrpc":"2.0","method":"hello","id":1},
+				{"jsonrpc":"2.0","method":"hello","id":2},
+				{"jsonrpc":"2.0","method":"notify"},
+				{"jsonrpc":"2.0","method":"missing","id":3}
+			]"#,
+			(),
+		);
+
+		let snapshot = metrics.snapshot();
+		let calls = |method: &str, kind| snapshot.get(&(method.to_owned(), kind)).map(|m| m.calls);
+		assert_eq!(calls("hello", CallKind::Single), Some(1));
+		assert_eq!(calls("hello", CallKind::Batch), Some(2));
+		assert_eq!(calls("fail", CallKind::Single), Some(1));
+		assert_eq!(calls("notify", CallKind::Batch), Some(1));
+		assert_eq!(calls("missing", CallKind::Batch), None);
+		assert_eq!(calls(UNKNOWN_METHOD, CallKind::Batch), Some(1));
+
+		let hello = &snapshot[&("hello".to_owned(), CallKind::Batch)];
+		assert_eq!(hello.duration.buckets, vec![(0.0, 0), (60.0, 2)]);
+		assert_eq!(hello.duration.count, 2);
+		assert!(hello.errors.is_empty());
+		let fail = &snapshot[&("fail".to_owned(), CallKind::Single)];
+		assert_eq!(fail.errors.get(&-32602), Some(&1));
+	}
+
+	#[test]
+	fn should_not_label_calls_with_unregistered_names() {
+		let (mut io, metrics) = io();
+		io.set_max_params(1);
+
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"missing1"}"#, ());
+		io.handle_request_sync(r#"{"method":"missing2","id":1}"#, ());
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"missing3","params":[1,2],"id":1}"#, ());
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"hello","params":[1,2],"id":1}"#, ());
+
+		let snapshot = metrics.snapshot();
+		let methods: Vec<_> = snapshot.keys().map(|(method, _)| method.as_str()).collect();
+		assert_eq!(methods, vec![UNKNOWN_METHOD, "hello"]);
+		assert_eq!(snapshot[&(UNKNOWN_METHOD.to_owned(), CallKind::Single)].calls, 3);
+	}
+
+	#[test]
+	fn should_export_metrics_in_prometheus_format() {
+		let (io, metrics) = io();
+
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"fail","id":1}"#, ());
+
+		let prometheus = metrics.prometheus();
+		let lines: Vec<_> = prometheus.lines().filter(|line| !line.starts_with('#')).collect();
+		assert_eq!(
+			&lines[..5],
+			&[
+				r#"jsonrpc_calls_total{method="fail",kind="single"} 1"#,
+				r#"jsonrpc_call_errors_total{method="fail",kind="single",code="-32602"} 1"#,
+				r#"jsonrpc_call_duration_seconds_bucket{method="fail",kind="single",le="0"} 0"#,
+				r#"jsonrpc_call_duration_seconds_bucket{method="fail",kind="single",le="60"} 1"#,
+				r#"jsonrpc_call_duration_seconds_bucket{method="fail",kind="single",le="+Inf"} 1"#,
+			]
+		);
+		assert!(lines[6].starts_with(r#"jsonrpc_call_duration_seconds_count{method="fail",kind="single"} 1"#));
+		assert!(prometheus.contains("# TYPE jsonrpc_call_duration_seconds histogram"));
+	}
+
+	#[test]
+	fn should_escape_label_values() {
+		assert_eq!(
+			labels("a\"b\\c\n", CallKind::Batch),
+			r#"method="a\"b\\c\n",kind="batch""#
+		);
+	}
+}
@@ -0,0 +1,320 @@
+//! Middleware recording requests and responses for later replay.
+//!
+//! Each request is written as a single line of JSON (see `Record`),
+//! `jsonrpc-test` can replay such a log against a handler and report differing responses.
+//!
+//! Records are written by a background thread and flushed periodically, so recording
+//! doesn't block the requests. Use `RecordingMiddleware::flush` to make sure all records are written.
+//!
+//! ```rust
+//! use jsonrpc_core::middleware::recording::{Record, RecordingMiddleware};
+//! use jsonrpc_core::{MetaIoHandler, Metadata, Value};
+//! use std::sync::{Arc, Mutex};
+//!
+//! #[derive(Clone, Default)]
+//! struct Meta {
+//!     origin: String,
+//! }
+//! impl Metadata for Meta {}
+//!
+//! // e.g. `RecordingMiddleware::open("requests.jsonl")`
+//! let log = Arc::new(Mutex::new(Vec::new()));
+//! let recording = RecordingMiddleware::new(SharedLog(log.clone()))
+//!     .with_meta_summary(|meta: &Meta| Value::String(meta.origin.clone()));
+//! let mut io = MetaIoHandler::with_middleware(recording.clone());
+//! io.add_sync_method("say_hello", |_| Ok(Value::String("Hello World!".into())));
+//!
+//! let meta = Meta { origin: "dashboard".into() };
+//! io.handle_request_sync(r#"{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#, meta);
+//!
+//! recording.flush();
+//! let log = log.lock().unwrap();
+//! let record: Record = serde_json::from_slice(&log).unwrap();
+//! assert_eq!(record.meta, Some(Value::String("dashboard".into())));
+//!
+//! # struct SharedLog(Arc<Mutex<Vec<u8>>>);
+//! # impl std::io::Write for SharedLog {
+//! #     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.lock().unwrap().write(buf) }
+//! #     fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+//! # }
+//! ```
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::future::Future;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures_util::future::{Either, FutureExt};
+
+use super::{Middleware, NoopCallFuture};
+use crate::calls::Metadata;
+use crate::io::FutureResponse;
+use crate::types::{Request, Response, Value};
+
+/// A recorded request, serialized as a single line of the log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+	/// Time the request was received, in milliseconds since UNIX epoch.
+	pub timestamp_ms: u64,
+	/// Summary of request metadata, if the middleware has a summary function.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub meta: Option<Value>,
+	/// The request.
+	pub request: Request,
+	/// The response, `None` if the request consisted of notifications only.
+	pub response: Option<Response>,
+	/// Time it took to produce the response, in milliseconds.
+	pub duration_ms: f64,
+}
+
+type MetaSummary<M> = Arc<dyn Fn(&M) -> Value + Send + Sync>;
+
+/// Maximal time records stay buffered before they are flushed to the log.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+enum Command {
+	Write(Vec<u8>),
+	Flush(Sender<()>),
+}
+
+/// Middleware writing a `Record` of every request to a log, one JSON object per line.
+///
+/// Requests which can't be parsed never reach middlewares, so they are not recorded.
+/// Failures to write the log are logged and don't affect responses.
+///
+/// The log is written by a background thread, records are buffered and flushed at least
+/// once per `FLUSH_INTERVAL` (a second). The thread exits, flushing the remaining records,
+/// once the middleware and all its clones are dropped.
+///
+/// Clones write to the same log.
+pub struct RecordingMiddleware<M> {
+	log: Arc<Mutex<Sender<Command>>>,
+	meta_summary: Option<MetaSummary<M>>,
+}
+
+impl<M: Metadata> RecordingMiddleware<M> {
+	/// Creates new middleware writing records to given writer.
+	pub fn new<W: Write + Send + 'static>(log: W) -> Self {
+		let (sender, receiver) = mpsc::channel();
+		thread::Builder::new()
+			.name("jsonrpc-recording".into())
+			.spawn(move || write_records(BufWriter::new(log), receiver))
+			.expect("Failed to spawn the recording thread");
+		RecordingMiddleware {
+			log: Arc::new(Mutex::new(sender)),
+			meta_summary: None,
+		}
+	}
+
+	/// Creates new middleware appending records to a file, creating it if needed.
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(Self::new(file))
+	}
+
+	/// Records a summary of request metadata returned by given function.
+	///
+	/// Metadata often holds sessions or credentials, so it's up to the summary
+	/// to pick what's safe and useful to record.
+	pub fn with_meta_summary<F>(mut self, summary: F) -> Self
+	where
+		F: Fn(&M) -> Value + Send + Sync + 'static,
+	{
+		self.meta_summary = Some(Arc::new(summary));
+		self
+	}
+
+	/// Blocks until all records of completed requests are written and the log is flushed.
+	pub fn flush(&self) {
+		let (done, flushed) = mpsc::channel();
+		if self.send(Command::Flush(done)) {
+			// fails only if the writer thread panicked
+			let _ = flushed.recv();
+		}
+	}
+}
+
+impl<M> RecordingMiddleware<M> {
+	fn send(&self, command: Command) -> bool {
+		// the lock is held only to enqueue the command, the log is written by the writer thread
+		let log = self.log.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		log.send(command).is_ok()
+	}
+}
+
+impl<M: Metadata> Middleware<M> for RecordingMiddleware<M> {
+	type Future = FutureResponse;
+	type CallFuture = NoopCallFuture;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+	where
+		F: Fn(Request, M) -> X + Send + Sync,
+		X: Future<Output = Option<Response>> + Send + 'static,
+	{
+		let timestamp_ms = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|since_epoch| since_epoch.as_millis() as u64)
+			.unwrap_or(0);
+		let meta_summary = self.meta_summary.as_ref().map(|summary| summary(&meta));
+		let recorded = request.clone();
+		let recording = self.clone();
+		let start = Instant::now();
+
+		Either::Left(Box::pin(next(request, meta).map(move |response| {
+			let record = Record {
+				timestamp_ms,
+				meta: meta_summary,
+				request: recorded,
+				response: response.clone(),
+				duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+			};
+			match serde_json::to_vec(&record) {
+				Ok(mut line) => {
+					line.push(b'\n');
+					if !recording.send(Command::Write(line)) {
+						warn!(target: "rpc", "Failed to record request: the recording thread has stopped");
+					}
+				}
+				Err(err) => warn!(target: "rpc", "Failed to record request: {}", err),
+			}
+			response
+		})))
+	}
+}
+
+/// Writes records to the log until all senders are dropped.
+fn write_records<W: Write>(mut log: BufWriter<W>, commands: Receiver<Command>) {
+	let mut last_flush = Instant::now();
+	let mut pending = false;
+	loop {
+		let timeout = FLUSH_INTERVAL.checked_sub(last_flush.elapsed()).unwrap_or_default();
+		match commands.recv_timeout(timeout) {
+			Ok(Command::Write(line)) => {
+				if let Err(err) = log.write_all(&line) {
+					warn!(target: "rpc", "Failed to record request: {}", err);
+				}
+				pending = true;
+			}
+			Ok(Command::Flush(done)) => {
+				flush_log(&mut log);
+				pending = false;
+				last_flush = Instant::now();
+				let _ = done.send(());
+				continue;
+			}
+			Err(RecvTimeoutError::Timeout) => {}
+			Err(RecvTimeoutError::Disconnected) => {
+				flush_log(&mut log);
+				return;
+			}
+		}
+		if last_flush.elapsed() >= FLUSH_INTERVAL {
+			if pending {
+				flush_log(&mut log);
+				pending = false;
+			}
+			last_flush = Instant::now();
+		}
+	}
+}
+
+fn flush_log<W: Write>(log: &mut BufWriter<W>) {
+	if let Err(err) = log.flush() {
+		warn!(target: "rpc", "Failed to flush the recorded requests: {}", err);
+	}
+}
+
+impl<M> Clone for RecordingMiddleware<M> {
+	fn clone(&self) -> Self {
+		RecordingMiddleware {
+			log: self.log.clone(),
+			meta_summary: self.meta_summary.clone(),
+		}
+	}
+}
+
+impl<M> fmt::Debug for RecordingMiddleware<M> {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("RecordingMiddleware")
+			.field("meta_summary", &self.meta_summary.is_some())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MetaIoHandler, Params};
+
+	#[derive(Clone, Default)]
+	struct Log(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for Log {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn should_record_requests_and_responses() {
+		let log = Log::default();
+		let recording = RecordingMiddleware::new(log.clone()).with_meta_summary(|_: &()| Value::from("local"));
+		let mut io = MetaIoHandler::with_middleware(recording.clone());
+		io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into())));
+		io.add_notification("notify", |_: Params| {});
+
+		let request = r#"{"jsonrpc":"2.0","method":"hello","id":1}"#;
+		let notification = r#"{"jsonrpc":"2.0","method":"notify"}"#;
+		io.handle_request_sync(request, ());
+		io.handle_request_sync(notification, ());
+		io.handle_request_sync("{", ());
+		recording.flush();
+
+		let log = log.0.lock().unwrap();
+		let records: Vec<Record> = log
+			.split(|byte| *byte == b'\n')
+			.filter(|line| !line.is_empty())
+			.map(|line| serde_json::from_slice(line).unwrap())
+			.collect();
+		assert_eq!(records.len(), 2);
+		assert_eq!(records[0].meta, Some(Value::from("local")));
+		assert_eq!(records[0].request, serde_json::from_str(request).unwrap());
+		assert_eq!(
+			records[0].response,
+			Some(Response::from_json(r#"{"jsonrpc":"2.0","result":"world","id":1}"#).unwrap())
+		);
+		assert!(records[0].timestamp_ms > 0);
+		assert!(records[0].duration_ms >= 0.0);
+		assert_eq!(records[1].request, serde_json::from_str(notification).unwrap());
+		assert_eq!(records[1].response, None);
+	}
+
+	#[test]
+	fn should_flush_records_periodically() {
+		let log = Log::default();
+		let mut io = MetaIoHandler::with_middleware(RecordingMiddleware::new(log.clone()));
+		io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into())));
+
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"hello","id":1}"#, ());
+
+		let start = Instant::now();
+		while log.0.lock().unwrap().is_empty() {
+			assert!(start.elapsed() < FLUSH_INTERVAL * 5, "Records were never flushed");
+			thread::sleep(Duration::from_millis(10));
+		}
+		let record: Record = serde_json::from_slice(&log.0.lock().unwrap()).unwrap();
+		assert_eq!(
+			record.request,
+			serde_json::from_str(r#"{"jsonrpc":"2.0","method":"hello","id":1}"#).unwrap()
+		);
+	}
+}
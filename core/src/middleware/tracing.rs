@@ -0,0 +1,212 @@
+//! Middleware emitting `tracing` spans for requests and calls.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Instant;
+
+use ::tracing::{field, info_span, Instrument};
+use futures_util::future::{Either, FutureExt};
+
+use super::{CallKind, Middleware};
+use crate::calls::Metadata;
+use crate::io::{FutureOutput, FutureResponse};
+use crate::types::{Call, Id, Output, Request, Response};
+
+/// Middleware emitting `tracing` spans at `INFO` level.
+///
+/// Each request is processed within a `jsonrpc.request` span with `kind` and number of `calls`.
+/// Each call gets a child `jsonrpc.call` span with `method`, `id` (missing for notifications) and `kind`,
+/// recording `duration_ms`, `outcome` (`success`, `error` or `notification`) and `error_code` once it completes.
+#[derive(Clone, Debug, Default)]
+pub struct TracingMiddleware;
+
+impl<M: Metadata> Middleware<M> for TracingMiddleware {
+	type Future = FutureResponse;
+	type CallFuture = FutureOutput;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+	where
+		F: Fn(Request, M) -> X + Send + Sync,
+		X: Future<Output = Option<Response>> + Send + 'static,
+	{
+		let calls = match request {
+			Request::Single(_) => 1,
+			Request::Batch(ref calls) => calls.len(),
+		};
+		let span = info_span!("jsonrpc.request", kind = %CallKind::of(&request), calls);
+		let response = span.in_scope(|| next(request, meta));
+
+		Either::Left(Box::pin(response.instrument(span)))
+	}
+
+	fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+	where
+		F: Fn(Call, M) -> X + Send + Sync,
+		X: Future<Output = Option<Output>> + Send + 'static,
+	{
+		let span = match call {
+			Call::MethodCall(ref call) => info_span!(
+				"jsonrpc.call",
+				method = %call.method,
+				id = %DisplayId(&call.id),
+				kind = %CallKind::current(),
+				duration_ms = field::Empty,
+				outcome = field::Empty,
+				error_code = field::Empty,
+			),
+			Call::Notification(ref notification) => info_span!(
+				"jsonrpc.call",
+				method = %notification.method,
+				kind = %CallKind::current(),
+				duration_ms = field::Empty,
+				outcome = field::Empty,
+			),
+			Call::Invalid { .. } => return Either::Right(next(call, meta)),
+		};
+		let start = Instant::now();
+		let output = span.in_scope(|| next(call, meta));
+
+		let call_span = span.clone();
+		let output = output.map(move |output| {
+			call_span.record("duration_ms", start.elapsed().as_secs_f64() * 1000.0);
+			match output {
+				Some(Output::Success(_)) => {
+					call_span.record("outcome", "success");
+				}
+				Some(Output::Failure(ref failure)) => {
+					call_span.record("outcome", "error");
+					call_span.record("error_code", failure.error.code.code());
+				}
+				None => {
+					call_span.record("outcome", "notification");
+				}
+			}
+			output
+		});
+
+		Either::Left(Box::pin(output.instrument(span)))
+	}
+}
+
+/// Displays ids the way they are serialized, e.g. `1` or `"abc"`.
+struct DisplayId<'a>(&'a Id);
+
+impl fmt::Display for DisplayId<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.0 {
+			Id::Null => f.write_str("null"),
+			Id::Num(num) => write!(f, "{}", num),
+			Id::Str(s) => write!(f, "{:?}", s),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Error, MetaIoHandler, Params, Value};
+	use ::tracing::span::{Attributes, Id as SpanId, Record};
+	use ::tracing::{Event, Metadata as TracingMetadata, Subscriber};
+	use std::collections::BTreeMap;
+	use std::sync::{Arc, Mutex};
+
+	type Spans = Arc<Mutex<Vec<(&'static str, BTreeMap<&'static str, String>)>>>;
+
+	struct Fields<'a>(&'a mut BTreeMap<&'static str, String>);
+
+	impl field::Visit for Fields<'_> {
+		fn record_str(&mut self, field: &field::Field, value: &str) {
+			self.0.insert(field.name(), value.to_owned());
+		}
+
+		fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+			self.0.insert(field.name(), format!("{:?}", value));
+		}
+	}
+
+	#[derive(Default)]
+	struct Recorder(Spans);
+
+	impl Subscriber for Recorder {
+		fn enabled(&self, _: &TracingMetadata<'_>) -> bool {
+			true
+		}
+
+		fn new_span(&self, span: &Attributes<'_>) -> SpanId {
+			let mut spans = self.0.lock().unwrap();
+			let mut fields = BTreeMap::new();
+			span.record(&mut Fields(&mut fields));
+			spans.push((span.metadata().name(), fields));
+			SpanId::from_u64(spans.len() as u64)
+		}
+
+		fn record(&self, span: &SpanId, values: &Record<'_>) {
+			let mut spans = self.0.lock().unwrap();
+			values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1].1));
+		}
+
+		fn record_follows_from(&self, _: &SpanId, _: &SpanId) {}
+		fn event(&self, _: &Event<'_>) {}
+		fn enter(&self, _: &SpanId) {}
+		fn exit(&self, _: &SpanId) {}
+	}
+
+	#[test]
+	fn should_emit_spans_for_requests_and_calls() {
+		let mut io = MetaIoHandler::<(), _>::with_middleware(TracingMiddleware);
+		io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into())));
+		io.add_sync_method("fail", |_: Params| -> crate::Result<Value> {
+			Err(Error::internal_error())
+		});
+		let recorder = Recorder::default();
+		let spans = recorder.0.clone();
+
+		::tracing::subscriber::with_default(recorder, || {
+			io.handle_request_sync(
+				r#"[{"jsonrpc":"2.0","method":"hello","id":"a"},{"jsonrpc":"2.0","method":"fail","id":2}]"#,
+				(),
+			)
+		});
+
+		let mut spans = spans.lock().unwrap().clone();
+		for (_, fields) in &mut spans {
+			if let Some(duration) = fields.remove("duration_ms") {
+				assert!(duration.parse::<f64>().is_ok());
+			}
+		}
+		let span = |name, fields: &[(&'static str, &str)]| {
+			(
+				name,
+				fields
+					.iter()
+					.map(|(key, value)| (*key, value.to_string()))
+					.collect::<BTreeMap<_, _>>(),
+			)
+		};
+		assert_eq!(
+			spans,
+			vec![
+				span("jsonrpc.request", &[("kind", "batch"), ("calls", "2")]),
+				span(
+					"jsonrpc.call",
+					&[
+						("method", "hello"),
+						("id", "\"a\""),
+						("kind", "batch"),
+						("outcome", "success")
+					]
+				),
+				span(
+					"jsonrpc.call",
+					&[
+						("method", "fail"),
+						("id", "2"),
+						("kind", "batch"),
+						("outcome", "error"),
+						("error_code", "-32603")
+					]
+				),
+			]
+		);
+	}
+}
@@ -0,0 +1,117 @@
+//! Isolation of panics in method and notification handlers.
+
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use futures_util::future::{self, FutureExt};
+use futures_util::stream::{self, StreamExt};
+
+use crate::streaming::ResultStream;
+use crate::types::Error;
+use crate::BoxFuture;
+
+type Hook = Arc<dyn Fn(&str, &(dyn Any + Send)) + Send + Sync>;
+
+/// Reports panics caught in handlers, logs them by default.
+#[derive(Clone, Default)]
+pub(crate) struct PanicHook {
+	hook: Option<Hook>,
+}
+
+impl PanicHook {
+	/// Creates a hook invoked with method name and panic payload.
+	pub(crate) fn new<F>(hook: F) -> Self
+	where
+		F: Fn(&str, &(dyn Any + Send)) + Send + Sync + 'static,
+	{
+		PanicHook {
+			hook: Some(Arc::new(hook)),
+		}
+	}
+
+	fn report(&self, method: &str, payload: &(dyn Any + Send)) {
+		match self.hook {
+			Some(ref hook) => hook(method, payload),
+			None => error!(target: "rpc", "Method {} panicked: {}", method, message(payload)),
+		}
+	}
+
+	/// Invokes a synchronous handler, catching its panics.
+	pub(crate) fn catch<F: FnOnce()>(&self, method: &str, call: F) {
+		if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(call)) {
+			self.report(method, &*payload);
+		}
+	}
+
+	/// Invokes a handler, catching panics both when it's called and when its future is polled.
+	///
+	/// Caught panics are reported and the future resolves to `on_panic()` instead.
+	pub(crate) fn isolate<F, R>(&self, method: &str, call: F, on_panic: fn() -> R) -> BoxFuture<R>
+	where
+		F: FnOnce() -> BoxFuture<R>,
+		R: Send + 'static,
+	{
+		match panic::catch_unwind(AssertUnwindSafe(call)) {
+			Ok(result) => {
+				let hook = self.clone();
+				let method = method.to_owned();
+				Box::pin(AssertUnwindSafe(result).catch_unwind().map(move |result| {
+					result.unwrap_or_else(|payload| {
+						hook.report(&method, &*payload);
+						on_panic()
+					})
+				}))
+			}
+			Err(payload) => {
+				self.report(method, &*payload);
+				Box::pin(future::ready(on_panic()))
+			}
+		}
+	}
+
+	/// Invokes a streaming handler, catching its panics both when it's called and when the stream is polled.
+	///
+	/// Caught panics are reported and the stream ends with an `InternalError`.
+	pub(crate) fn isolate_stream<F>(&self, method: &str, call: F) -> ResultStream
+	where
+		F: FnOnce() -> ResultStream,
+	{
+		match panic::catch_unwind(AssertUnwindSafe(call)) {
+			Ok(items) => {
+				let hook = self.clone();
+				let method = method.to_owned();
+				Box::pin(AssertUnwindSafe(items).catch_unwind().map(move |item| {
+					item.unwrap_or_else(|payload| {
+						hook.report(&method, &*payload);
+						Err(Error::internal_error())
+					})
+				}))
+			}
+			Err(payload) => {
+				self.report(method, &*payload);
+				Box::pin(stream::once(future::ready(Err(Error::internal_error()))))
+			}
+		}
+	}
+}
+
+impl fmt::Debug for PanicHook {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("PanicHook")
+			.field("custom", &self.hook.is_some())
+			.finish()
+	}
+}
+
+/// Extracts a message from payload of `panic!`.
+fn message(payload: &(dyn Any + Send)) -> &str {
+	match payload.downcast_ref::<&'static str>() {
+		Some(message) => message,
+		None => payload
+			.downcast_ref::<String>()
+			.map(String::as_str)
+			.unwrap_or("Box<dyn Any>"),
+	}
+}
@@ -0,0 +1,160 @@
+//! Request handler which can be modified while the server is running.
+//!
+//! Servers keep the `MetaIoHandler` they were started with, so its method table is fixed.
+//! A `SharedIoHandler` holds a handler which can be updated or replaced at any time through any of its clones.
+//! Servers are given a `MetaIoHandler` forwarding all requests to its current version (see `SharedIoHandler::handler`):
+//!
+//! ```
+//! use jsonrpc_core::registry::SharedIoHandler;
+//! use jsonrpc_core::*;
+//!
+//! let mut io = IoHandler::new();
+//! io.add_method("plugin_hello", |_params| async { Ok(Value::String("hello".into())) });
+//! let shared = SharedIoHandler::new(io);
+//! let server_handler: MetaIoHandler<()> = shared.handler();
+//!
+//! // Later on, while the server is running:
+//! shared.update(|io| {
+//!     io.remove_prefix("plugin_");
+//!     io.add_method("plugin_hi", |_params| async { Ok(Value::String("hi".into())) });
+//! });
+//!
+//! let request = r#"{"jsonrpc": "2.0", "method": "plugin_hi", "id": 1}"#;
+//! let response = r#"{"jsonrpc":"2.0","result":"hi","id":1}"#;
+//! assert_eq!(server_handler.handle_request_sync(request, ()), Some(response.into()));
+//! ```
+//!
+//! Calls already in progress complete with the version of the handler they were started with.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::calls::Metadata;
+use crate::io::MetaIoHandler;
+use crate::middleware::{self, Middleware};
+
+/// A `MetaIoHandler` shared between clones, which can be updated at runtime.
+pub struct SharedIoHandler<T: Metadata, S: Middleware<T> = middleware::Noop> {
+	handler: Arc<RwLock<Arc<MetaIoHandler<T, S>>>>,
+}
+
+impl<T: Metadata, S: Middleware<T>> SharedIoHandler<T, S> {
+	/// Creates new shared handler with given initial version.
+	pub fn new<H: Into<MetaIoHandler<T, S>>>(handler: H) -> Self {
+		SharedIoHandler {
+			handler: Arc::new(RwLock::new(Arc::new(handler.into()))),
+		}
+	}
+
+	/// Returns current version of the handler.
+	pub fn load(&self) -> Arc<MetaIoHandler<T, S>> {
+		self.handler
+			.read()
+			.expect("SharedIoHandler lock is never poisoned; qed")
+			.clone()
+	}
+
+	/// Replaces the handler with a new one, e.g. built from scratch.
+	pub fn replace<H: Into<MetaIoHandler<T, S>>>(&self, handler: H) {
+		*self
+			.handler
+			.write()
+			.expect("SharedIoHandler lock is never poisoned; qed") = Arc::new(handler.into());
+	}
+}
+
+impl<T: Metadata, S: Middleware<T> + Clone> SharedIoHandler<T, S> {
+	/// Modifies a copy of the current handler and makes it the current one.
+	///
+	/// The copy shares state of the handler (e.g. calls in progress, concurrency limits)
+	/// with the previous version. Concurrent updates are applied one after another.
+	pub fn update<F>(&self, update: F)
+	where
+		F: FnOnce(&mut MetaIoHandler<T, S>),
+	{
+		let mut current = self
+			.handler
+			.write()
+			.expect("SharedIoHandler lock is never poisoned; qed");
+		let mut handler = (**current).clone();
+		update(&mut handler);
+		*current = Arc::new(handler);
+	}
+
+	/// Returns a handler forwarding all requests to the current version of this one.
+	///
+	/// This is the handler to start servers with. Its own methods and settings are not used.
+	pub fn handler(&self) -> MetaIoHandler<T, S> {
+		MetaIoHandler::shared(self.clone(), self.load().middleware().clone())
+	}
+}
+
+impl<T: Metadata, S: Middleware<T> + Clone> From<SharedIoHandler<T, S>> for MetaIoHandler<T, S> {
+	fn from(shared: SharedIoHandler<T, S>) -> Self {
+		shared.handler()
+	}
+}
+
+impl<T: Metadata, S: Middleware<T>> Clone for SharedIoHandler<T, S> {
+	fn clone(&self) -> Self {
+		SharedIoHandler {
+			handler: self.handler.clone(),
+		}
+	}
+}
+
+impl<T: Metadata + fmt::Debug, S: Middleware<T> + fmt::Debug> fmt::Debug for SharedIoHandler<T, S> {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("SharedIoHandler")
+			.field("handler", &self.load())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::discover::Info;
+	use crate::{IoHandler, Value};
+
+	#[test]
+	fn should_update_methods_at_runtime() {
+		let mut io = IoHandler::new();
+		io.add_method("say_hello", |_| async { Ok(Value::String("hello".into())) });
+		let shared = SharedIoHandler::new(io);
+		let handler = shared.handler();
+
+		let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(handler.handle_request_sync(request, ()), Some(response.to_string()));
+
+		// replaces a method registered before the server was started
+		shared.update(|io| io.add_method("say_hello", |_| async { Ok(Value::String("hi".into())) }));
+		let response = r#"{"jsonrpc":"2.0","result":"hi","id":1}"#;
+		assert_eq!(handler.handle_request_sync(request, ()), Some(response.to_string()));
+		assert_eq!(handler.handle_raw_request_sync(request, ()), Some(response.to_string()));
+
+		shared.update(|io| {
+			io.remove_prefix("say_");
+		});
+		let not_found = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+		assert_eq!(handler.handle_request_sync(request, ()), Some(not_found.to_string()));
+
+		shared.replace(IoHandler::new());
+		assert_eq!(shared.load().iter().count(), 0);
+	}
+
+	#[test]
+	fn should_describe_current_methods() {
+		let mut io = IoHandler::new();
+		io.enable_discovery(Info::new("test", "1.0.0"));
+		let shared = SharedIoHandler::new(io);
+		let handler = shared.handler();
+
+		shared.update(|io| io.add_method("plugin_hello", |_| async { Ok(Value::Null) }));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "rpc.discover", "id": 1}"#;
+		let response: Value = serde_json::from_str(&handler.handle_request_sync(request, ()).unwrap()).unwrap();
+		assert_eq!(response["result"]["methods"][0]["name"], "plugin_hello");
+	}
+}
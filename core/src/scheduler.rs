@@ -0,0 +1,418 @@
+//! Limits of concurrently executing method calls.
+//!
+//! Calls exceeding the limits wait in a queue ordered by priority of their methods,
+//! calls arriving when the queue is full fail with `Error::server_overloaded`.
+//!
+//! ```rust
+//! use jsonrpc_core::scheduler::Priority;
+//! use jsonrpc_core::{IoHandler, Value};
+//!
+//! let mut io = IoHandler::new();
+//! io.add_sync_method("trace_block", |_| Ok(Value::Array(vec![])));
+//! io.add_sync_method("status", |_| Ok(Value::from("ok")));
+//!
+//! io.set_max_concurrency(64);
+//! io.set_max_queued(1024);
+//! // at most 4 traces at once, so they never take all of the slots
+//! io.set_method_concurrency("trace_block", 4);
+//! io.set_method_priority("trace_block", Priority::Low);
+//! io.set_method_priority("status", Priority::High);
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use futures_util::{future, stream, StreamExt};
+
+use crate::streaming::ResultStream;
+use crate::types::{Error, Value};
+use crate::BoxFuture;
+
+/// Priority of calls waiting for a free slot, calls of higher priority are started first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+	/// Started only when no other calls are waiting.
+	Low,
+	/// Default priority.
+	#[default]
+	Normal,
+	/// Started before any other calls.
+	High,
+}
+
+/// Queued calls by priority, then by arrival.
+type Key = (Reverse<Priority>, u64);
+
+#[derive(Debug)]
+struct Waiter {
+	method: String,
+	granted: bool,
+	waker: Option<Waker>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	max_concurrency: Option<usize>,
+	max_queued: Option<usize>,
+	method_concurrency: HashMap<String, usize>,
+	priorities: HashMap<String, Priority>,
+	running: usize,
+	running_per_method: HashMap<String, usize>,
+	queue: BTreeMap<Key, Waiter>,
+	next_seq: u64,
+}
+
+impl State {
+	fn is_limited(&self, method: &str) -> bool {
+		self.max_concurrency.is_some() || self.method_concurrency.contains_key(method)
+	}
+
+	fn is_full(&self) -> bool {
+		matches!(self.max_concurrency, Some(max) if self.running >= max)
+	}
+
+	fn has_slot(&self, method: &str) -> bool {
+		let global = !self.is_full();
+		let per_method = match self.method_concurrency.get(method) {
+			Some(max) => self.running_per_method.get(method).copied().unwrap_or(0) < *max,
+			None => true,
+		};
+		global && per_method
+	}
+
+	fn take_slot(&mut self, method: &str) {
+		self.running += 1;
+		*self.running_per_method.entry(method.to_owned()).or_insert(0) += 1;
+	}
+
+	fn release_slot(&mut self, method: &str) {
+		self.running -= 1;
+		if let Some(running) = self.running_per_method.get_mut(method) {
+			*running -= 1;
+			if *running == 0 {
+				self.running_per_method.remove(method);
+			}
+		}
+		self.dispatch();
+	}
+
+	/// Grants free slots to waiting calls, skipping calls of methods which are at their own limit.
+	fn dispatch(&mut self) {
+		if self.is_full() {
+			return;
+		}
+		let keys: Vec<Key> = self.queue.keys().copied().collect();
+		for key in keys {
+			let method = match self.queue.get(&key) {
+				Some(waiter) if !waiter.granted && self.has_slot(&waiter.method) => waiter.method.clone(),
+				_ => continue,
+			};
+			self.take_slot(&method);
+			let waiter = self.queue.get_mut(&key).expect("Key was just read from the queue; qed");
+			waiter.granted = true;
+			if let Some(waker) = waiter.waker.take() {
+				waker.wake();
+			}
+			if self.is_full() {
+				return;
+			}
+		}
+	}
+}
+
+/// Decides when method calls are started, shared by clones of `MetaIoHandler`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Scheduler {
+	state: Arc<Mutex<State>>,
+}
+
+impl Scheduler {
+	pub(crate) fn set_max_concurrency(&self, max: usize) {
+		self.lock().max_concurrency = Some(max);
+	}
+
+	pub(crate) fn set_max_queued(&self, max: usize) {
+		self.lock().max_queued = Some(max);
+	}
+
+	pub(crate) fn set_method_concurrency(&self, method: &str, max: usize) {
+		self.lock().method_concurrency.insert(method.into(), max);
+	}
+
+	pub(crate) fn set_method_priority(&self, method: &str, priority: Priority) {
+		self.lock().priorities.insert(method.into(), priority);
+	}
+
+	/// Adds per-method limits and priorities of another scheduler, and its global limits if there are none yet.
+	pub(crate) fn extend(&self, other: &Scheduler) {
+		if Arc::ptr_eq(&self.state, &other.state) {
+			return;
+		}
+		let other = other.lock();
+		let mut state = self.lock();
+		state.max_concurrency = state.max_concurrency.or(other.max_concurrency);
+		state.max_queued = state.max_queued.or(other.max_queued);
+		state.method_concurrency.extend(
+			other
+				.method_concurrency
+				.iter()
+				.map(|(method, max)| (method.clone(), *max)),
+		);
+		state.priorities.extend(
+			other
+				.priorities
+				.iter()
+				.map(|(method, priority)| (method.clone(), *priority)),
+		);
+	}
+
+	/// Starts the call once there is a free slot, or rejects it if the queue is full.
+	///
+	/// Calls of methods without limits are started right away.
+	pub(crate) fn schedule<F>(&self, method: &str, call: F) -> BoxFuture<crate::Result<Value>>
+	where
+		F: FnOnce() -> BoxFuture<crate::Result<Value>> + Send + 'static,
+	{
+		match self.admit(method) {
+			Admission::Unlimited => call(),
+			Admission::Started(slot) => {
+				let result = call();
+				Box::pin(async move {
+					let result = result.await;
+					drop(slot);
+					result
+				})
+			}
+			Admission::Queued(queued) => Box::pin(async move {
+				let slot = queued.await;
+				let result = call().await;
+				drop(slot);
+				result
+			}),
+			Admission::Rejected => Box::pin(async { Err(Error::server_overloaded()) }),
+		}
+	}
+
+	/// Starts a streaming call once there is a free slot, or rejects it if the queue is full.
+	///
+	/// The slot is held until the stream ends or is dropped.
+	pub(crate) fn schedule_stream<F>(&self, method: &str, call: F) -> ResultStream
+	where
+		F: FnOnce() -> ResultStream + Send + 'static,
+	{
+		fn hold(items: ResultStream, slot: Slot) -> ResultStream {
+			Box::pin(items.map(move |item| {
+				let _slot = &slot;
+				item
+			}))
+		}
+
+		match self.admit(method) {
+			Admission::Unlimited => call(),
+			Admission::Started(slot) => hold(call(), slot),
+			Admission::Queued(queued) => {
+				let mut call = Some(call);
+				Box::pin(stream::once(queued).flat_map(move |slot| {
+					let call = call.take().expect("A queued call is granted a slot once; qed");
+					hold(call(), slot)
+				}))
+			}
+			Admission::Rejected => Box::pin(stream::once(future::ready(Err(Error::server_overloaded())))),
+		}
+	}
+
+	/// Takes a slot for a call of given method, or queues the call if there is none.
+	fn admit(&self, method: &str) -> Admission {
+		let mut state = self.lock();
+		if !state.is_limited(method) {
+			return Admission::Unlimited;
+		}
+
+		// waiting calls are granted free slots as soon as they are released,
+		// so a free slot means none of them could use it
+		if state.has_slot(method) {
+			state.take_slot(method);
+			return Admission::Started(Slot {
+				scheduler: self.clone(),
+				method: method.to_owned(),
+			});
+		}
+
+		if matches!(state.max_queued, Some(max) if state.queue.len() >= max) {
+			warn!(target: "rpc", "Call to {} rejected, too many calls are queued.", method);
+			return Admission::Rejected;
+		}
+
+		let priority = state.priorities.get(method).copied().unwrap_or_default();
+		let key = (Reverse(priority), state.next_seq);
+		state.next_seq += 1;
+		state.queue.insert(
+			key,
+			Waiter {
+				method: method.to_owned(),
+				granted: false,
+				waker: None,
+			},
+		);
+		Admission::Queued(Queued {
+			scheduler: self.clone(),
+			key: Some(key),
+		})
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().expect("Scheduler is never poisoned; qed")
+	}
+}
+
+/// Outcome of a call asking for a slot.
+enum Admission {
+	/// The method has no limits, the call doesn't take a slot.
+	Unlimited,
+	/// The call can start right away.
+	Started(Slot),
+	/// The call has to wait for a slot.
+	Queued(Queued),
+	/// The queue is full.
+	Rejected,
+}
+
+/// A slot taken by a running call, released on drop.
+struct Slot {
+	scheduler: Scheduler,
+	method: String,
+}
+
+impl Drop for Slot {
+	fn drop(&mut self) {
+		self.scheduler.lock().release_slot(&self.method);
+	}
+}
+
+/// Resolves to a slot once a queued call is granted one.
+struct Queued {
+	scheduler: Scheduler,
+	key: Option<Key>,
+}
+
+impl Future for Queued {
+	type Output = Slot;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Slot> {
+		let key = self.key.expect("Queued is not polled after completion; qed");
+		let mut state = self.scheduler.lock();
+		let waiter = state
+			.queue
+			.get_mut(&key)
+			.expect("Waiters are removed only here and on drop; qed");
+		if waiter.granted {
+			let waiter = state
+				.queue
+				.remove(&key)
+				.expect("Waiter was just read from the queue; qed");
+			drop(state);
+			self.key = None;
+			Poll::Ready(Slot {
+				scheduler: self.scheduler.clone(),
+				method: waiter.method,
+			})
+		} else {
+			waiter.waker = Some(cx.waker().clone());
+			Poll::Pending
+		}
+	}
+}
+
+impl Drop for Queued {
+	fn drop(&mut self) {
+		// the call was cancelled (or timed out) while waiting
+		if let Some(key) = self.key.take() {
+			let mut state = self.scheduler.lock();
+			if let Some(waiter) = state.queue.remove(&key) {
+				if waiter.granted {
+					state.release_slot(&waiter.method);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures_util::future::{self, FutureExt};
+
+	fn call(
+		log: &Arc<Mutex<Vec<&'static str>>>,
+		name: &'static str,
+	) -> impl FnOnce() -> BoxFuture<crate::Result<Value>> {
+		let log = log.clone();
+		move || {
+			log.lock().unwrap().push(name);
+			Box::pin(future::ready(Ok(Value::Null)))
+		}
+	}
+
+	#[test]
+	fn should_start_queued_calls_by_priority() {
+		let scheduler = Scheduler::default();
+		scheduler.set_max_concurrency(1);
+		scheduler.set_method_priority("status", Priority::High);
+		scheduler.set_method_priority("trace", Priority::Low);
+		let log = Arc::default();
+
+		let first = scheduler.schedule("block", call(&log, "block"));
+		let mut queued = vec![
+			scheduler.schedule("trace", call(&log, "trace")),
+			scheduler.schedule("block", call(&log, "block")),
+			scheduler.schedule("status", call(&log, "status")),
+		];
+		assert_eq!(*log.lock().unwrap(), vec!["block"]);
+		for call in &mut queued {
+			assert!(call.now_or_never().is_none());
+		}
+
+		futures_executor::block_on(first).unwrap();
+		futures_executor::block_on(future::join_all(queued));
+		assert_eq!(*log.lock().unwrap(), vec!["block", "status", "block", "trace"]);
+		assert_eq!(scheduler.lock().running, 0);
+	}
+
+	#[test]
+	fn should_not_block_other_methods_on_method_limit() {
+		let scheduler = Scheduler::default();
+		scheduler.set_method_concurrency("trace", 1);
+		let log = Arc::default();
+
+		let _trace = scheduler.schedule("trace", call(&log, "trace"));
+		let mut queued = scheduler.schedule("trace", call(&log, "queued"));
+		let status = scheduler.schedule("status", call(&log, "status"));
+		assert!((&mut queued).now_or_never().is_none());
+		assert!(status.now_or_never().is_some());
+		assert_eq!(*log.lock().unwrap(), vec!["trace", "status"]);
+	}
+
+	#[test]
+	fn should_reject_calls_when_queue_is_full() {
+		let scheduler = Scheduler::default();
+		scheduler.set_max_concurrency(1);
+		scheduler.set_max_queued(1);
+		let log = Arc::default();
+
+		let running = scheduler.schedule("a", call(&log, "a"));
+		let queued = scheduler.schedule("b", call(&log, "b"));
+		let rejected = scheduler.schedule("c", call(&log, "c"));
+		assert_eq!(rejected.now_or_never(), Some(Err(Error::server_overloaded())));
+
+		// dropping a queued call frees its place in the queue
+		drop(queued);
+		drop(running);
+		assert_eq!(scheduler.lock().running, 0);
+		assert!(scheduler.lock().queue.is_empty());
+		assert_eq!(*log.lock().unwrap(), vec!["a"]);
+	}
+}
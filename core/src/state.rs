@@ -0,0 +1,221 @@
+//! Methods sharing state.
+//!
+//! `MetaIoHandler::with_state` takes the state once and passes a clone of it to every call,
+//! so methods can be plain `async fn`s instead of closures capturing their own copies.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use jsonrpc_core::{Error, IoHandler, Params, Value};
+//!
+//! struct Db {
+//!     balance: u64,
+//! }
+//!
+//! async fn balance(db: Arc<Db>, _params: Params, _meta: ()) -> jsonrpc_core::Result<Value> {
+//!     Ok(Value::from(db.balance))
+//! }
+//!
+//! let mut io = IoHandler::new();
+//! let mut methods = io.with_state(Arc::new(Db { balance: 10 }));
+//! methods.add_method("balance", balance);
+//! methods.add_typed_method("withdraw", |db: Arc<Db>, (amount,): (u64,), _meta| async move {
+//!     db.balance.checked_sub(amount).ok_or_else(|| Error::invalid_params("Insufficient balance"))
+//! });
+//!
+//! let request = r#"{"jsonrpc": "2.0", "method": "withdraw", "params": [3], "id": 1}"#;
+//! let response = r#"{"jsonrpc":"2.0","result":7,"id":1}"#;
+//! assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+//! ```
+
+use std::future::Future;
+use std::marker::PhantomData;
+
+use futures_util::TryFutureExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::calls::{Metadata, RpcMethod};
+use crate::io::MetaIoHandler;
+use crate::middleware::Middleware;
+use crate::types::raw::RawParams;
+use crate::types::{Error, Params, Value};
+use crate::BoxFuture;
+
+/// Registers methods receiving shared state, created by `MetaIoHandler::with_state`.
+#[derive(Debug)]
+pub struct StateMethods<'a, T: Metadata, S: Middleware<T>, St> {
+	io: &'a mut MetaIoHandler<T, S>,
+	state: St,
+}
+
+impl<'a, T, S, St> StateMethods<'a, T, S, St>
+where
+	T: Metadata,
+	S: Middleware<T>,
+	St: Clone + Send + Sync + 'static,
+{
+	pub(crate) fn new(io: &'a mut MetaIoHandler<T, S>, state: St) -> Self {
+		StateMethods { io, state }
+	}
+
+	/// Adds new asynchronous method receiving the state, params and metadata.
+	pub fn add_method<F, X>(&mut self, name: &str, method: F)
+	where
+		F: Fn(St, Params, T) -> X + Send + Sync + 'static,
+		X: Future<Output = crate::Result<Value>> + Send + 'static,
+	{
+		let state = self.state.clone();
+		self.io
+			.add_method_with_meta(name, move |params, meta| method(state.clone(), params, meta));
+	}
+
+	/// Adds new asynchronous method with typed params and result.
+	///
+	/// Params are deserialized into `P` before the method is called (calls with params
+	/// which don't match fail with `InvalidParams`), and the returned value is serialized to JSON.
+	/// Any error convertible into `Error` can be returned, e.g. one deriving `RpcError`.
+	pub fn add_typed_method<F, P, X, R, E>(&mut self, name: &str, method: F)
+	where
+		F: Fn(St, P, T) -> X + Send + Sync + 'static,
+		P: DeserializeOwned + 'static,
+		X: Future<Output = Result<R, E>> + Send + 'static,
+		R: Serialize + 'static,
+		E: Into<Error> + 'static,
+	{
+		self.io.add_method_with_meta(
+			name,
+			TypedMethod {
+				state: self.state.clone(),
+				method,
+				_params: PhantomData,
+			},
+		);
+	}
+}
+
+struct TypedMethod<St, F, P> {
+	state: St,
+	method: F,
+	_params: PhantomData<fn(P)>,
+}
+
+impl<St, F, P> TypedMethod<St, F, P> {
+	fn call_parsed<T, X, R, E>(&self, params: crate::Result<P>, meta: T) -> BoxFuture<crate::Result<Value>>
+	where
+		F: Fn(St, P, T) -> X,
+		St: Clone,
+		X: Future<Output = Result<R, E>> + Send + 'static,
+		R: Serialize + 'static,
+		E: Into<Error> + 'static,
+	{
+		let params = match params {
+			Ok(params) => params,
+			Err(err) => return Box::pin(async { Err(err) }),
+		};
+		Box::pin(
+			(self.method)(self.state.clone(), params, meta)
+				.map_ok(|value| crate::to_value(value).expect("Expected always-serializable type; qed"))
+				.map_err(Into::into),
+		)
+	}
+}
+
+impl<T, St, F, P, X, R, E> RpcMethod<T> for TypedMethod<St, F, P>
+where
+	T: Metadata,
+	St: Clone + Send + Sync + 'static,
+	F: Fn(St, P, T) -> X + Send + Sync + 'static,
+	P: DeserializeOwned + 'static,
+	X: Future<Output = Result<R, E>> + Send + 'static,
+	R: Serialize + 'static,
+	E: Into<Error> + 'static,
+{
+	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.call_parsed(params.parse(), meta)
+	}
+
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.call_parsed(params.parse(), meta)
+	}
+}
+
+impl<T: Metadata, S: Middleware<T>> MetaIoHandler<T, S> {
+	/// Returns a registrar of methods receiving a clone of `state` on every call.
+	///
+	/// The state is typically an `Arc` or a handle which is cheap to clone.
+	pub fn with_state<St>(&mut self, state: St) -> StateMethods<'_, T, S, St>
+	where
+		St: Clone + Send + Sync + 'static,
+	{
+		StateMethods::new(self, state)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ErrorCode;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::sync::Arc;
+
+	#[derive(Default)]
+	struct Counter(AtomicUsize);
+
+	#[derive(Deserialize)]
+	struct Add {
+		by: usize,
+	}
+
+	async fn get(counter: Arc<Counter>, _params: Params, meta: Arc<usize>) -> crate::Result<Value> {
+		Ok(Value::from(counter.0.load(Ordering::SeqCst) + *meta))
+	}
+
+	fn io() -> (MetaIoHandler<Arc<usize>>, Arc<Counter>) {
+		let counter = Arc::new(Counter::default());
+		let mut io = MetaIoHandler::default();
+		let mut methods = io.with_state(counter.clone());
+		methods.add_method("get", get);
+		methods.add_typed_method("add", |counter: Arc<Counter>, params: Add, _meta| async move {
+			Ok::<_, Error>(counter.0.fetch_add(params.by, Ordering::SeqCst) + params.by)
+		});
+		methods.add_typed_method("fail", |_, (): (), _| async {
+			Err::<(), _>(Error::new(ErrorCode::ServerError(-32010)))
+		});
+		(io, counter)
+	}
+
+	#[test]
+	fn should_pass_state_to_methods() {
+		let (io, counter) = io();
+
+		let request = r#"{"jsonrpc":"2.0","method":"add","params":{"by":2},"id":1}"#;
+		assert_eq!(
+			io.handle_request_sync(request, Arc::new(0)),
+			Some(r#"{"jsonrpc":"2.0","result":2,"id":1}"#.into())
+		);
+		let request = r#"{"jsonrpc":"2.0","method":"get","id":1}"#;
+		assert_eq!(
+			io.handle_request_sync(request, Arc::new(1)),
+			Some(r#"{"jsonrpc":"2.0","result":3,"id":1}"#.into())
+		);
+		assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+	}
+
+	#[test]
+	fn should_reject_params_of_wrong_type() {
+		let (io, counter) = io();
+
+		let request = r#"{"jsonrpc":"2.0","method":"add","params":{"by":"two"},"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params: invalid type: string \"two\", expected usize."},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, Arc::new(0)), Some(response.into()));
+		assert_eq!(
+			futures_executor::block_on(io.handle_raw_request(request, Arc::new(0))),
+			Some(response.into())
+		);
+		assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+		let request = r#"{"jsonrpc":"2.0","method":"fail","id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Server error"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, Arc::new(0)), Some(response.into()));
+	}
+}
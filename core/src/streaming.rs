@@ -0,0 +1,168 @@
+//! Methods streaming their results.
+//!
+//! A streaming method returns a `Stream` of items instead of a single `Value`.
+//! `MetaIoHandler::handle_request_streaming` serializes a call to such method as an array
+//! produced chunk by chunk, so the whole result never has to be held in memory.
+//! Everywhere else (batches, `handle_request`, ...) the items are collected into an array.
+//!
+//! The http server answers such calls with a chunked body. The tcp, ipc and stdio servers write
+//! the chunks as they are produced, as long as messages are JSON text. Incremental emission over
+//! websockets is not supported: the ws server sends the collected array in a single message,
+//! since the underlying websocket library can't send a message in parts.
+//!
+//! ```rust
+//! use jsonrpc_core::futures::{executor, stream, StreamExt};
+//! use jsonrpc_core::streaming::StreamingResponse;
+//! use jsonrpc_core::{IoHandler, Params, Value};
+//!
+//! let mut io = IoHandler::new();
+//! io.add_stream_method("logs", |_params: Params, _meta: ()| stream::iter((0..3).map(|i| Ok(Value::from(i)))));
+//!
+//! let request = r#"{"jsonrpc": "2.0", "method": "logs", "id": 1}"#;
+//! let response = match executor::block_on(io.handle_request_streaming(request)) {
+//!     Some(StreamingResponse::Chunked(chunks)) => executor::block_on(chunks.map(Result::unwrap).collect::<String>()),
+//!     _ => unreachable!(),
+//! };
+//! assert_eq!(response, r#"{"jsonrpc":"2.0","result":[0,1,2],"id":1}"#);
+//! assert_eq!(io.handle_request_sync(request), Some(response));
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::{future, stream, FutureExt, Stream, StreamExt, TryStreamExt};
+
+use crate::calls::{Metadata, RpcMethod};
+use crate::types::{Error, Id, Output, Params, Success, Value, Version};
+use crate::BoxFuture;
+
+/// A stream of items of a streaming method.
+pub type ResultStream = Pin<Box<dyn Stream<Item = crate::Result<Value>> + Send>>;
+
+/// Chunks of a serialized response.
+///
+/// An error means the method failed after part of the response was already produced,
+/// transports should abort the response so that the client doesn't mistake it for a complete one.
+pub type ResponseChunks = Pin<Box<dyn Stream<Item = Result<String, Error>> + Send>>;
+
+/// Asynchronous method streaming its result.
+pub trait RpcStreamMethod<T: Metadata>: Send + Sync + 'static {
+	/// Call method
+	fn call(&self, params: Params, meta: T) -> ResultStream;
+}
+
+impl<T: Metadata, F, S> RpcStreamMethod<T> for F
+where
+	F: Fn(Params, T) -> S + Send + Sync + 'static,
+	S: Stream<Item = crate::Result<Value>> + Send + 'static,
+{
+	fn call(&self, params: Params, meta: T) -> ResultStream {
+		Box::pin(self(params, meta))
+	}
+}
+
+impl<T: Metadata> fmt::Debug for dyn RpcStreamMethod<T> {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		write!(fmt, "<stream method>")
+	}
+}
+
+/// Response produced by `MetaIoHandler::handle_request_streaming`.
+pub enum StreamingResponse {
+	/// Response serialized at once.
+	Complete(String),
+	/// Response to a call of a streaming method, the chunks concatenated form the serialized response.
+	Chunked(ResponseChunks),
+}
+
+impl fmt::Debug for StreamingResponse {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			StreamingResponse::Complete(response) => fmt.debug_tuple("Complete").field(response).finish(),
+			StreamingResponse::Chunked(_) => fmt.debug_tuple("Chunked").finish(),
+		}
+	}
+}
+
+/// Exposes a streaming method as a regular method collecting all items into an array.
+pub(crate) fn collect<T: Metadata>(method: Arc<dyn RpcStreamMethod<T>>) -> Arc<dyn RpcMethod<T>> {
+	Arc::new(Collect(method))
+}
+
+struct Collect<T>(Arc<dyn RpcStreamMethod<T>>);
+
+impl<T: Metadata> RpcMethod<T> for Collect<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		Box::pin(
+			self.0
+				.call(params, meta)
+				.try_collect()
+				.map(|items| items.map(Value::Array)),
+		)
+	}
+}
+
+/// Serializes the response to a streaming method call chunk by chunk.
+///
+/// Waits for the first item, so that a method failing right away gets a regular error response.
+pub(crate) fn respond(items: ResultStream, id: Id, jsonrpc: Option<Version>) -> BoxFuture<StreamingResponse> {
+	Box::pin(items.into_future().map(move |(first, rest)| {
+		let first = match first {
+			Some(Ok(first)) => first,
+			Some(Err(error)) => return complete(Output::from(Err(error), id, jsonrpc)),
+			None => return complete(Output::from(Ok(Value::Array(Vec::new())), id, jsonrpc)),
+		};
+
+		// Serialize the response with a placeholder result, so that the envelope is exactly
+		// the same as of a complete response, and stream the items in its place.
+		let envelope = serde_json::to_string(&Output::Success(Success {
+			jsonrpc,
+			result: Value::Null,
+			id,
+		}))
+		.expect("Output is serializable to JSON; qed");
+		let (header, footer) = envelope.split_at(envelope.find(RESULT_PLACEHOLDER).expect("Result is serialized; qed"));
+		let header = format!("{}\"result\":[{}", header, first);
+		let footer = format!("]{}", &footer[RESULT_PLACEHOLDER.len()..]);
+
+		let rest = stream::unfold(Some(rest), move |rest| {
+			let footer = footer.clone();
+			async move {
+				let mut rest = rest?;
+				match rest.next().await {
+					Some(Ok(item)) => Some((Ok(format!(",{}", item)), Some(rest))),
+					Some(Err(error)) => Some((Err(error), None)),
+					None => Some((Ok(footer), None)),
+				}
+			}
+		});
+		StreamingResponse::Chunked(Box::pin(stream::once(future::ready(Ok(header))).chain(rest)))
+	}))
+}
+
+/// Ends given stream with `error` as soon as `interrupt` resolves, dropping the rest of it.
+pub(crate) fn interrupt<F>(items: ResultStream, interrupt: F, error: Error) -> ResultStream
+where
+	F: Future<Output = ()> + Send + 'static,
+{
+	let state = Some((items, Box::pin(interrupt)));
+	Box::pin(stream::unfold(state, move |state| {
+		let error = error.clone();
+		async move {
+			let (mut items, mut interrupt) = state?;
+			let item = match future::select(items.next(), &mut interrupt).await {
+				future::Either::Left((item, _)) => item,
+				future::Either::Right(((), _)) => return Some((Err(error), None)),
+			};
+			item.map(|item| (item, Some((items, interrupt))))
+		}
+	}))
+}
+
+const RESULT_PLACEHOLDER: &str = "\"result\":null";
+
+fn complete(output: Output) -> StreamingResponse {
+	StreamingResponse::Complete(serde_json::to_string(&output).expect("Output is serializable to JSON; qed"))
+}
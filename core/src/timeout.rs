@@ -0,0 +1,69 @@
+//! Method call deadlines.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_timer::Delay;
+use futures_util::future::{self, Either};
+
+use crate::calls::{Metadata, RemoteProcedure, RpcMethod};
+use crate::discover::MethodDescription;
+use crate::streaming::{self, ResultStream};
+use crate::types::raw::RawParams;
+use crate::types::{Error, Params, Value};
+use crate::BoxFuture;
+
+/// Resolves to `Error::request_timeout` if given future does not complete in time.
+pub(crate) fn with_timeout(
+	method: BoxFuture<crate::Result<Value>>,
+	timeout: Duration,
+) -> BoxFuture<crate::Result<Value>> {
+	Box::pin(async move {
+		match future::select(method, Delay::new(timeout)).await {
+			Either::Left((result, _)) => result,
+			Either::Right(((), _)) => Err(Error::request_timeout()),
+		}
+	})
+}
+
+/// Ends given stream with `Error::request_timeout` if it does not end in time.
+pub(crate) fn with_stream_timeout(items: ResultStream, timeout: Duration) -> ResultStream {
+	// the deadline starts when the stream is first polled, like in `with_timeout`
+	let deadline = async move { Delay::new(timeout).await };
+	streaming::interrupt(items, deadline, Error::request_timeout())
+}
+
+/// Attaches a deadline to a method.
+///
+/// The deadline is enforced by `MetaIoHandler` unless it has its own setting for this method.
+pub(crate) fn set_timeout<T: Metadata>(procedure: &mut RemoteProcedure<T>, timeout: Duration) {
+	if let RemoteProcedure::Method(ref mut method) = *procedure {
+		*method = Arc::new(TimedMethod {
+			method: method.clone(),
+			timeout,
+		});
+	}
+}
+
+struct TimedMethod<T> {
+	method: Arc<dyn RpcMethod<T>>,
+	timeout: Duration,
+}
+
+impl<T: Metadata> RpcMethod<T> for TimedMethod<T> {
+	fn call(&self, params: Params, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.method.call(params, meta)
+	}
+
+	fn call_raw(&self, params: RawParams, meta: T) -> BoxFuture<crate::Result<Value>> {
+		self.method.call_raw(params, meta)
+	}
+
+	fn description(&self) -> Option<&MethodDescription> {
+		self.method.description()
+	}
+
+	fn timeout(&self) -> Option<Duration> {
+		Some(self.timeout)
+	}
+}
@@ -0,0 +1,426 @@
+//! Interoperability with `tower` services and layers.
+//!
+//! `MetaIoHandler` is a `Service<(Request, M)>`, so any `tower::Layer` can wrap it directly.
+//! `LayerMiddleware` plugs a layer into the handler as a `Middleware`
+//! and `MiddlewareLayer` turns a `Middleware` into a layer.
+
+use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures_channel::oneshot;
+use futures_util::future::{self, Either, FutureExt};
+use futures_util::task::noop_waker_ref;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::calls::Metadata;
+use crate::io::{FutureResponse, FutureRpcResult, MetaIoHandler};
+use crate::middleware::{Middleware, NoopCallFuture};
+use crate::types::{Call, Error, Id, Output, Request, Response, Version};
+use crate::BoxFuture;
+
+/// Future returned by `MetaIoHandler` used as a `Service`.
+pub type ServiceFuture<F, G> =
+	future::Map<FutureRpcResult<F, G>, fn(Option<Response>) -> Result<Option<Response>, Infallible>>;
+
+impl<T: Metadata, S: Middleware<T>> Service<(Request, T)> for MetaIoHandler<T, S> {
+	type Response = Option<Response>;
+	type Error = Infallible;
+	type Future = ServiceFuture<S::Future, S::CallFuture>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, (request, meta): (Request, T)) -> Self::Future {
+		self.handle_rpc_request(request, meta)
+			.map(Ok as fn(Option<Response>) -> Result<Option<Response>, Infallible>)
+	}
+}
+
+type Handoff<M> = (Request, M, oneshot::Sender<Option<Response>>);
+
+/// Innermost service of `LayerMiddleware`, passes the request back to `MetaIoHandler`.
+pub struct Next<M> {
+	handoffs: Arc<Mutex<Vec<Handoff<M>>>>,
+}
+
+impl<M> Clone for Next<M> {
+	fn clone(&self) -> Self {
+		Next {
+			handoffs: self.handoffs.clone(),
+		}
+	}
+}
+
+impl<M: Metadata> Service<(Request, M)> for Next<M> {
+	type Response = Option<Response>;
+	type Error = Infallible;
+	type Future = future::Map<
+		oneshot::Receiver<Option<Response>>,
+		fn(Result<Option<Response>, oneshot::Canceled>) -> Result<Option<Response>, Infallible>,
+	>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, (request, meta): (Request, M)) -> Self::Future {
+		let (sender, receiver) = oneshot::channel();
+		self.handoffs
+			.lock()
+			.expect("Handoffs are never poisoned; qed")
+			.push((request, meta, sender));
+		receiver.map(|response| Ok(response.unwrap_or(None)))
+	}
+}
+
+/// Middleware processing requests with a service built by `tower::Layer`.
+///
+/// The service is built once and shared by all requests. It's polled for readiness without
+/// waiting, requests arriving when it's not ready are rejected with `Error::server_overloaded`.
+/// The layer has to call the inner service from `Service::call`, i.e. layers which defer
+/// the call to a background task (like `Buffer`) are not supported.
+pub struct LayerMiddleware<M, L: Layer<Next<M>>> {
+	service: Mutex<L::Service>,
+	handoffs: Arc<Mutex<Vec<Handoff<M>>>>,
+}
+
+impl<M: Metadata, L: Layer<Next<M>>> LayerMiddleware<M, L> {
+	/// Creates new middleware applying given layer.
+	pub fn new(layer: L) -> Self {
+		let handoffs = Arc::new(Mutex::new(Vec::new()));
+		let service = layer.layer(Next {
+			handoffs: handoffs.clone(),
+		});
+		LayerMiddleware {
+			service: Mutex::new(service),
+			handoffs,
+		}
+	}
+}
+
+impl<M, L, S> Middleware<M> for LayerMiddleware<M, L>
+where
+	M: Metadata,
+	L: Layer<Next<M>, Service = S> + Send + Sync + 'static,
+	S: Service<(Request, M), Response = Option<Response>> + Send + 'static,
+	S::Future: Send + 'static,
+	S::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+	type Future = FutureResponse;
+	type CallFuture = NoopCallFuture;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+	where
+		F: Fn(Request, M) -> X + Send + Sync,
+		X: Future<Output = Option<Response>> + Send + 'static,
+	{
+		let failure = FailureResponse::new(&request);
+		let (response, handoffs) = {
+			let mut service = self.service.lock().expect("Service is never poisoned; qed");
+			match service.poll_ready(&mut Context::from_waker(noop_waker_ref())) {
+				Poll::Ready(Ok(())) => {}
+				Poll::Ready(Err(error)) => return Either::Left(Box::pin(future::ready(failure.error(error)))),
+				Poll::Pending => {
+					return Either::Left(Box::pin(future::ready(failure.respond(Error::server_overloaded()))))
+				}
+			}
+			let response = service.call((request, meta));
+			let handoffs = std::mem::take(&mut *self.handoffs.lock().expect("Handoffs are never poisoned; qed"));
+			(response, handoffs)
+		};
+
+		let forwarded = future::join_all(handoffs.into_iter().map(|(request, meta, sender)| {
+			next(request, meta).map(move |response| {
+				let _ = sender.send(response);
+			})
+		}));
+		let response = response.map(move |response| response.unwrap_or_else(|error| failure.error(error)));
+
+		Either::Left(Box::pin(async move {
+			futures_util::pin_mut!(response, forwarded);
+			match future::select(response, forwarded).await {
+				Either::Left((response, _)) => response,
+				Either::Right((_, response)) => response.await,
+			}
+		}))
+	}
+}
+
+/// Layer wrapping services with a `Middleware`.
+#[derive(Debug)]
+pub struct MiddlewareLayer<S> {
+	middleware: Arc<S>,
+}
+
+impl<S> MiddlewareLayer<S> {
+	/// Creates new layer applying given middleware.
+	pub fn new(middleware: S) -> Self {
+		MiddlewareLayer {
+			middleware: Arc::new(middleware),
+		}
+	}
+}
+
+impl<S> Clone for MiddlewareLayer<S> {
+	fn clone(&self) -> Self {
+		MiddlewareLayer {
+			middleware: self.middleware.clone(),
+		}
+	}
+}
+
+impl<S, I> Layer<I> for MiddlewareLayer<S> {
+	type Service = MiddlewareService<S, I>;
+
+	fn layer(&self, inner: I) -> Self::Service {
+		MiddlewareService {
+			middleware: self.middleware.clone(),
+			inner,
+		}
+	}
+}
+
+/// Service processing requests with a `Middleware` before passing them to the inner service.
+///
+/// The inner service is cloned for each request and errors it returns
+/// are converted to `InternalError` responses.
+#[derive(Debug)]
+pub struct MiddlewareService<S, I> {
+	middleware: Arc<S>,
+	inner: I,
+}
+
+impl<S, I: Clone> Clone for MiddlewareService<S, I> {
+	fn clone(&self) -> Self {
+		MiddlewareService {
+			middleware: self.middleware.clone(),
+			inner: self.inner.clone(),
+		}
+	}
+}
+
+impl<M, S, I> Service<(Request, M)> for MiddlewareService<S, I>
+where
+	M: Metadata,
+	S: Middleware<M>,
+	I: Service<(Request, M), Response = Option<Response>> + Clone + Send + Sync + 'static,
+	I::Future: Send + 'static,
+	I::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+	type Response = Option<Response>;
+	type Error = Infallible;
+	type Future = BoxFuture<Result<Option<Response>, Infallible>>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		// readiness of the inner service is awaited for each call
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, (request, meta): (Request, M)) -> Self::Future {
+		let inner = &self.inner;
+		let response = self.middleware.on_request(request, meta, |request, meta| {
+			let failure = FailureResponse::new(&request);
+			let mut inner = inner.clone();
+			async move {
+				let ready: Result<(), Box<dyn StdError + Send + Sync>> =
+					future::poll_fn(|cx| inner.poll_ready(cx)).await.map_err(Into::into);
+				let response = match ready {
+					Ok(()) => inner.call((request, meta)).await.map_err(Into::into),
+					Err(error) => Err(error),
+				};
+				response.unwrap_or_else(|error| failure.error(error))
+			}
+		});
+		Box::pin(response.map(Ok))
+	}
+}
+
+/// Response rejecting all calls of a request.
+struct FailureResponse {
+	batch: bool,
+	calls: Vec<(Id, Option<Version>)>,
+}
+
+impl FailureResponse {
+	fn new(request: &Request) -> Self {
+		// notifications don't get any response
+		let id = |call: &Call| match call {
+			Call::MethodCall(method) => Some((method.id.clone(), method.jsonrpc)),
+			Call::Notification(_) => None,
+			Call::Invalid { id } => Some((id.clone(), Some(Version::V2))),
+		};
+		match request {
+			Request::Single(call) => FailureResponse {
+				batch: false,
+				calls: id(call).into_iter().collect(),
+			},
+			Request::Batch(calls) => FailureResponse {
+				batch: true,
+				calls: calls.iter().filter_map(id).collect(),
+			},
+		}
+	}
+
+	fn error<E: Into<Box<dyn StdError + Send + Sync>>>(self, error: E) -> Option<Response> {
+		warn!("Service error: {}", error.into());
+		self.respond(Error::internal_error())
+	}
+
+	fn respond(self, error: Error) -> Option<Response> {
+		let mut outputs = self
+			.calls
+			.into_iter()
+			.map(|(id, jsonrpc)| Output::from(Err(error.clone()), id, jsonrpc));
+		if self.batch {
+			let outputs: Vec<_> = outputs.collect();
+			if outputs.is_empty() {
+				None
+			} else {
+				Some(Response::Batch(outputs))
+			}
+		} else {
+			outputs.next().map(Response::Single)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{IoHandler, MetaIoHandler, Params, Value};
+	use futures_executor::block_on;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	#[derive(Clone)]
+	struct Counting<S> {
+		inner: S,
+		calls: Arc<AtomicUsize>,
+		ready: bool,
+	}
+
+	impl<R, S: Service<R>> Service<R> for Counting<S> {
+		type Response = S::Response;
+		type Error = S::Error;
+		type Future = S::Future;
+
+		fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+			if self.ready {
+				self.inner.poll_ready(cx)
+			} else {
+				Poll::Pending
+			}
+		}
+
+		fn call(&mut self, request: R) -> Self::Future {
+			self.calls.fetch_add(1, Ordering::SeqCst);
+			self.inner.call(request)
+		}
+	}
+
+	struct CountingLayer {
+		calls: Arc<AtomicUsize>,
+		ready: bool,
+	}
+
+	impl<S> Layer<S> for CountingLayer {
+		type Service = Counting<S>;
+
+		fn layer(&self, inner: S) -> Self::Service {
+			Counting {
+				inner,
+				calls: self.calls.clone(),
+				ready: self.ready,
+			}
+		}
+	}
+
+	fn io<S: Middleware<()>>(middleware: S) -> MetaIoHandler<(), S> {
+		let mut io = MetaIoHandler::with_middleware(middleware);
+		io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into())));
+		io
+	}
+
+	fn request(request: &str) -> Request {
+		serde_json::from_str(request).unwrap()
+	}
+
+	#[test]
+	fn should_handle_requests_as_service() {
+		let mut io = IoHandler::new();
+		io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into())));
+		let mut service: MetaIoHandler<()> = io.into();
+
+		let response = block_on(service.call((request(r#"{"jsonrpc":"2.0","method":"hello","id":1}"#), ())));
+
+		assert_eq!(
+			serde_json::to_string(&response.unwrap()).unwrap(),
+			r#"{"jsonrpc":"2.0","result":"world","id":1}"#
+		);
+	}
+
+	#[test]
+	fn should_apply_layer_as_middleware() {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let io = io(LayerMiddleware::new(CountingLayer {
+			calls: calls.clone(),
+			ready: true,
+		}));
+
+		let request = r#"[{"jsonrpc":"2.0","method":"hello","id":1},{"jsonrpc":"2.0","method":"hello","id":2}]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":"world","id":1},{"jsonrpc":"2.0","result":"world","id":2}]"#;
+
+		assert_eq!(io.handle_request_sync(request, ()), Some(response.into()));
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn should_reject_requests_if_layer_is_not_ready() {
+		let calls = Arc::new(AtomicUsize::new(0));
+		let io = io(LayerMiddleware::new(CountingLayer {
+			calls: calls.clone(),
+			ready: false,
+		}));
+
+		let request = r#"{"jsonrpc":"2.0","method":"hello","id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Server is overloaded"},"id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request, ()), Some(response.into()));
+		assert_eq!(calls.load(Ordering::SeqCst), 0);
+	}
+
+	#[test]
+	fn should_apply_middleware_as_layer() {
+		struct Rejecting;
+		impl Middleware<()> for Rejecting {
+			type Future = FutureResponse;
+			type CallFuture = NoopCallFuture;
+
+			fn on_request<F, X>(&self, request: Request, meta: (), next: F) -> Either<Self::Future, X>
+			where
+				F: Fn(Request, ()) -> X + Send + Sync,
+				X: Future<Output = Option<Response>> + Send + 'static,
+			{
+				match request {
+					Request::Batch(_) => Either::Left(Box::pin(future::ready(None))),
+					request => Either::Right(next(request, meta)),
+				}
+			}
+		}
+
+		let mut service = MiddlewareLayer::new(Rejecting).layer(io(crate::NoopMiddleware));
+
+		let single = block_on(service.call((request(r#"{"jsonrpc":"2.0","method":"hello","id":1}"#), ())));
+		let batch = block_on(service.call((request(r#"[{"jsonrpc":"2.0","method":"hello","id":1}]"#), ())));
+
+		assert_eq!(
+			serde_json::to_string(&single.unwrap()).unwrap(),
+			r#"{"jsonrpc":"2.0","result":"world","id":1}"#
+		);
+		assert_eq!(batch, Ok(None));
+	}
+}
@@ -149,6 +149,87 @@ impl Error {
 		Self::new(ErrorCode::InternalError)
 	}
 
+	/// Creates new error returned when a call does not complete in time.
+	pub fn request_timeout() -> Self {
+		Error {
+			code: ErrorCode::ServerError(-32001),
+			message: "Request timed out".to_owned(),
+			data: None,
+		}
+	}
+
+	/// Creates new error returned when a call is cancelled before completion.
+	pub fn request_cancelled() -> Self {
+		Error {
+			code: ErrorCode::ServerError(-32800),
+			message: "Request cancelled".to_owned(),
+			data: None,
+		}
+	}
+
+	/// Creates new error returned when the caller lacks a permission required by the method.
+	pub fn access_denied() -> Self {
+		Error {
+			code: ErrorCode::ServerError(-32003),
+			message: "Access denied".to_owned(),
+			data: None,
+		}
+	}
+
+	/// Creates new error returned when the server is not ready to process more requests.
+	pub fn server_overloaded() -> Self {
+		Error {
+			code: ErrorCode::ServerError(-32005),
+			message: "Server is overloaded".to_owned(),
+			data: None,
+		}
+	}
+
+	/// Creates new `InvalidRequest` returned when a batch has more calls than allowed.
+	pub fn batch_too_large(max_batch_size: usize) -> Self {
+		Error {
+			code: ErrorCode::InvalidRequest,
+			message: format!("Batch too large, at most {} calls are allowed", max_batch_size),
+			data: None,
+		}
+	}
+
+	/// Creates new `InvalidRequest` returned when a request is larger than allowed.
+	pub fn request_too_large(max_size: usize) -> Self {
+		Error {
+			code: ErrorCode::InvalidRequest,
+			message: format!("Request too large, at most {} bytes are allowed", max_size),
+			data: None,
+		}
+	}
+
+	/// Creates new `InvalidRequest` returned when a request is nested deeper than allowed.
+	pub fn request_too_deep(max_depth: usize) -> Self {
+		Error {
+			code: ErrorCode::InvalidRequest,
+			message: format!("Request nested too deep, at most {} levels are allowed", max_depth),
+			data: None,
+		}
+	}
+
+	/// Creates new `InvalidRequest` returned when a request contains a string longer than allowed.
+	pub fn string_too_long(max_length: usize) -> Self {
+		Error {
+			code: ErrorCode::InvalidRequest,
+			message: format!("String too long, at most {} bytes are allowed", max_length),
+			data: None,
+		}
+	}
+
+	/// Creates new `InvalidRequest` returned when a call has more params than allowed.
+	pub fn too_many_params(max_params: usize) -> Self {
+		Error {
+			code: ErrorCode::InvalidRequest,
+			message: format!("Too many params, at most {} are allowed", max_params),
+			data: None,
+		}
+	}
+
 	/// Creates new `InvalidRequest` with invalid version description
 	pub fn invalid_version() -> Self {
 		Error {
@@ -166,3 +247,33 @@ impl std::fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+/// Application error with stable codes, convertible to and from `Error`.
+///
+/// Usually implemented for an enum with `#[derive(RpcError)]` from `jsonrpc-derive`,
+/// which also implements `From<_> for Error`, so methods can return `Result<T, YourError>`.
+/// Clients recover the error with `RpcError::into_typed`.
+pub trait RpcErrorCode: Sized {
+	/// Returns code of the error.
+	fn code(&self) -> i64;
+
+	/// Returns message of the error.
+	fn message(&self) -> String;
+
+	/// Returns structured data attached to the error.
+	fn data(&self) -> Option<Value> {
+		None
+	}
+
+	/// Recovers the error from an `Error`, returns `None` if the code or data doesn't match.
+	fn from_error(error: &Error) -> Option<Self>;
+
+	/// Converts the error into an `Error`.
+	fn into_error(self) -> Error {
+		Error {
+			code: ErrorCode::from(self.code()),
+			message: self.message(),
+			data: self.data(),
+		}
+	}
+}
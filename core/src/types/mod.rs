@@ -3,6 +3,7 @@
 pub mod error;
 pub mod id;
 pub mod params;
+pub mod raw;
 pub mod request;
 pub mod response;
 pub mod version;
@@ -11,7 +12,7 @@ pub use serde_json::to_string;
 pub use serde_json::value::to_value;
 pub use serde_json::Value;
 
-pub use self::error::{Error, ErrorCode};
+pub use self::error::{Error, ErrorCode, RpcErrorCode};
 pub use self::id::Id;
 pub use self::params::Params;
 pub use self::request::{Call, MethodCall, Notification, Request};
@@ -0,0 +1,336 @@
+//! jsonrpc request with unparsed params
+//!
+//! Types in this module keep request params as `RawValue`, so they are deserialized
+//! only once, directly into the type expected by the method.
+//! See `MetaIoHandler::handle_raw_request`.
+
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use super::{Error, ErrorCode, Id, Params, Version};
+
+/// Request parameters kept as unparsed JSON.
+///
+/// Contains either an array, an object or nothing (`Params::None`).
+#[derive(Debug, Clone, Default)]
+pub struct RawParams(Option<Box<RawValue>>);
+
+impl RawParams {
+	/// Creates params from JSON text, which must be an array, an object or `null`.
+	pub fn from_json(json: &str) -> Result<Self, Error> {
+		let raw: Box<RawValue> = serde_json::from_str(json).map_err(|_| Error::parse_error())?;
+		Self::from_raw(Some(raw)).ok_or_else(Error::invalid_request)
+	}
+
+	fn from_raw(raw: Option<Box<RawValue>>) -> Option<Self> {
+		match raw {
+			None => Some(RawParams(None)),
+			Some(raw) => match raw.get().trim_start().as_bytes().first() {
+				Some(b'n') => Some(RawParams(None)),
+				Some(b'[') | Some(b'{') => Some(RawParams(Some(raw))),
+				_ => None,
+			},
+		}
+	}
+
+	/// Returns the params JSON text, `None` if there are no params.
+	pub fn get(&self) -> Option<&str> {
+		self.0.as_ref().map(|raw| raw.get())
+	}
+
+	/// Parse params into expected types.
+	///
+	/// Borrowed types (e.g. `&str`) are supported as long as they do not need unescaping.
+	pub fn parse<'a, D>(&'a self) -> Result<D, Error>
+	where
+		D: Deserialize<'a>,
+	{
+		serde_json::from_str(self.get().unwrap_or("null"))
+			.map_err(|e| Error::invalid_params(format!("Invalid params: {}.", without_position(&e))))
+	}
+
+	/// Returns number of positional params, `0` if there are no params.
+	pub fn array_len(&self) -> Result<usize, Error> {
+		match self.get().map(|json| json.trim_start().as_bytes()[0]) {
+			None => Ok(0),
+			Some(b'[') => self.parse::<Vec<&RawValue>>().map(|params| params.len()),
+			Some(_) => Err(Error::invalid_params("`params` should be an array")),
+		}
+	}
+
+	/// Returns `true` if params are given by name, as an object.
+	pub fn is_map(&self) -> bool {
+		matches!(self.get().map(|json| json.trim_start().as_bytes()[0]), Some(b'{'))
+	}
+
+	/// Check for no params, returns Err if any params
+	pub fn expect_no_params(&self) -> Result<(), Error> {
+		match self.array_len() {
+			Ok(0) => Ok(()),
+			_ => Err(Error::invalid_params_with_details(
+				"No parameters were expected",
+				self.get().unwrap_or_default(),
+			)),
+		}
+	}
+
+	/// Converts into parsed `Params`.
+	pub fn into_params(self) -> Result<Params, Error> {
+		match self.0 {
+			None => Ok(Params::None),
+			Some(raw) => serde_json::from_str(raw.get()).map_err(|_| Error::new(ErrorCode::InvalidParams)),
+		}
+	}
+}
+
+/// Error message without the position within params, the same as produced for parsed `Params`.
+fn without_position(error: &serde_json::Error) -> String {
+	let message = error.to_string();
+	let suffix = format!(" at line {} column {}", error.line(), error.column());
+	match message.strip_suffix(&suffix) {
+		Some(message) => message.to_owned(),
+		None => message,
+	}
+}
+
+/// Request parameters, either already parsed or kept as JSON text.
+///
+/// Used by methods which can handle both request pipelines,
+/// e.g. the ones generated by `jsonrpc-derive`.
+#[derive(Debug, Clone)]
+pub enum AnyParams {
+	/// Parsed params.
+	Parsed(Params),
+	/// Unparsed params.
+	Raw(RawParams),
+}
+
+impl AnyParams {
+	/// Parse params into expected types.
+	pub fn parse<D>(self) -> Result<D, Error>
+	where
+		D: DeserializeOwned,
+	{
+		match self {
+			AnyParams::Parsed(params) => params.parse(),
+			AnyParams::Raw(params) => params.parse(),
+		}
+	}
+
+	/// Returns number of positional params, `0` if there are no params.
+	pub fn array_len(&self) -> Result<usize, Error> {
+		match *self {
+			AnyParams::Parsed(Params::Array(ref params)) => Ok(params.len()),
+			AnyParams::Parsed(Params::None) => Ok(0),
+			AnyParams::Parsed(_) => Err(Error::invalid_params("`params` should be an array")),
+			AnyParams::Raw(ref params) => params.array_len(),
+		}
+	}
+
+	/// Returns `true` if params are given by name, as an object.
+	pub fn is_map(&self) -> bool {
+		match *self {
+			AnyParams::Parsed(ref params) => matches!(*params, Params::Map(_)),
+			AnyParams::Raw(ref params) => params.is_map(),
+		}
+	}
+
+	/// Check for no params, returns Err if any params
+	pub fn expect_no_params(self) -> Result<(), Error> {
+		match self {
+			AnyParams::Parsed(params) => params.expect_no_params(),
+			AnyParams::Raw(params) => params.expect_no_params(),
+		}
+	}
+
+	/// Converts into parsed `Params`.
+	pub fn into_params(self) -> Result<Params, Error> {
+		match self {
+			AnyParams::Parsed(params) => Ok(params),
+			AnyParams::Raw(params) => params.into_params(),
+		}
+	}
+}
+
+impl From<Params> for AnyParams {
+	fn from(params: Params) -> Self {
+		AnyParams::Parsed(params)
+	}
+}
+
+impl From<RawParams> for AnyParams {
+	fn from(params: RawParams) -> Self {
+		AnyParams::Raw(params)
+	}
+}
+
+/// Represents jsonrpc request which is a method call.
+#[derive(Debug, Clone)]
+pub struct RawMethodCall {
+	/// A String specifying the version of the JSON-RPC protocol.
+	pub jsonrpc: Option<Version>,
+	/// A String containing the name of the method to be invoked.
+	pub method: String,
+	/// Unparsed parameter values to be used during the invocation of the method.
+	pub params: RawParams,
+	/// An identifier established by the Client.
+	pub id: Id,
+}
+
+/// Represents jsonrpc request which is a notification.
+#[derive(Debug, Clone)]
+pub struct RawNotification {
+	/// A String specifying the version of the JSON-RPC protocol.
+	pub jsonrpc: Option<Version>,
+	/// A String containing the name of the method to be invoked.
+	pub method: String,
+	/// Unparsed parameter values to be used during the invocation of the method.
+	pub params: RawParams,
+}
+
+/// Represents single jsonrpc call.
+#[derive(Debug, Clone)]
+pub enum RawCall {
+	/// Call method
+	MethodCall(RawMethodCall),
+	/// Fire notification
+	Notification(RawNotification),
+	/// Invalid call
+	Invalid {
+		/// Call id (if known)
+		id: Id,
+	},
+}
+
+/// Represents jsonrpc request.
+#[derive(Debug, Clone)]
+pub enum RawRequest {
+	/// Single request (call)
+	Single(RawCall),
+	/// Batch of requests (calls)
+	Batch(Vec<RawCall>),
+}
+
+impl RawRequest {
+	/// Parses request, keeping params of all calls unparsed.
+	///
+	/// Fails with `ParseError` in the same cases `Request` deserialization fails.
+	pub fn from_json(json: &str) -> Result<Self, Error> {
+		if json.trim_start().starts_with('[') {
+			let calls: Vec<&RawValue> = serde_json::from_str(json).map_err(|_| Error::parse_error())?;
+			calls
+				.into_iter()
+				.map(RawCall::from_raw)
+				.collect::<Result<_, _>>()
+				.map(RawRequest::Batch)
+		} else {
+			let call: &RawValue = serde_json::from_str(json).map_err(|_| Error::parse_error())?;
+			RawCall::from_raw(call).map(RawRequest::Single)
+		}
+	}
+}
+
+impl RawCall {
+	fn from_raw(raw: &RawValue) -> Result<Self, Error> {
+		if !raw.get().trim_start().starts_with('{') {
+			return Err(Error::parse_error());
+		}
+
+		let call = serde_json::from_str::<CallObject>(raw.get())
+			.ok()
+			.and_then(|mut call| Some((RawParams::from_raw(call.params.take())?, call)));
+		let (params, call) = match call {
+			Some(call) => call,
+			None => {
+				let id = serde_json::from_str::<InvalidCallObject>(raw.get()).map_err(|_| Error::parse_error())?;
+				return Ok(RawCall::Invalid { id: id.id });
+			}
+		};
+
+		Ok(match call.id {
+			Some(id) => RawCall::MethodCall(RawMethodCall {
+				jsonrpc: call.jsonrpc,
+				method: call.method,
+				params,
+				id,
+			}),
+			None => RawCall::Notification(RawNotification {
+				jsonrpc: call.jsonrpc,
+				method: call.method,
+				params,
+			}),
+		})
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CallObject {
+	jsonrpc: Option<Version>,
+	method: String,
+	#[serde(default)]
+	params: Option<Box<RawValue>>,
+	#[serde(default, deserialize_with = "deserialize_some")]
+	id: Option<Id>,
+}
+
+#[derive(Deserialize)]
+struct InvalidCallObject {
+	#[serde(default = "default_id")]
+	id: Id,
+}
+
+fn default_id() -> Id {
+	Id::Null
+}
+
+/// Distinguishes `"id": null` from a missing id.
+fn deserialize_some<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Id>, D::Error> {
+	Id::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn raw_request_deserialize() {
+		let s = r#"[
+			{"jsonrpc": "2.0", "method": "update", "params": [1, "a"], "id": null},
+			{"jsonrpc": "2.0", "method": "notify", "params": {"a": 1}},
+			{"jsonrpc": "2.0", "method": "update", "params": 5, "id": 2},
+			{"jsonrpc": "2.0", "method": "update", "extra": true, "id": 3}
+		]"#;
+
+		let calls = match RawRequest::from_json(s).unwrap() {
+			RawRequest::Batch(calls) => calls,
+			RawRequest::Single(_) => panic!("Expected a batch."),
+		};
+
+		match calls[0] {
+			RawCall::MethodCall(ref call) => {
+				assert_eq!(call.id, Id::Null);
+				assert_eq!(call.params.get(), Some(r#"[1, "a"]"#));
+				assert_eq!(call.params.array_len(), Ok(2));
+				assert_eq!(call.params.parse::<(u64, &str)>(), Ok((1, "a")));
+			}
+			ref call => panic!("Unexpected call: {:?}", call),
+		}
+		match calls[1] {
+			RawCall::Notification(ref call) => {
+				assert_eq!(call.method, "notify");
+				assert!(call.params.array_len().is_err());
+			}
+			ref call => panic!("Unexpected call: {:?}", call),
+		}
+		assert!(matches!(calls[2], RawCall::Invalid { id: Id::Num(2) }));
+		assert!(matches!(calls[3], RawCall::Invalid { id: Id::Num(3) }));
+	}
+
+	#[test]
+	fn raw_request_parse_error() {
+		assert_eq!(RawRequest::from_json("[1]").unwrap_err(), Error::parse_error());
+		assert_eq!(RawRequest::from_json("{").unwrap_err(), Error::parse_error());
+	}
+}
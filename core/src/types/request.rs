@@ -1,9 +1,14 @@
 //! jsonrpc request
 
-use super::{Id, Params, Version};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use super::{Id, Params, Value, Version};
 
 /// Represents jsonrpc request which is a method call.
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+///
+/// Calls with `Version::V1` are serialized in 1.0 format, with positional params.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct MethodCall {
 	/// A String specifying the version of the JSON-RPC protocol.
@@ -21,7 +26,9 @@ pub struct MethodCall {
 }
 
 /// Represents jsonrpc request which is a notification.
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+///
+/// Notifications with `Version::V1` are serialized in 1.0 format, as calls with `null` id.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Notification {
 	/// A String specifying the version of the JSON-RPC protocol.
@@ -34,6 +41,64 @@ pub struct Notification {
 	pub params: Params,
 }
 
+impl Serialize for MethodCall {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let mut call = match self.jsonrpc {
+			Some(Version::V1) => {
+				let mut call = serializer.serialize_struct("MethodCall", 3)?;
+				call.serialize_field("method", &self.method)?;
+				serialize_positional(&mut call, &self.params)?;
+				call
+			}
+			jsonrpc => {
+				let mut call = serializer.serialize_struct("MethodCall", 4)?;
+				call.serialize_field("jsonrpc", &jsonrpc)?;
+				call.serialize_field("method", &self.method)?;
+				call.serialize_field("params", &self.params)?;
+				call
+			}
+		};
+		call.serialize_field("id", &self.id)?;
+		call.end()
+	}
+}
+
+impl Serialize for Notification {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		match self.jsonrpc {
+			Some(Version::V1) => {
+				let mut notification = serializer.serialize_struct("Notification", 3)?;
+				notification.serialize_field("method", &self.method)?;
+				serialize_positional(&mut notification, &self.params)?;
+				notification.serialize_field("id", &Id::Null)?;
+				notification.end()
+			}
+			jsonrpc => {
+				let mut notification = serializer.serialize_struct("Notification", 3)?;
+				notification.serialize_field("jsonrpc", &jsonrpc)?;
+				notification.serialize_field("method", &self.method)?;
+				notification.serialize_field("params", &self.params)?;
+				notification.end()
+			}
+		}
+	}
+}
+
+/// Serializes params of a JSON-RPC 1.0 call, which are required, so missing params are sent as an empty array.
+fn serialize_positional<S: SerializeStruct>(call: &mut S, params: &Params) -> Result<(), S::Error> {
+	const NO_PARAMS: &[Value] = &[];
+	match params {
+		Params::None => call.serialize_field("params", NO_PARAMS),
+		params => call.serialize_field("params", params),
+	}
+}
+
 /// Represents single jsonrpc call.
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -160,6 +225,30 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn jsonrpc_1_serialize() {
+		let m = MethodCall {
+			jsonrpc: Some(Version::V1),
+			method: "getblockcount".to_owned(),
+			params: Params::None,
+			id: Id::Num(1),
+		};
+		let n = Notification {
+			jsonrpc: Some(Version::V1),
+			method: "update".to_owned(),
+			params: Params::Array(vec![Value::from(1)]),
+		};
+
+		assert_eq!(
+			serde_json::to_string(&m).unwrap(),
+			r#"{"method":"getblockcount","params":[],"id":1}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&n).unwrap(),
+			r#"{"method":"update","params":[1],"id":null}"#
+		);
+	}
+
 	#[test]
 	fn notification_deserialize() {
 		use serde_json;
@@ -1,9 +1,14 @@
 //! jsonrpc response
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use super::{Error, ErrorCode, Id, Value, Version};
 use crate::Result as CoreResult;
 
 /// Successful response
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+///
+/// Responses with `Version::V1` are serialized in 1.0 format, with `null` error.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Success {
 	/// Protocol version
@@ -16,7 +21,9 @@ pub struct Success {
 }
 
 /// Unsuccessful response
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+///
+/// Responses with `Version::V1` are serialized in 1.0 format, with `null` result.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Failure {
 	/// Protocol Version
@@ -28,9 +35,66 @@ pub struct Failure {
 	pub id: Id,
 }
 
+impl Serialize for Success {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let mut success = match self.jsonrpc {
+			Some(Version::V1) => {
+				let mut success = serializer.serialize_struct("Success", 3)?;
+				success.serialize_field("result", &self.result)?;
+				success.serialize_field("error", &None::<Error>)?;
+				success
+			}
+			Some(jsonrpc) => {
+				let mut success = serializer.serialize_struct("Success", 3)?;
+				success.serialize_field("jsonrpc", &jsonrpc)?;
+				success.serialize_field("result", &self.result)?;
+				success
+			}
+			None => {
+				let mut success = serializer.serialize_struct("Success", 2)?;
+				success.serialize_field("result", &self.result)?;
+				success
+			}
+		};
+		success.serialize_field("id", &self.id)?;
+		success.end()
+	}
+}
+
+impl Serialize for Failure {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let mut failure = match self.jsonrpc {
+			Some(Version::V1) => {
+				let mut failure = serializer.serialize_struct("Failure", 3)?;
+				failure.serialize_field("result", &Value::Null)?;
+				failure.serialize_field("error", &self.error)?;
+				failure
+			}
+			Some(jsonrpc) => {
+				let mut failure = serializer.serialize_struct("Failure", 3)?;
+				failure.serialize_field("jsonrpc", &jsonrpc)?;
+				failure.serialize_field("error", &self.error)?;
+				failure
+			}
+			None => {
+				let mut failure = serializer.serialize_struct("Failure", 2)?;
+				failure.serialize_field("error", &self.error)?;
+				failure
+			}
+		};
+		failure.serialize_field("id", &self.id)?;
+		failure.end()
+	}
+}
+
 /// Represents output - failure or success
-#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 #[serde(untagged)]
 pub enum Output {
 	/// Success
@@ -39,6 +103,45 @@ pub enum Output {
 	Failure(Failure),
 }
 
+/// Output in JSON-RPC 1.0 format, with both `result` and `error` present.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct V1Output {
+	#[serde(default)]
+	result: Value,
+	error: Option<Error>,
+	id: Id,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OutputRepr {
+	Success(Success),
+	Failure(Failure),
+	V1(V1Output),
+}
+
+impl<'de> Deserialize<'de> for Output {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let jsonrpc = Some(Version::V1);
+		Ok(match OutputRepr::deserialize(deserializer)? {
+			OutputRepr::Success(success) => Output::Success(success),
+			OutputRepr::Failure(failure) => Output::Failure(failure),
+			OutputRepr::V1(V1Output {
+				error: Some(error), id, ..
+			}) => Output::Failure(Failure { jsonrpc, error, id }),
+			OutputRepr::V1(V1Output {
+				result,
+				error: None,
+				id,
+			}) => Output::Success(Success { jsonrpc, result, id }),
+		})
+	}
+}
+
 impl Output {
 	/// Creates new output given `Result`, `Id` and `Version`.
 	pub fn from(result: CoreResult<Value>, id: Id, jsonrpc: Option<Version>) -> Self {
@@ -294,3 +397,55 @@ fn should_parse_empty_response_as_batch() {
 	);
 	assert_eq!(deserialized2.unwrap(), Response::Batch(vec![]));
 }
+
+#[test]
+fn jsonrpc_1_output_serialize() {
+	use serde_json;
+
+	let so = Output::Success(Success {
+		jsonrpc: Some(Version::V1),
+		result: Value::from(1),
+		id: Id::Num(1),
+	});
+	let fo = Output::Failure(Failure {
+		jsonrpc: Some(Version::V1),
+		error: Error::parse_error(),
+		id: Id::Num(2),
+	});
+
+	assert_eq!(
+		serde_json::to_string(&so).unwrap(),
+		r#"{"result":1,"error":null,"id":1}"#
+	);
+	assert_eq!(
+		serde_json::to_string(&fo).unwrap(),
+		r#"{"result":null,"error":{"code":-32700,"message":"Parse error"},"id":2}"#
+	);
+}
+
+#[test]
+fn jsonrpc_1_output_deserialize() {
+	use serde_json;
+
+	let dso = r#"{"result":{"blocks":10},"error":null,"id":"1"}"#;
+	let dfo = r#"{"result":null,"error":{"code":-32700,"message":"Parse error"},"id":2}"#;
+
+	let deserialized: Output = serde_json::from_str(dso).unwrap();
+	assert_eq!(
+		deserialized,
+		Output::Success(Success {
+			jsonrpc: Some(Version::V1),
+			result: serde_json::json!({"blocks": 10}),
+			id: Id::Str("1".into()),
+		})
+	);
+	let deserialized: Output = serde_json::from_str(dfo).unwrap();
+	assert_eq!(
+		deserialized,
+		Output::Failure(Failure {
+			jsonrpc: Some(Version::V1),
+			error: Error::parse_error(),
+			id: Id::Num(2),
+		})
+	);
+}
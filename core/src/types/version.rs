@@ -7,6 +7,11 @@ use std::fmt;
 /// Protocol Version
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
 pub enum Version {
+	/// JSONRPC 1.0
+	///
+	/// Messages of this version are serialized in 1.0 format, without the `jsonrpc` field,
+	/// see `Compatibility::V1Strict`. Servers in other modes reject requests with this version.
+	V1,
 	/// JSONRPC 2.0
 	V2,
 }
@@ -17,6 +22,7 @@ impl Serialize for Version {
 		S: Serializer,
 	{
 		match *self {
+			Version::V1 => serializer.serialize_str("1.0"),
 			Version::V2 => serializer.serialize_str("2.0"),
 		}
 	}
@@ -45,6 +51,7 @@ impl<'a> Visitor<'a> for VersionVisitor {
 		E: de::Error,
 	{
 		match value {
+			"1.0" => Ok(Version::V1),
 			"2.0" => Ok(Version::V2),
 			_ => Err(de::Error::custom("invalid version")),
 		}
@@ -204,6 +204,7 @@ use syn::parse_macro_input;
 mod options;
 mod params_style;
 mod rpc_attr;
+mod rpc_error;
 mod rpc_trait;
 mod to_client;
 mod to_delegate;
@@ -212,6 +213,39 @@ mod to_delegate;
 /// wires up methods decorated with `#[rpc]` or `#[pubsub]` attributes.
 /// Attach the delegate to an `IoHandler` and the methods are now callable
 /// via JSON-RPC.
+///
+/// Params are passed as an array by default (`params = "positional"`), the style can be changed
+/// for the whole trait (`#[rpc(params = "...")]`) or a single method (`#[rpc(name = "...", params = "...")]`):
+/// - `named`: params are an object with keys named after the method arguments,
+/// - `both`: the server accepts both forms, the client sends an array,
+/// - `raw`: the method takes `Params` as they were received.
+///
+/// Note that the generated client of a `both` method can only send positional params. To call it with named
+/// params, create a `TypedClient` from the same `RpcChannel` and pass an object (e.g. `serde_json::json!({"a": 1})`)
+/// as args of its `call_method`.
+///
+/// Trailing `Option` arguments may be omitted by callers. So may trailing arguments with a default value,
+/// given as `#[rpc(default)]` (`Default::default()`) or `#[rpc(default = "expression")]`:
+///
+/// ```ignore
+/// #[rpc(name = "pow")]
+/// fn pow(&self, base: u64, #[rpc(default = "2")] exp: u32) -> Result<u64>;
+/// ```
+///
+/// With `#[rpc(namespace = "eth")]` names and aliases of all methods in the trait are prefixed with
+/// the namespace and a separator (`_` by default, `separator = "."` changes it), so `name = "getBalance"`
+/// is served and called as `eth_getBalance`. Subscription names are used as they are. The server trait
+/// also gets `to_delegate_with_namespace("testnet")`, mounting the same methods under another namespace.
+///
+/// Methods and subscriptions can be deprecated with `#[rpc(name = "...", deprecated = "note")]`.
+/// The generated client method is marked `#[deprecated]`, the server keeps handling calls but logs
+/// a warning (at most once a minute per method) and lists the method as deprecated in `rpc.discover`.
+///
+/// With `#[rpc(open_rpc)]` the server trait also gets an `open_rpc_spec()` function returning
+/// an OpenRPC document of its methods, e.g. `RpcImpl::open_rpc_spec()`. Types of all params and results
+/// have to implement `schemars::JsonSchema`, and the `schemars` feature of `jsonrpc-core` has to be enabled.
+/// Methods served by `rpc.discover` are then described with the same schemas, inlined. Without `open_rpc`
+/// their schemas are guessed from type names.
 #[proc_macro_attribute]
 pub fn rpc(args: TokenStream, input: TokenStream) -> TokenStream {
 	let input_toks = parse_macro_input!(input as syn::Item);
@@ -227,3 +261,38 @@ pub fn rpc(args: TokenStream, input: TokenStream) -> TokenStream {
 		Err(err) => err.to_compile_error().into(),
 	}
 }
+
+/// Derives `RpcErrorCode` and `From<_> for jsonrpc_core::Error` for an application error enum.
+///
+/// Each variant needs a stable `code` and may override the `message` (defaults to variant name).
+/// Fields of the variant are sent as `data`: a single field as is, named fields as an object
+/// and multiple unnamed fields as an array.
+///
+/// ```
+/// use jsonrpc_core::{Error, ErrorCode, RpcErrorCode};
+/// use jsonrpc_derive::RpcError;
+///
+/// #[derive(Debug, PartialEq, RpcError)]
+/// pub enum AccountError {
+///     #[rpc_error(code = -32010, message = "Account not found")]
+///     NotFound,
+///     #[rpc_error(code = -32011, message = "Insufficient balance")]
+///     InsufficientBalance { required: u64, available: u64 },
+/// }
+///
+/// let error: Error = AccountError::InsufficientBalance { required: 5, available: 3 }.into();
+/// assert_eq!(error.code, ErrorCode::ServerError(-32011));
+/// assert_eq!(
+///     AccountError::from_error(&error),
+///     Some(AccountError::InsufficientBalance { required: 5, available: 3 })
+/// );
+/// ```
+#[proc_macro_derive(RpcError, attributes(rpc_error))]
+pub fn rpc_error(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as syn::DeriveInput);
+
+	match rpc_error::rpc_error_impl(input) {
+		Ok(output) => output.into(),
+		Err(err) => err.to_compile_error().into(),
+	}
+}
@@ -5,13 +5,20 @@ use crate::rpc_attr::path_eq_str;
 
 const CLIENT_META_WORD: &str = "client";
 const SERVER_META_WORD: &str = "server";
+const OPEN_RPC_META_WORD: &str = "open_rpc";
 const PARAMS_META_KEY: &str = "params";
+const NAMESPACE_META_KEY: &str = "namespace";
+const SEPARATOR_META_KEY: &str = "separator";
+const DEFAULT_SEPARATOR: &str = "_";
 
 #[derive(Debug)]
 pub struct DeriveOptions {
 	pub enable_client: bool,
 	pub enable_server: bool,
 	pub params_style: ParamStyle,
+	pub open_rpc: bool,
+	pub namespace: Option<String>,
+	pub separator: String,
 }
 
 impl DeriveOptions {
@@ -20,11 +27,22 @@ impl DeriveOptions {
 			enable_client,
 			enable_server,
 			params_style,
+			open_rpc: false,
+			namespace: None,
+			separator: DEFAULT_SEPARATOR.into(),
 		}
 	}
 
+	/// Prefix of all method names of the trait, e.g. `eth_` for `namespace = "eth"`.
+	pub fn namespace_prefix(&self) -> Option<String> {
+		self.namespace
+			.as_ref()
+			.map(|namespace| format!("{}{}", namespace, self.separator))
+	}
+
 	pub fn try_from(args: syn::AttributeArgs) -> Result<Self, syn::Error> {
 		let mut options = DeriveOptions::new(false, false, ParamStyle::default());
+		let mut separator = None;
 		for arg in args {
 			if let syn::NestedMeta::Meta(meta) = arg {
 				match meta {
@@ -40,6 +58,7 @@ impl DeriveOptions {
 						{
 							CLIENT_META_WORD => options.enable_client = true,
 							SERVER_META_WORD => options.enable_server = true,
+							OPEN_RPC_META_WORD => options.open_rpc = true,
 							_ => {}
 						};
 					}
@@ -49,6 +68,20 @@ impl DeriveOptions {
 								options.params_style = ParamStyle::from_str(&lit.value())
 									.map_err(|e| syn::Error::new_spanned(nv.clone(), e))?;
 							}
+						} else if path_eq_str(&nv.path, NAMESPACE_META_KEY) {
+							match nv.lit {
+								syn::Lit::Str(ref lit) if !lit.value().is_empty() => {
+									options.namespace = Some(lit.value())
+								}
+								_ => {
+									return Err(syn::Error::new_spanned(nv, "`namespace` should be a non-empty string"))
+								}
+							}
+						} else if path_eq_str(&nv.path, SEPARATOR_META_KEY) {
+							match nv.lit {
+								syn::Lit::Str(ref lit) => separator = Some((lit.value(), nv.clone())),
+								_ => return Err(syn::Error::new_spanned(nv, "`separator` should be a string")),
+							}
 						} else {
 							return Err(syn::Error::new_spanned(nv, "Unexpected RPC attribute key"));
 						}
@@ -62,9 +95,20 @@ impl DeriveOptions {
 			options.enable_client = true;
 			options.enable_server = true;
 		}
-		if options.enable_server && options.params_style == ParamStyle::Named {
-			// This is not allowed at this time
-			panic!("Server code generation only supports `params = \"positional\"` (default) or `params = \"raw\" at this time.")
+		if let Some((separator, nv)) = separator {
+			if options.namespace.is_none() {
+				return Err(syn::Error::new_spanned(
+					nv,
+					"`separator` can only be used with `namespace`",
+				));
+			}
+			options.separator = separator;
+		}
+		if options.open_rpc && !options.enable_server {
+			return Err(syn::Error::new(
+				proc_macro2::Span::call_site(),
+				"`open_rpc` generates a method of the server trait, it can't be used with `client` only",
+			));
 		}
 		Ok(options)
 	}
@@ -2,12 +2,14 @@ use std::str::FromStr;
 
 const POSITIONAL: &str = "positional";
 const NAMED: &str = "named";
+const BOTH: &str = "both";
 const RAW: &str = "raw";
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ParamStyle {
 	Positional,
 	Named,
+	Both,
 	Raw,
 }
 
@@ -24,10 +26,11 @@ impl FromStr for ParamStyle {
 		match s {
 			POSITIONAL => Ok(Self::Positional),
 			NAMED => Ok(Self::Named),
+			BOTH => Ok(Self::Both),
 			RAW => Ok(Self::Raw),
 			_ => Err(format!(
-				"Invalid value for params key. Must be one of [{}, {}, {}]",
-				POSITIONAL, NAMED, RAW
+				"Invalid value for params key. Must be one of [{}, {}, {}, {}]",
+				POSITIONAL, NAMED, BOTH, RAW
 			)),
 		}
 	}
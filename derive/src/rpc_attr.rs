@@ -12,6 +12,9 @@ pub struct RpcMethodAttribute {
 	pub aliases: Vec<String>,
 	pub kind: AttributeKind,
 	pub params_style: Option<ParamStyle>, // None means do not override the top level default
+	pub timeout_ms: Option<u64>,
+	pub deprecated: Option<String>,
+	pub arg_defaults: Vec<Option<syn::Expr>>, // default values of all typed arguments, in order
 }
 
 #[derive(Clone, Debug)]
@@ -44,6 +47,9 @@ const SUBSCRIBE_META_WORD: &str = "subscribe";
 const UNSUBSCRIBE_META_WORD: &str = "unsubscribe";
 const RETURNS_META_WORD: &str = "returns";
 const PARAMS_STYLE_KEY: &str = "params";
+const TIMEOUT_MS_KEY: &str = "timeout_ms";
+const DEFAULT_KEY: &str = "default";
+const DEPRECATED_KEY: &str = "deprecated";
 
 const MULTIPLE_RPC_ATTRIBUTES_ERR: &str = "Expected only a single rpc attribute per method";
 const INVALID_ATTR_PARAM_NAMES_ERR: &str = "Invalid attribute parameter(s):";
@@ -51,6 +57,10 @@ const MISSING_NAME_ERR: &str = "rpc attribute should have a name e.g. `name = \"
 const MISSING_SUB_NAME_ERR: &str = "pubsub attribute should have a subscription name";
 const BOTH_SUB_AND_UNSUB_ERR: &str = "pubsub attribute annotated with both subscribe and unsubscribe";
 const NEITHER_SUB_OR_UNSUB_ERR: &str = "pubsub attribute not annotated with either subscribe or unsubscribe";
+const INVALID_TIMEOUT_ERR: &str = "timeout_ms should be a positive integer e.g. `timeout_ms = 1000`";
+const INVALID_DEFAULT_ERR: &str =
+	"rpc attribute of an argument should be `#[rpc(default)]` or `#[rpc(default = \"expression\")]`";
+const INVALID_DEPRECATED_ERR: &str = "deprecated should be a note e.g. `deprecated = \"Use `other_method` instead\"`";
 
 impl RpcMethodAttribute {
 	pub fn parse_attr(method: &syn::TraitItemMethod) -> Result<Option<RpcMethodAttribute>> {
@@ -61,10 +71,23 @@ impl RpcMethodAttribute {
 			.filter_map(|attr| Self::parse_meta(attr, &output))
 			.collect::<Result<Vec<_>>>()?;
 
-		if attrs.len() <= 1 {
-			Ok(attrs.first().cloned())
-		} else {
-			Err(Error::new_spanned(method, MULTIPLE_RPC_ATTRIBUTES_ERR))
+		if attrs.len() > 1 {
+			return Err(Error::new_spanned(method, MULTIPLE_RPC_ATTRIBUTES_ERR));
+		}
+		match attrs.into_iter().next() {
+			Some(mut attr) => {
+				attr.arg_defaults = method
+					.sig
+					.inputs
+					.iter()
+					.filter_map(|arg| match arg {
+						syn::FnArg::Typed(arg) => Some(parse_arg_default(arg)),
+						_ => None,
+					})
+					.collect::<Result<_>>()?;
+				Ok(Some(attr))
+			}
+			None => Ok(None),
 		}
 	}
 
@@ -89,14 +112,19 @@ impl RpcMethodAttribute {
 										// "`raw_params` will be deprecated in a future release. Use `params = \"raw\" instead`"
 										Ok(Some(ParamStyle::Raw))
 									}
-									false => get_meta_list(meta).map_or(Ok(None), |ml| get_params_style(ml).map(Some)),
+									false => get_meta_list(meta).map_or(Ok(None), get_params_style),
 								}?;
+								let timeout_ms = get_meta_list(meta).map_or(Ok(None), get_timeout_ms)?;
+								let deprecated = get_meta_list(meta).map_or(Ok(None), get_deprecated)?;
 								Ok(RpcMethodAttribute {
 									attr: attr.clone(),
 									name,
 									aliases,
 									kind,
 									params_style,
+									timeout_ms,
+									deprecated,
+									arg_defaults: Vec::new(),
 								})
 							})
 					})
@@ -120,6 +148,11 @@ impl RpcMethodAttribute {
 			return Err(syn::Error::new_spanned(output, &"Notifications must return ()"));
 		}
 
+		let timeout = get_meta_list(meta).and_then(|ml| get_name_lit(TIMEOUT_MS_KEY, ml));
+		if is_notification && timeout.is_some() {
+			return Err(syn::Error::new_spanned(output, "Notifications can not have a timeout"));
+		}
+
 		Ok(AttributeKind::Rpc {
 			has_metadata,
 			returns,
@@ -162,6 +195,35 @@ impl RpcMethodAttribute {
 	}
 }
 
+/// Parses default value of an argument, given as `#[rpc(default)]` or `#[rpc(default = "expression")]`.
+fn parse_arg_default(arg: &syn::PatType) -> Result<Option<syn::Expr>> {
+	let mut attrs = arg.attrs.iter().filter(|attr| is_rpc_attr(attr));
+	let attr = match (attrs.next(), attrs.next()) {
+		(None, _) => return Ok(None),
+		(Some(attr), None) => attr,
+		(Some(_), Some(attr)) => return Err(Error::new_spanned(attr, MULTIPLE_RPC_ATTRIBUTES_ERR)),
+	};
+	let meta = attr.parse_meta()?;
+	let nested = get_meta_list(&meta)
+		.filter(|ml| ml.nested.len() == 1)
+		.map(|ml| &ml.nested[0]);
+	match nested {
+		Some(syn::NestedMeta::Meta(syn::Meta::Path(path))) if path_eq_str(path, DEFAULT_KEY) => {
+			Ok(Some(syn::parse_quote!(::std::default::Default::default())))
+		}
+		Some(syn::NestedMeta::Meta(syn::Meta::NameValue(nv))) if path_eq_str(&nv.path, DEFAULT_KEY) => match nv.lit {
+			syn::Lit::Str(ref lit) => lit.parse().map(Some),
+			ref lit => Err(Error::new_spanned(lit, INVALID_DEFAULT_ERR)),
+		},
+		_ => Err(Error::new_spanned(attr, INVALID_DEFAULT_ERR)),
+	}
+}
+
+/// Returns `true` for `#[rpc(...)]` attributes, which have to be removed from the generated code.
+pub fn is_rpc_attr(attr: &syn::Attribute) -> bool {
+	path_eq_str(&attr.path, RPC_ATTR_NAME)
+}
+
 fn validate_attribute_meta(meta: syn::Meta) -> Result<syn::Meta> {
 	#[derive(Default)]
 	struct Visitor {
@@ -191,7 +253,13 @@ fn validate_attribute_meta(meta: syn::Meta) -> Result<syn::Meta> {
 			validate_idents(
 				&meta,
 				&visitor.name_value_names,
-				&[RPC_NAME_KEY, RETURNS_META_WORD, PARAMS_STYLE_KEY],
+				&[
+					RPC_NAME_KEY,
+					RETURNS_META_WORD,
+					PARAMS_STYLE_KEY,
+					TIMEOUT_MS_KEY,
+					DEPRECATED_KEY,
+				],
 			)?;
 			validate_idents(&meta, &visitor.meta_list_names, &[ALIASES_KEY])
 		}
@@ -201,7 +269,11 @@ fn validate_attribute_meta(meta: syn::Meta) -> Result<syn::Meta> {
 				&visitor.meta_words,
 				&[SUBSCRIBE_META_WORD, UNSUBSCRIBE_META_WORD, RAW_PARAMS_META_WORD],
 			)?;
-			validate_idents(&meta, &visitor.name_value_names, &[SUBSCRIPTION_NAME_KEY, RPC_NAME_KEY])?;
+			validate_idents(
+				&meta,
+				&visitor.name_value_names,
+				&[SUBSCRIPTION_NAME_KEY, RPC_NAME_KEY, DEPRECATED_KEY],
+			)?;
 			validate_idents(&meta, &visitor.meta_list_names, &[ALIASES_KEY])
 		}
 		_ => Ok(meta), // ignore other attributes - compiler will catch unknown ones
@@ -237,14 +309,20 @@ fn get_meta_list(meta: &syn::Meta) -> Option<&syn::MetaList> {
 }
 
 fn get_name_value(key: &str, ml: &syn::MetaList) -> Option<String> {
+	get_name_lit(key, ml).and_then(|lit| {
+		if let syn::Lit::Str(ref lit) = lit {
+			Some(lit.value())
+		} else {
+			None
+		}
+	})
+}
+
+fn get_name_lit<'a>(key: &str, ml: &'a syn::MetaList) -> Option<&'a syn::Lit> {
 	ml.nested.iter().find_map(|nested| {
 		if let syn::NestedMeta::Meta(syn::Meta::NameValue(mnv)) = nested {
 			if path_eq_str(&mnv.path, key) {
-				if let syn::Lit::Str(ref lit) = mnv.lit {
-					Some(lit.value())
-				} else {
-					None
-				}
+				Some(&mnv.lit)
 			} else {
 				None
 			}
@@ -254,6 +332,25 @@ fn get_name_value(key: &str, ml: &syn::MetaList) -> Option<String> {
 	})
 }
 
+fn get_deprecated(ml: &syn::MetaList) -> Result<Option<String>> {
+	match get_name_lit(DEPRECATED_KEY, ml) {
+		Some(syn::Lit::Str(lit)) if !lit.value().trim().is_empty() => Ok(Some(lit.value())),
+		Some(lit) => Err(Error::new_spanned(lit, INVALID_DEPRECATED_ERR)),
+		None => Ok(None),
+	}
+}
+
+fn get_timeout_ms(ml: &syn::MetaList) -> Result<Option<u64>> {
+	match get_name_lit(TIMEOUT_MS_KEY, ml) {
+		Some(syn::Lit::Int(lit)) => match lit.base10_parse::<u64>() {
+			Ok(timeout) if timeout > 0 => Ok(Some(timeout)),
+			_ => Err(Error::new_spanned(lit, INVALID_TIMEOUT_ERR)),
+		},
+		Some(lit) => Err(Error::new_spanned(lit, INVALID_TIMEOUT_ERR)),
+		None => Ok(None),
+	}
+}
+
 fn has_meta_word(word: &str, ml: &syn::MetaList) -> bool {
 	ml.nested.iter().any(|nested| {
 		if let syn::NestedMeta::Meta(syn::Meta::Path(p)) = nested {
@@ -292,9 +389,11 @@ fn get_aliases(ml: &syn::MetaList) -> Vec<String> {
 		})
 }
 
-fn get_params_style(ml: &syn::MetaList) -> Result<ParamStyle> {
-	get_name_value(PARAMS_STYLE_KEY, ml).map_or(Ok(ParamStyle::default()), |s| {
-		ParamStyle::from_str(&s).map_err(|e| Error::new_spanned(ml, e))
+fn get_params_style(ml: &syn::MetaList) -> Result<Option<ParamStyle>> {
+	get_name_value(PARAMS_STYLE_KEY, ml).map_or(Ok(None), |s| {
+		ParamStyle::from_str(&s)
+			.map(Some)
+			.map_err(|e| Error::new_spanned(ml, e))
 	})
 }
 
@@ -0,0 +1,240 @@
+use crate::rpc_trait::crate_name;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::collections::HashMap;
+use syn::{
+	parse::{Parse, ParseStream},
+	punctuated::Punctuated,
+	Error, Result, Token,
+};
+
+const RPC_ERROR_ATTR_NAME: &str = "rpc_error";
+const CODE_KEY: &str = "code";
+const MESSAGE_KEY: &str = "message";
+
+const ONLY_ENUMS_ERR: &str = "#[derive(RpcError)] only works with enums";
+const MISSING_ATTR_ERR: &str = "Each variant should have an rpc_error attribute e.g. `#[rpc_error(code = -32010)]`";
+const MULTIPLE_ATTRS_ERR: &str = "Expected only a single rpc_error attribute per variant";
+const MISSING_CODE_ERR: &str = "rpc_error attribute should have a code e.g. `code = -32010`";
+const INVALID_MESSAGE_ERR: &str = "message should be a string literal e.g. `message = \"Not found\"`";
+const INVALID_KEY_ERR: &str = "Invalid rpc_error attribute parameter, expected `code` or `message`";
+const DUPLICATE_CODE_ERR: &str = "Error code is already used by another variant";
+
+/// `key = value` parameter of the `rpc_error` attribute.
+struct RpcErrorParam {
+	key: syn::Ident,
+	value: syn::Expr,
+}
+
+impl Parse for RpcErrorParam {
+	fn parse(input: ParseStream) -> Result<Self> {
+		let key = input.parse()?;
+		input.parse::<Token![=]>()?;
+		let value = input.parse()?;
+		Ok(RpcErrorParam { key, value })
+	}
+}
+
+/// Code and message of a variant.
+struct RpcErrorAttribute {
+	code: syn::Expr,
+	message: String,
+}
+
+impl RpcErrorAttribute {
+	fn parse_attr(variant: &syn::Variant) -> Result<RpcErrorAttribute> {
+		let attrs: Vec<_> = variant
+			.attrs
+			.iter()
+			.filter(|attr| attr.path.is_ident(RPC_ERROR_ATTR_NAME))
+			.collect();
+		let attr = match attrs.as_slice() {
+			[attr] => attr,
+			[] => return Err(Error::new_spanned(variant, MISSING_ATTR_ERR)),
+			_ => return Err(Error::new_spanned(variant, MULTIPLE_ATTRS_ERR)),
+		};
+
+		let params = attr.parse_args_with(Punctuated::<RpcErrorParam, Token![,]>::parse_terminated)?;
+		let mut code = None;
+		let mut message = variant.ident.to_string();
+		for param in params {
+			if param.key == CODE_KEY {
+				code = Some(param.value);
+			} else if param.key == MESSAGE_KEY {
+				message = match param.value {
+					syn::Expr::Lit(syn::ExprLit {
+						lit: syn::Lit::Str(ref lit),
+						..
+					}) => lit.value(),
+					ref value => return Err(Error::new_spanned(value, INVALID_MESSAGE_ERR)),
+				};
+			} else {
+				return Err(Error::new_spanned(param.key, INVALID_KEY_ERR));
+			}
+		}
+
+		match code {
+			Some(code) => Ok(RpcErrorAttribute { code, message }),
+			None => Err(Error::new_spanned(attr, MISSING_CODE_ERR)),
+		}
+	}
+}
+
+/// Returns value of the code if it's an integer literal, possibly negated.
+fn literal_code(code: &syn::Expr) -> Option<i64> {
+	match code {
+		syn::Expr::Lit(syn::ExprLit {
+			lit: syn::Lit::Int(lit),
+			..
+		}) => lit.base10_parse().ok(),
+		syn::Expr::Unary(syn::ExprUnary {
+			op: syn::UnOp::Neg(_),
+			expr,
+			..
+		}) => literal_code(expr).map(|code| -code),
+		syn::Expr::Paren(syn::ExprParen { expr, .. }) => literal_code(expr),
+		_ => None,
+	}
+}
+
+pub fn rpc_error_impl(input: syn::DeriveInput) -> Result<TokenStream> {
+	let variants = match input.data {
+		syn::Data::Enum(ref data) => &data.variants,
+		_ => return Err(Error::new_spanned(&input.ident, ONLY_ENUMS_ERR)),
+	};
+
+	let mut codes = HashMap::new();
+	let mut code_arms = Vec::new();
+	let mut message_arms = Vec::new();
+	let mut data_arms = Vec::new();
+	let mut from_error_branches = Vec::new();
+
+	for variant in variants {
+		let attr = RpcErrorAttribute::parse_attr(variant)?;
+		if let Some(code) = literal_code(&attr.code) {
+			if codes.insert(code, variant.ident.clone()).is_some() {
+				return Err(Error::new_spanned(&attr.code, DUPLICATE_CODE_ERR));
+			}
+		}
+
+		let ident = &variant.ident;
+		let code = &attr.code;
+		let message = &attr.message;
+		code_arms.push(quote! { Self::#ident { .. } => (#code) as i64, });
+		message_arms.push(quote! { Self::#ident { .. } => #message.into(), });
+
+		let (data_arm, from_error) = match variant.fields {
+			syn::Fields::Unit => (quote! { Self::#ident => None, }, quote! { Some(Self::#ident) }),
+			syn::Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => (
+				quote! {
+					Self::#ident(ref field) => Some(_jsonrpc_core::to_value(field).unwrap_or(_jsonrpc_core::Value::Null)),
+				},
+				quote! {
+					_jsonrpc_core::serde_json::from_value(data).ok().map(Self::#ident)
+				},
+			),
+			syn::Fields::Unnamed(ref fields) => {
+				let len = fields.unnamed.len();
+				let bindings: Vec<_> = (0..len)
+					.map(|i| syn::Ident::new(&format!("field_{}", i), Span::call_site()))
+					.collect();
+				let values = (0..len).map(|_| quote! { _jsonrpc_core::serde_json::from_value(items.next()?).ok()? });
+				(
+					quote! {
+						Self::#ident(#(ref #bindings),*) => Some(_jsonrpc_core::Value::Array(vec![
+							#(_jsonrpc_core::to_value(#bindings).unwrap_or(_jsonrpc_core::Value::Null)),*
+						])),
+					},
+					quote! {
+						let mut items = match data {
+							_jsonrpc_core::Value::Array(items) if items.len() == #len => items.into_iter(),
+							_ => return None,
+						};
+						Some(Self::#ident(#(#values),*))
+					},
+				)
+			}
+			syn::Fields::Named(ref fields) => {
+				let names: Vec<_> = fields.named.iter().filter_map(|field| field.ident.as_ref()).collect();
+				let keys: Vec<_> = names.iter().map(|name| name.to_string()).collect();
+				(
+					quote! {
+						Self::#ident { #(ref #names),* } => {
+							let mut map = _jsonrpc_core::serde_json::Map::new();
+							#(
+								map.insert(
+									#keys.into(),
+									_jsonrpc_core::to_value(#names).unwrap_or(_jsonrpc_core::Value::Null),
+								);
+							)*
+							Some(_jsonrpc_core::Value::Object(map))
+						}
+					},
+					quote! {
+						let mut map = match data {
+							_jsonrpc_core::Value::Object(map) => map,
+							_ => return None,
+						};
+						Some(Self::#ident {
+							#(
+								#names: _jsonrpc_core::serde_json::from_value(
+									map.remove(#keys).unwrap_or(_jsonrpc_core::Value::Null),
+								).ok()?,
+							)*
+						})
+					},
+				)
+			}
+		};
+		data_arms.push(data_arm);
+		from_error_branches.push(quote! {
+			if code == (#code) as i64 {
+				return { #from_error };
+			}
+		});
+	}
+
+	let core_name = crate_name("jsonrpc-core")?;
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+	Ok(quote! {
+		const _: () = {
+			use #core_name as _jsonrpc_core;
+
+			impl #impl_generics _jsonrpc_core::RpcErrorCode for #name #ty_generics #where_clause {
+				fn code(&self) -> i64 {
+					match *self {
+						#(#code_arms)*
+					}
+				}
+
+				fn message(&self) -> String {
+					match *self {
+						#(#message_arms)*
+					}
+				}
+
+				fn data(&self) -> Option<_jsonrpc_core::Value> {
+					match *self {
+						#(#data_arms)*
+					}
+				}
+
+				#[allow(unused_variables, unused_mut)]
+				fn from_error(error: &_jsonrpc_core::Error) -> Option<Self> {
+					let code = error.code.code();
+					let data = error.data.clone().unwrap_or(_jsonrpc_core::Value::Null);
+					#(#from_error_branches)*
+					None
+				}
+			}
+
+			impl #impl_generics From<#name #ty_generics> for _jsonrpc_core::Error #where_clause {
+				fn from(error: #name #ty_generics) -> Self {
+					_jsonrpc_core::RpcErrorCode::into_error(error)
+				}
+			}
+		};
+	})
+}
@@ -1,8 +1,7 @@
 use crate::options::DeriveOptions;
-use crate::params_style::ParamStyle;
-use crate::rpc_attr::{AttributeKind, PubSubMethodKind, RpcMethodAttribute};
+use crate::rpc_attr::{is_rpc_attr, AttributeKind, PubSubMethodKind, RpcMethodAttribute};
 use crate::to_client::generate_client_module;
-use crate::to_delegate::{generate_trait_item_method, MethodRegistration, RpcMethod};
+use crate::to_delegate::{generate_open_rpc_spec_method, generate_trait_item_methods, MethodRegistration, RpcMethod};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
 use std::collections::HashMap;
@@ -22,10 +21,6 @@ const MISSING_UNSUBSCRIBE_METHOD_ERR: &str =
 	"Can't find unsubscribe method, expected a method annotated with `unsubscribe` \
 	 e.g. `#[pubsub(subscription = \"hello\", unsubscribe, name = \"hello_unsubscribe\")]`";
 
-pub const USING_NAMED_PARAMS_WITH_SERVER_ERR: &str =
-	"`params = \"named\"` can only be used to generate a client (on a trait annotated with #[rpc(client)]). \
-	 At this time the server does not support named parameters.";
-
 const RPC_MOD_NAME_PREFIX: &str = "rpc_impl_";
 
 struct RpcTrait {
@@ -42,6 +37,11 @@ impl<'a> Fold for RpcTrait {
 			let rpc_method = self.methods.iter().find(|m| m.trait_item == method);
 			rpc_method.map_or(true, |rpc| rpc.attr.attr != *a)
 		});
+		for arg in foldable_method.sig.inputs.iter_mut() {
+			if let syn::FnArg::Typed(arg) = arg {
+				arg.attrs.retain(|a| !is_rpc_attr(a));
+			}
+		}
 		fold::fold_trait_item_method(self, foldable_method)
 	}
 
@@ -59,14 +59,27 @@ impl<'a> Fold for RpcTrait {
 	}
 }
 
-fn compute_method_registrations(item_trait: &syn::ItemTrait) -> Result<(Vec<MethodRegistration>, Vec<RpcMethod>)> {
+fn compute_method_registrations(
+	item_trait: &syn::ItemTrait,
+	options: &DeriveOptions,
+) -> Result<(Vec<MethodRegistration>, Vec<RpcMethod>)> {
 	let methods_result: Result<Vec<_>> = item_trait
 		.items
 		.iter()
 		.filter_map(|trait_item| {
 			if let syn::TraitItem::Method(method) = trait_item {
 				match RpcMethodAttribute::parse_attr(method) {
-					Ok(Some(attr)) => Some(Ok(RpcMethod::new(attr, method.clone()))),
+					Ok(Some(mut attr)) => {
+						// methods without their own params style use the one of the trait
+						attr.params_style.get_or_insert_with(|| options.params_style.clone());
+						if let Some(prefix) = options.namespace_prefix() {
+							attr.name = format!("{}{}", prefix, attr.name);
+							for alias in &mut attr.aliases {
+								*alias = format!("{}{}", prefix, alias);
+							}
+						}
+						Some(Ok(RpcMethod::new(attr, method.clone())))
+					}
 					Ok(None) => None, // non rpc annotated trait method
 					Err(err) => Some(Err(syn::Error::new_spanned(method, err))),
 				}
@@ -174,6 +187,7 @@ fn generate_server_module(
 	method_registrations: &[MethodRegistration],
 	item_trait: &syn::ItemTrait,
 	methods: &[RpcMethod],
+	options: &DeriveOptions,
 ) -> Result<TokenStream> {
 	let has_pubsub_methods = methods.iter().any(RpcMethod::is_pubsub);
 
@@ -184,14 +198,21 @@ fn generate_server_module(
 	};
 	let mut rpc_server_trait = fold::fold_item_trait(&mut rpc_trait, item_trait.clone());
 
-	let to_delegate_method = generate_trait_item_method(
-		&method_registrations,
+	let to_delegate_methods = generate_trait_item_methods(
+		method_registrations,
 		&rpc_server_trait,
 		rpc_trait.has_metadata,
 		has_pubsub_methods,
+		options,
 	)?;
 
-	rpc_server_trait.items.push(syn::TraitItem::Method(to_delegate_method));
+	rpc_server_trait
+		.items
+		.extend(to_delegate_methods.into_iter().map(syn::TraitItem::Method));
+	if options.open_rpc {
+		let open_rpc_method = generate_open_rpc_spec_method(method_registrations, &rpc_server_trait)?;
+		rpc_server_trait.items.push(syn::TraitItem::Method(open_rpc_method));
+	}
 
 	let trait_bounds: Punctuated<syn::TypeParamBound, Token![+]> = parse_quote!(Sized + Send + Sync + 'static);
 	rpc_server_trait.supertraits.extend(trait_bounds);
@@ -222,12 +243,6 @@ fn rpc_wrapper_mod_name(rpc_trait: &syn::ItemTrait) -> syn::Ident {
 	syn::Ident::new(&mod_name, proc_macro2::Span::call_site())
 }
 
-fn has_named_params(methods: &[RpcMethod]) -> bool {
-	methods
-		.iter()
-		.any(|method| method.attr.params_style == Some(ParamStyle::Named))
-}
-
 pub fn crate_name(name: &str) -> Result<Ident> {
 	proc_macro_crate::crate_name(name)
 		.map(|name| Ident::new(&name, Span::call_site()))
@@ -245,7 +260,7 @@ pub fn rpc_impl(input: syn::Item, options: &DeriveOptions) -> Result<proc_macro2
 		}
 	};
 
-	let (method_registrations, methods) = compute_method_registrations(&rpc_trait)?;
+	let (method_registrations, methods) = compute_method_registrations(&rpc_trait, options)?;
 
 	let name = rpc_trait.ident.clone();
 	let mod_name_ident = rpc_wrapper_mod_name(&rpc_trait);
@@ -264,10 +279,7 @@ pub fn rpc_impl(input: syn::Item, options: &DeriveOptions) -> Result<proc_macro2
 		});
 	}
 	if options.enable_server {
-		if has_named_params(&methods) {
-			return Err(syn::Error::new_spanned(rpc_trait, USING_NAMED_PARAMS_WITH_SERVER_ERR));
-		}
-		let rpc_server_module = generate_server_module(&method_registrations, &rpc_trait, &methods)?;
+		let rpc_server_module = generate_server_module(&method_registrations, &rpc_trait, &methods, options)?;
 		submodules.push(rpc_server_module);
 		exports.push(quote! {
 			pub use self::#mod_name_ident::gen_server::#name;
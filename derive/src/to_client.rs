@@ -1,8 +1,8 @@
 use crate::options::DeriveOptions;
 use crate::params_style::ParamStyle;
-use crate::rpc_attr::AttributeKind;
+use crate::rpc_attr::{is_rpc_attr, AttributeKind};
 use crate::rpc_trait::crate_name;
-use crate::to_delegate::{generate_where_clause_serialization_predicates, MethodRegistration};
+use crate::to_delegate::{generate_where_clause_serialization_predicates, MethodRegistration, RpcMethod};
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use syn::punctuated::Punctuated;
@@ -97,7 +97,7 @@ fn generate_client_methods(methods: &[MethodRegistration], options: &DeriveOptio
 	for method in methods {
 		match method {
 			MethodRegistration::Standard { method, .. } => {
-				let attrs = get_doc_comments(&method.trait_item.attrs);
+				let attrs = get_client_attrs(method);
 				let rpc_name = method.name();
 				let name = &method.trait_item.sig.ident;
 				let args = compute_args(&method.trait_item);
@@ -121,7 +121,9 @@ fn generate_client_methods(methods: &[MethodRegistration], options: &DeriveOptio
 							})
 						}
 					}
-					ParamStyle::Positional => quote! {  // use tuple style serialization
+					// servers accepting both forms are sent positional params, understood by any server,
+					// there's no way to send named params with the generated client (documented on `#[rpc]`)
+					ParamStyle::Positional | ParamStyle::Both => quote! {  // use tuple style serialization
 						(#(#arg_names,)*)
 					},
 					ParamStyle::Raw => match arg_names.first() {
@@ -145,7 +147,7 @@ fn generate_client_methods(methods: &[MethodRegistration], options: &DeriveOptio
 				unsubscribe,
 			} => {
 				for subscribe in subscribes {
-					let attrs = get_doc_comments(&subscribe.trait_item.attrs);
+					let attrs = get_client_attrs(subscribe);
 					let name = &subscribe.trait_item.sig.ident;
 					let mut args = compute_args(&subscribe.trait_item).into_iter();
 					let returns = compute_subscription_type(&args.next().unwrap());
@@ -165,7 +167,7 @@ fn generate_client_methods(methods: &[MethodRegistration], options: &DeriveOptio
 				}
 			}
 			MethodRegistration::Notification { method, .. } => {
-				let attrs = get_doc_comments(&method.trait_item.attrs);
+				let attrs = get_client_attrs(method);
 				let rpc_name = method.name();
 				let name = &method.trait_item.sig.ident;
 				let args = compute_args(&method.trait_item);
@@ -184,6 +186,15 @@ fn generate_client_methods(methods: &[MethodRegistration], options: &DeriveOptio
 	Ok(client_methods)
 }
 
+/// Doc comments of the trait method, and `#[deprecated]` if the method is deprecated.
+fn get_client_attrs(method: &RpcMethod) -> Vec<syn::Attribute> {
+	let mut attrs = get_doc_comments(&method.trait_item.attrs);
+	if let Some(ref note) = method.attr.deprecated {
+		attrs.push(syn::parse_quote!(#[deprecated(note = #note)]));
+	}
+	attrs
+}
+
 fn get_doc_comments(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
 	let mut doc_comments = vec![];
 	for attr in attrs {
@@ -206,8 +217,8 @@ fn get_doc_comments(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
 fn compute_args(method: &syn::TraitItemMethod) -> Punctuated<syn::FnArg, syn::token::Comma> {
 	let mut args = Punctuated::new();
 	for arg in &method.sig.inputs {
-		let ty = match arg {
-			syn::FnArg::Typed(syn::PatType { ty, .. }) => ty,
+		let (ty, attrs) = match arg {
+			syn::FnArg::Typed(syn::PatType { ty, attrs, .. }) => (ty, attrs),
 			_ => continue,
 		};
 		let segments = match &**ty {
@@ -222,7 +233,11 @@ fn compute_args(method: &syn::TraitItemMethod) -> Punctuated<syn::FnArg, syn::to
 		if *ident == "Self" {
 			continue;
 		}
-		args.push(arg.to_owned());
+		let mut arg = arg.to_owned();
+		if let syn::FnArg::Typed(ref mut arg) = arg {
+			arg.attrs = attrs.iter().filter(|attr| !is_rpc_attr(attr)).cloned().collect();
+		}
+		args.push(arg);
 	}
 	args
 }
@@ -268,7 +283,7 @@ fn compute_returns(method: &syn::TraitItemMethod, returns: &Option<String>) -> R
 	Ok(returns)
 }
 
-fn try_infer_returns(output: &syn::ReturnType) -> Option<syn::Type> {
+pub fn try_infer_returns(output: &syn::ReturnType) -> Option<syn::Type> {
 	let extract_path_segments = |ty: &syn::Type| match ty {
 		syn::Type::Path(syn::TypePath {
 			path: syn::Path { segments, .. },
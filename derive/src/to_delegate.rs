@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
+use crate::options::DeriveOptions;
 use crate::params_style::ParamStyle;
-use crate::rpc_attr::RpcMethodAttribute;
+use crate::rpc_attr::{AttributeKind, PubSubMethodKind, RpcMethodAttribute};
+use crate::to_client::try_infer_returns;
 use quote::quote;
 use syn::{
 	parse_quote,
@@ -27,20 +29,35 @@ pub enum MethodRegistration {
 }
 
 impl MethodRegistration {
-	fn generate(&self) -> Result<proc_macro2::TokenStream> {
+	/// Generates registration of the methods in `del`.
+	///
+	/// With a namespace `prefix`, method names are resolved at runtime by a `namespaced` closure.
+	/// With `schemas`, methods are described with schemas generated by `schemars`.
+	fn generate(&self, prefix: Option<&str>, schemas: bool) -> Result<proc_macro2::TokenStream> {
 		match self {
 			MethodRegistration::Standard { method, has_metadata } => {
-				let rpc_name = &method.name();
+				let rpc_name = method_name(method.name(), prefix);
 				let add_method = if *has_metadata {
-					quote!(add_method_with_meta)
+					quote!(add_any_params_method_with_meta)
 				} else {
-					quote!(add_method)
+					quote!(add_any_params_method)
 				};
 				let closure = method.generate_delegate_closure(false)?;
-				let add_aliases = method.generate_add_aliases();
+				let add_aliases = method.generate_add_aliases(prefix);
+				let description = method.generate_description(quote!(), schemas)?;
+				let deprecate = method.generate_deprecate(prefix);
+
+				let set_timeout = method.attr.timeout_ms.map(|timeout_ms| {
+					quote! {
+						del.set_method_timeout(#rpc_name, ::std::time::Duration::from_millis(#timeout_ms));
+					}
+				});
 
 				Ok(quote! {
 					del.#add_method(#rpc_name, #closure);
+					del.describe_method(#rpc_name, #description);
+					#deprecate
+					#set_timeout
 					#add_aliases
 				})
 			}
@@ -49,7 +66,7 @@ impl MethodRegistration {
 				subscribes,
 				unsubscribe,
 			} => {
-				let unsub_name = unsubscribe.name();
+				let unsub_name = method_name(unsubscribe.name(), prefix);
 				let unsub_method_ident = unsubscribe.ident();
 				let unsub_closure = quote! {
 					move |base, id, meta| {
@@ -66,9 +83,16 @@ impl MethodRegistration {
 				let mut add_subscriptions = proc_macro2::TokenStream::new();
 
 				for subscribe in subscribes.iter() {
-					let sub_name = subscribe.name();
+					let sub_name = method_name(subscribe.name(), prefix);
 					let sub_closure = subscribe.generate_delegate_closure(true)?;
-					let sub_aliases = subscribe.generate_add_aliases();
+					let sub_aliases = subscribe.generate_add_aliases(prefix);
+					let sub_deprecate = subscribe.generate_deprecate(prefix);
+					let sub_description = subscribe.generate_description(
+						quote! {
+							subscribe: Some(#name.into()),
+						},
+						schemas,
+					)?;
 
 					add_subscriptions = quote! {
 						#add_subscriptions
@@ -77,29 +101,44 @@ impl MethodRegistration {
 							(#sub_name, #sub_closure),
 							(#unsub_name, #unsub_closure),
 						);
+						del.describe_method(#sub_name, #sub_description);
+						#sub_deprecate
 						#sub_aliases
 					};
 				}
 
-				let unsub_aliases = unsubscribe.generate_add_aliases();
+				let unsub_aliases = unsubscribe.generate_add_aliases(prefix);
+				let unsub_deprecate = unsubscribe.generate_deprecate(prefix);
+				let unsub_description = unsubscribe.generate_description(
+					quote! {
+						unsubscribe: Some(#name.into()),
+					},
+					schemas,
+				)?;
 
 				Ok(quote! {
 					#add_subscriptions
+					del.describe_method(#unsub_name, #unsub_description);
+					#unsub_deprecate
 					#unsub_aliases
 				})
 			}
 			MethodRegistration::Notification { method, has_metadata } => {
-				let name = &method.name();
+				let name = method_name(method.name(), prefix);
 				let add_notification = if *has_metadata {
-					quote!(add_notification_with_meta)
+					quote!(add_any_params_notification_with_meta)
 				} else {
-					quote!(add_notification)
+					quote!(add_any_params_notification)
 				};
 				let closure = method.generate_delegate_closure(false)?;
-				let add_aliases = method.generate_add_aliases();
+				let add_aliases = method.generate_add_aliases(prefix);
+				let description = method.generate_description(quote!(), schemas)?;
+				let deprecate = method.generate_deprecate(prefix);
 
 				Ok(quote! {
 					del.#add_notification(#name, #closure);
+					del.describe_method(#name, #description);
+					#deprecate
 					#add_aliases
 				})
 			}
@@ -107,6 +146,28 @@ impl MethodRegistration {
 	}
 }
 
+impl MethodRegistration {
+	fn generate_open_rpc(&self) -> Result<Vec<proc_macro2::TokenStream>> {
+		match self {
+			MethodRegistration::Standard { method, .. } | MethodRegistration::Notification { method, .. } => {
+				Ok(vec![method.generate_open_rpc_method(quote!())?])
+			}
+			MethodRegistration::PubSub {
+				name,
+				subscribes,
+				unsubscribe,
+			} => {
+				let mut methods = subscribes
+					.iter()
+					.map(|subscribe| subscribe.generate_open_rpc_method(quote! { subscribe: Some(#name.into()), }))
+					.collect::<Result<Vec<_>>>()?;
+				methods.push(unsubscribe.generate_open_rpc_method(quote! { unsubscribe: Some(#name.into()), })?);
+				Ok(methods)
+			}
+		}
+	}
+}
+
 const SUBSCRIBER_TYPE_IDENT: &str = "Subscriber";
 const METADATA_CLOSURE_ARG: &str = "meta";
 const SUBSCRIBER_CLOSURE_ARG: &str = "subscriber";
@@ -116,20 +177,22 @@ const TUPLE_FIELD_NAMES: [&str; 16] = [
 	"a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p",
 ];
 
-pub fn generate_trait_item_method(
+pub fn generate_trait_item_methods(
 	methods: &[MethodRegistration],
 	trait_item: &syn::ItemTrait,
 	has_metadata: bool,
 	has_pubsub_methods: bool,
-) -> Result<syn::TraitItemMethod> {
+	options: &DeriveOptions,
+) -> Result<Vec<syn::TraitItemMethod>> {
 	let io_delegate_type = if has_pubsub_methods {
 		quote!(_jsonrpc_pubsub::IoDelegate)
 	} else {
 		quote!(_jsonrpc_core::IoDelegate)
 	};
+	let prefix = options.namespace_prefix();
 	let add_methods = methods
 		.iter()
-		.map(MethodRegistration::generate)
+		.map(|method| method.generate(prefix.as_deref(), options.open_rpc))
 		.collect::<Result<Vec<_>>>()?;
 	let to_delegate_body = quote! {
 		let mut del = #io_delegate_type::new(self.into());
@@ -137,28 +200,124 @@ pub fn generate_trait_item_method(
 		del
 	};
 
-	let method: syn::TraitItemMethod = if has_metadata {
-		parse_quote! {
+	let methods: Vec<syn::TraitItemMethod> = match (&options.namespace, has_metadata) {
+		(None, true) => vec![parse_quote! {
 			/// Create an `IoDelegate`, wiring rpc calls to the trait methods.
 			fn to_delegate(self) -> #io_delegate_type<Self, Self::Metadata> {
 				#to_delegate_body
 			}
-		}
-	} else {
-		parse_quote! {
+		}],
+		(None, false) => vec![parse_quote! {
 			/// Create an `IoDelegate`, wiring rpc calls to the trait methods.
 			fn to_delegate<M: _jsonrpc_core::Metadata>(self) -> #io_delegate_type<Self, M> {
 				#to_delegate_body
 			}
+		}],
+		(Some(namespace), true) => {
+			let separator = &options.separator;
+			vec![
+				parse_quote! {
+					/// Create an `IoDelegate`, wiring rpc calls to the trait methods.
+					fn to_delegate(self) -> #io_delegate_type<Self, Self::Metadata> {
+						self.to_delegate_with_namespace(#namespace)
+					}
+				},
+				parse_quote! {
+					/// Create an `IoDelegate`, wiring rpc calls to the trait methods, prefixed with given namespace.
+					fn to_delegate_with_namespace(self, namespace: &str) -> #io_delegate_type<Self, Self::Metadata> {
+						let namespaced = |name: &str| format!("{}{}{}", namespace, #separator, name);
+						#to_delegate_body
+					}
+				},
+			]
+		}
+		(Some(namespace), false) => {
+			let separator = &options.separator;
+			vec![
+				parse_quote! {
+					/// Create an `IoDelegate`, wiring rpc calls to the trait methods.
+					fn to_delegate<M: _jsonrpc_core::Metadata>(self) -> #io_delegate_type<Self, M> {
+						self.to_delegate_with_namespace(#namespace)
+					}
+				},
+				parse_quote! {
+					/// Create an `IoDelegate`, wiring rpc calls to the trait methods, prefixed with given namespace.
+					fn to_delegate_with_namespace<M: _jsonrpc_core::Metadata>(
+						self,
+						namespace: &str,
+					) -> #io_delegate_type<Self, M> {
+						let namespaced = |name: &str| format!("{}{}{}", namespace, #separator, name);
+						#to_delegate_body
+					}
+				},
+			]
 		}
 	};
 
-	let predicates = generate_where_clause_serialization_predicates(&trait_item, false);
-	let mut method = method;
-	method.sig.generics.make_where_clause().predicates.extend(predicates);
+	let mut predicates = generate_where_clause_serialization_predicates(&trait_item, false);
+	if options.open_rpc {
+		predicates.extend(json_schema_predicates(trait_item));
+	}
+	Ok(methods
+		.into_iter()
+		.map(|mut method| {
+			method
+				.sig
+				.generics
+				.make_where_clause()
+				.predicates
+				.extend(predicates.clone());
+			method
+		})
+		.collect())
+}
+
+/// Generates `open_rpc_spec` method of the server trait, describing all its methods.
+pub fn generate_open_rpc_spec_method(
+	methods: &[MethodRegistration],
+	trait_item: &syn::ItemTrait,
+) -> Result<syn::TraitItemMethod> {
+	let methods = methods
+		.iter()
+		.map(MethodRegistration::generate_open_rpc)
+		.collect::<Result<Vec<_>>>()?
+		.into_iter()
+		.flatten();
+	let title = trait_item.ident.to_string();
+
+	let mut method: syn::TraitItemMethod = parse_quote! {
+		/// Returns OpenRPC document describing methods of this trait.
+		///
+		/// Schemas of params and results are generated with `schemars`, the document is titled
+		/// after the trait and versioned after the crate defining it.
+		fn open_rpc_spec() -> _jsonrpc_core::discover::OpenRpc {
+			#[allow(unused_mut)]
+			let mut generator = _jsonrpc_core::discover::schema_generator();
+			let methods = vec![#(#methods),*];
+			_jsonrpc_core::discover::OpenRpc::with_schemas(
+				_jsonrpc_core::discover::Info::new(#title, env!("CARGO_PKG_VERSION")),
+				methods,
+				generator,
+			)
+		}
+	};
+	method
+		.sig
+		.generics
+		.make_where_clause()
+		.predicates
+		.extend(json_schema_predicates(trait_item));
 	Ok(method)
 }
 
+/// Requires type params of the trait to implement `JsonSchema`.
+fn json_schema_predicates(trait_item: &syn::ItemTrait) -> impl Iterator<Item = syn::WherePredicate> + '_ {
+	trait_item.generics.type_params().map(|ty| -> syn::WherePredicate {
+		let ty = &ty.ident;
+		parse_quote!(#ty: _jsonrpc_core::schemars::JsonSchema)
+	})
+}
+
 #[derive(Clone)]
 pub struct RpcMethod {
 	pub attr: RpcMethodAttribute,
@@ -206,7 +365,7 @@ impl RpcMethod {
 	}
 
 	fn generate_delegate_closure(&self, is_subscribe: bool) -> Result<proc_macro2::TokenStream> {
-		let mut param_types: Vec<_> = self
+		let param_types: Vec<_> = self
 			.trait_item
 			.sig
 			.inputs
@@ -220,7 +379,11 @@ impl RpcMethod {
 
 		// special args are those which are not passed directly via rpc params: metadata, subscriber
 		let special_args = Self::special_args(&param_types);
-		param_types.retain(|ty| !special_args.iter().any(|(_, sty)| sty == ty));
+		let (param_types, defaults): (Vec<_>, Vec<_>) = self
+			.rpc_args()
+			.into_iter()
+			.map(|(_, ty, default)| (ty, default))
+			.unzip();
 		if param_types.len() > TUPLE_FIELD_NAMES.len() {
 			return Err(syn::Error::new_spanned(
 				&self.trait_item,
@@ -234,22 +397,63 @@ impl RpcMethod {
 			.collect());
 		let param_types = &param_types;
 		let parse_params = {
-			// last arguments that are `Option`-s are optional 'trailing' arguments
-			let trailing_args_num = param_types.iter().rev().take_while(|t| is_option_type(t)).count();
+			// last arguments that are `Option`-s or have default values are optional 'trailing' arguments
+			let trailing_args_num = param_types
+				.iter()
+				.zip(&defaults)
+				.rev()
+				.take_while(|(ty, default)| is_option_type(ty) || default.is_some())
+				.count();
+			if defaults[..param_types.len() - trailing_args_num]
+				.iter()
+				.any(Option::is_some)
+			{
+				return Err(syn::Error::new_spanned(
+					&self.trait_item,
+					"Arguments with default values can only be followed by other optional arguments",
+				));
+			}
 
-			if trailing_args_num != 0 {
-				self.params_with_trailing(trailing_args_num, param_types, tuple_fields)
+			let parse_positional = if trailing_args_num != 0 {
+				self.params_with_trailing(trailing_args_num, param_types, &defaults, tuple_fields)
 			} else if param_types.is_empty() {
 				quote! { let params = params.expect_no_params(); }
 			} else if self.attr.params_style == Some(ParamStyle::Raw) {
-				quote! { let params: _jsonrpc_core::Result<_> = Ok((params,)); }
-			} else if self.attr.params_style == Some(ParamStyle::Positional) {
-				quote! { let params = params.parse::<(#(#param_types, )*)>(); }
+				quote! { let params = params.into_params().map(|params| (params,)); }
 			} else {
-				unimplemented!("Server side named parameters are not implemented");
+				quote! { let params = params.parse::<(#(#param_types, )*)>(); }
+			};
+
+			match self.attr.params_style {
+				Some(ParamStyle::Named) | Some(ParamStyle::Both) if !param_types.is_empty() => {
+					let parse_named = self.params_by_name(param_types, &defaults);
+					let parse_other = if self.attr.params_style == Some(ParamStyle::Both) {
+						quote! {
+							#parse_positional
+							params
+						}
+					} else {
+						quote! { Err(_jsonrpc_core::Error::invalid_params("`params` should be an object")) }
+					};
+					quote! {
+						let params = if params.is_map() {
+							#parse_named
+						} else {
+							#parse_other
+						};
+					}
+				}
+				_ => parse_positional,
 			}
 		};
 
+		// subscriptions are registered with parsed params only
+		let convert_params = if is_subscribe {
+			quote! { let params = _jsonrpc_core::types::raw::AnyParams::from(params); }
+		} else {
+			quote!()
+		};
+
 		let method_ident = self.ident();
 		let result = &self.trait_item.sig.output;
 		let extra_closure_args: &Vec<_> = &special_args.iter().cloned().map(|arg| arg.0).collect();
@@ -293,6 +497,7 @@ impl RpcMethod {
 		Ok(quote! {
 			move |#closure_args| {
 				let method = &(Self::#method_ident as #method_sig);
+				#convert_params
 				#parse_params
 				match params {
 					#match_params
@@ -335,6 +540,7 @@ impl RpcMethod {
 		&self,
 		trailing_args_num: usize,
 		param_types: &[syn::Type],
+		defaults: &[Option<syn::Expr>],
 		tuple_fields: &[syn::Ident],
 	) -> proc_macro2::TokenStream {
 		let total_args_num = param_types.len();
@@ -345,9 +551,12 @@ impl RpcMethod {
 				let passed_args_num = required_args_num + passed_trailing_args_num;
 				let passed_param_types = &param_types[..passed_args_num];
 				let passed_tuple_fields = &tuple_fields[..passed_args_num];
-				let missed_args_num = total_args_num - passed_args_num;
-				let missed_params_values = ::std::iter::repeat(quote! { None })
-					.take(missed_args_num)
+				let missed_params_values = defaults[passed_args_num..]
+					.iter()
+					.map(|default| match default {
+						Some(default) => quote! { #default },
+						None => quote! { None },
+					})
 					.collect::<Vec<_>>();
 
 				if passed_args_num == 0 {
@@ -368,11 +577,7 @@ impl RpcMethod {
 			.collect::<Vec<_>>();
 
 		quote! {
-			let passed_args_num = match params {
-				_jsonrpc_core::Params::Array(ref v) => Ok(v.len()),
-				_jsonrpc_core::Params::None => Ok(0),
-				_ => Err(_jsonrpc_core::Error::invalid_params("`params` should be an array"))
-			};
+			let passed_args_num = params.array_len();
 
 			let params = passed_args_num.and_then(|passed_args_num| {
 				match passed_args_num {
@@ -387,22 +592,268 @@ impl RpcMethod {
 		}
 	}
 
-	fn generate_add_aliases(&self) -> proc_macro2::TokenStream {
+	/// Parses params given as an object, with keys named after the method arguments.
+	///
+	/// Missing `Option` arguments are `None`, missing arguments with default values (or `null`)
+	/// get the default value, unknown keys are rejected.
+	fn params_by_name(&self, param_types: &[syn::Type], defaults: &[Option<syn::Expr>]) -> proc_macro2::TokenStream {
+		let names: Vec<_> = self
+			.rpc_params()
+			.into_iter()
+			.enumerate()
+			.map(|(idx, (name, _))| match syn::parse_str::<syn::Ident>(&name) {
+				Ok(name) => name,
+				Err(_) => ident(&format!("param{}", idx)),
+			})
+			.collect();
+		let type_params: Vec<_> = (0..names.len()).map(|idx| ident(&format!("P{}", idx))).collect();
+		let (fields, values): (Vec<_>, Vec<_>) = names
+			.iter()
+			.zip(&type_params)
+			.zip(defaults)
+			.map(|((name, ty), default)| match default {
+				Some(default) => (
+					quote! {
+						#[serde(default)]
+						#name: Option<#ty>
+					},
+					quote! { #name.unwrap_or_else(|| #default) },
+				),
+				None => (quote! { #name: #ty }, quote! { #name }),
+			})
+			.unzip();
+
+		quote! {
+			#[derive(_jsonrpc_core::serde::Deserialize)]
+			#[serde(crate = "_jsonrpc_core::serde", deny_unknown_fields)]
+			struct NamedParams<#(#type_params),*> {
+				#(#fields),*
+			}
+
+			params
+				.parse::<NamedParams<#(#param_types),*>>()
+				.map(|NamedParams { #(#names),* }| (#(#values, )*))
+		}
+	}
+
+	fn docs(&self) -> String {
+		self.trait_item
+			.attrs
+			.iter()
+			.filter_map(|attr| match attr.parse_meta() {
+				Ok(syn::Meta::NameValue(ref nv)) if nv.path.is_ident("doc") => match nv.lit {
+					syn::Lit::Str(ref doc) => Some(doc.value()),
+					_ => None,
+				},
+				_ => None,
+			})
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	/// Generates description of the method, registered with `describe_method`.
+	///
+	/// With `schemas`, params and results are described with `schemars` schemas, inlined
+	/// as the description is served without components.
+	fn generate_description(
+		&self,
+		extra_fields: proc_macro2::TokenStream,
+		schemas: bool,
+	) -> Result<proc_macro2::TokenStream> {
+		let docs = self.docs();
+		let (params, result) = self.generate_content_descriptors(schemas)?;
+		let generator = if schemas {
+			Some(quote! {
+				#[allow(unused_mut)]
+				let mut generator = _jsonrpc_core::discover::inline_schema_generator();
+			})
+		} else {
+			None
+		};
+
+		Ok(quote! {
+			{
+				#generator
+				_jsonrpc_core::discover::MethodDescription {
+					params: vec![#(#params),*],
+					result: #result,
+					#extra_fields
+					.._jsonrpc_core::discover::MethodDescription::from_docs(#docs)
+				}
+			}
+		})
+	}
+
+	/// Generates OpenRPC method object, with schemas of params and result generated by `schemars`.
+	fn generate_open_rpc_method(&self, extra_fields: proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream> {
 		let name = self.name();
+		let docs = self.docs();
+		let aliases = &self.attr.aliases;
+		let is_notification = self.attr.is_notification();
+		let deprecation = self.attr.deprecated.as_ref().map(|note| {
+			quote! {
+				deprecated: true,
+				deprecation: Some(#note.into()),
+			}
+		});
+		let (params, result) = self.generate_content_descriptors(true)?;
+
+		Ok(quote! {
+			_jsonrpc_core::discover::Method {
+				name: #name.into(),
+				description: _jsonrpc_core::discover::MethodDescription {
+					params: vec![#(#params),*],
+					result: #result,
+					#deprecation
+					#extra_fields
+					.._jsonrpc_core::discover::MethodDescription::from_docs(#docs)
+				},
+				aliases: vec![#(#aliases.into()),*],
+				notification: #is_notification,
+			}
+		})
+	}
+
+	/// Generates content descriptors of params and result.
+	///
+	/// With `schemas`, schemas are generated by `schemars` using a `generator` in scope,
+	/// otherwise they are guessed from type names.
+	fn generate_content_descriptors(
+		&self,
+		schemas: bool,
+	) -> Result<(Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream)> {
+		let rpc_params = self.rpc_params().into_iter().zip(self.rpc_args());
+		let params: Vec<_> = match self.attr.kind {
+			// unsubscribe methods take the subscription id, which has no schema of its own
+			AttributeKind::PubSub {
+				kind: PubSubMethodKind::Unsubscribe,
+				..
+			} => rpc_params
+				.last()
+				.map(|((name, _), _)| {
+					quote! { _jsonrpc_core::discover::ContentDescriptor::new(#name, "SubscriptionId") }
+				})
+				.into_iter()
+				.collect(),
+			_ => rpc_params
+				.map(|((name, ty), (_, _, default))| {
+					let required = default.is_none() && !is_option_type(&ty);
+					content_descriptor(&name, &ty, required, schemas)
+				})
+				.collect(),
+		};
+
+		let result = match self.attr.kind {
+			AttributeKind::PubSub { .. } if self.subscriber_arg().is_some() => Some(Err("SubscriptionId")),
+			AttributeKind::Rpc {
+				is_notification: true, ..
+			} => None,
+			AttributeKind::Rpc {
+				returns: Some(ref returns),
+				..
+			} => Some(Ok(syn::parse_str::<syn::Type>(returns)?)),
+			_ => try_infer_returns(&self.trait_item.sig.output).map(Ok),
+		};
+		let result = match result {
+			Some(Ok(ty)) => {
+				let descriptor = content_descriptor("result", &ty, !is_option_type(&ty), schemas);
+				quote! { Some(#descriptor) }
+			}
+			Some(Err(ty)) => quote! { Some(_jsonrpc_core::discover::ContentDescriptor::new("result", #ty)) },
+			None => quote! { None },
+		};
+
+		Ok((params, result))
+	}
+
+	/// Arguments passed via rpc params, with their patterns and default values.
+	fn rpc_args(&self) -> Vec<(syn::Pat, syn::Type, Option<syn::Expr>)> {
+		let args: Vec<_> = self
+			.trait_item
+			.sig
+			.inputs
+			.iter()
+			.filter_map(|arg| match arg {
+				syn::FnArg::Typed(ty) => Some((*ty.pat.clone(), *ty.ty.clone())),
+				_ => None,
+			})
+			.zip(self.attr.arg_defaults.iter().cloned().chain(std::iter::repeat(None)))
+			.map(|((pat, ty), default)| (pat, ty, default))
+			.collect();
+		let types: Vec<_> = args.iter().map(|(_, ty, _)| ty.clone()).collect();
+		let special_args = Self::special_args(&types);
+
+		args.into_iter()
+			.filter(|(_, ty, _)| !special_args.iter().any(|(_, sty)| sty == ty))
+			.collect()
+	}
+
+	/// Arguments passed via rpc params, with their names.
+	fn rpc_params(&self) -> Vec<(String, syn::Type)> {
+		self.rpc_args()
+			.into_iter()
+			.enumerate()
+			.map(|(idx, (pat, ty, _))| match pat {
+				syn::Pat::Ident(ref pat) => (pat.ident.to_string(), ty),
+				_ => (format!("param{}", idx), ty),
+			})
+			.collect()
+	}
+
+	fn generate_add_aliases(&self, prefix: Option<&str>) -> proc_macro2::TokenStream {
+		let name = method_name(self.name(), prefix);
 		let add_aliases: Vec<_> = self
 			.attr
 			.aliases
 			.iter()
-			.map(|alias| quote! { del.add_alias(#alias, #name); })
+			.map(|alias| {
+				let alias = method_name(alias, prefix);
+				quote! { del.add_alias(#alias, #name); }
+			})
 			.collect();
 		quote! { #(#add_aliases)* }
 	}
+
+	fn generate_deprecate(&self, prefix: Option<&str>) -> Option<proc_macro2::TokenStream> {
+		let name = method_name(self.name(), prefix);
+		self.attr
+			.deprecated
+			.as_ref()
+			.map(|note| quote! { del.deprecate_method(#name, #note); })
+	}
+}
+
+/// Returns the method name literal, or a call to `namespaced` with the name stripped of its namespace `prefix`.
+fn method_name(name: &str, prefix: Option<&str>) -> proc_macro2::TokenStream {
+	match prefix.and_then(|prefix| name.strip_prefix(prefix)) {
+		Some(name) => quote! { &namespaced(#name) },
+		None => quote! { #name },
+	}
 }
 
 fn ident(s: &str) -> syn::Ident {
 	syn::Ident::new(s, proc_macro2::Span::call_site())
 }
 
+fn type_name(ty: &syn::Type) -> String {
+	quote!(#ty).to_string().replace(' ', "")
+}
+
+/// Generates a content descriptor of given type, with its schema generated by `schemars` if `schemas` are enabled.
+fn content_descriptor(name: &str, ty: &syn::Type, required: bool, schemas: bool) -> proc_macro2::TokenStream {
+	if schemas {
+		quote! { _jsonrpc_core::discover::ContentDescriptor::for_type::<#ty>(#name, #required, &mut generator) }
+	} else {
+		let ty = type_name(ty);
+		quote! {
+			_jsonrpc_core::discover::ContentDescriptor {
+				required: #required,
+				.._jsonrpc_core::discover::ContentDescriptor::new(#name, #ty)
+			}
+		}
+	}
+}
+
 fn is_option_type(ty: &syn::Type) -> bool {
 	if let syn::Type::Path(path) = ty {
 		path.path.segments.first().map_or(false, |t| t.ident == "Option")
@@ -0,0 +1,108 @@
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_derive::rpc;
+
+const DEFAULT_EXP: u32 = 2;
+
+#[rpc]
+pub trait Rpc {
+	/// Raises a number to given power, squares it by default.
+	#[rpc(name = "pow")]
+	fn pow(&self, base: u64, #[rpc(default = "DEFAULT_EXP")] exp: u32) -> Result<u64>;
+
+	/// Greets with optional greeting and punctuation.
+	#[rpc(name = "greet", params = "both")]
+	fn greet(
+		&self,
+		name: String,
+		#[rpc(default = "\"hello\".into()")] greeting: String,
+		#[rpc(default)] suffix: String,
+		times: Option<usize>,
+	) -> Result<String>;
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn pow(&self, base: u64, exp: u32) -> Result<u64> {
+		Ok(base.pow(exp))
+	}
+
+	fn greet(&self, name: String, greeting: String, suffix: String, times: Option<usize>) -> Result<String> {
+		Ok(format!("{} {}{}", greeting, name, suffix.repeat(times.unwrap_or(1))))
+	}
+}
+
+fn io() -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	io
+}
+
+#[test]
+fn should_use_default_values_of_missing_params() {
+	let io = io();
+
+	let cases = [
+		(r#"{"jsonrpc":"2.0","id":1,"method":"pow","params":[3]}"#, "9"),
+		(r#"{"jsonrpc":"2.0","id":1,"method":"pow","params":[3,3]}"#, "27"),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":["alice"]}"#,
+			r#""hello alice""#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":["alice","hi"]}"#,
+			r#""hi alice""#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":["alice","hi","!",3]}"#,
+			r#""hi alice!!!""#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":{"name":"alice","suffix":"?"}}"#,
+			r#""hello alice?""#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":{"name":"alice","greeting":null}}"#,
+			r#""hello alice""#,
+		),
+	];
+
+	for (request, result) in &cases {
+		let response = format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#, result);
+		assert_eq!(io.handle_request_sync(request), Some(response.clone()), "{}", request);
+		assert_eq!(io.handle_raw_request_sync(request), Some(response), "{}", request);
+	}
+}
+
+#[test]
+fn should_require_params_without_default_values() {
+	let io = io();
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"pow","params":[]}"#;
+	let response =
+		r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"`params` should have at least 1 argument(s)"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":{"greeting":"hi"}}"#;
+	let response =
+		r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params: missing field `name`."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+}
+
+#[test]
+fn should_describe_params_with_default_values_as_optional() {
+	let mut io = io();
+	io.enable_discovery(jsonrpc_core::discover::Info::new("test", "1.0"));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"rpc.discover"}"#;
+	let result: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let methods = result["result"]["methods"].as_array().unwrap();
+	let pow = methods.iter().find(|m| m["name"] == "pow").unwrap();
+	let required: Vec<_> = pow["params"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|param| param["required"].clone())
+		.collect();
+	assert_eq!(required, vec![true, false]);
+}
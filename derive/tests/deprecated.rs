@@ -0,0 +1,83 @@
+#![allow(deprecated)]
+
+use jsonrpc_core::futures::{executor, future};
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_core_client::transports::local;
+use jsonrpc_derive::rpc;
+
+#[rpc]
+pub trait Rpc {
+	/// Adds two numbers.
+	#[rpc(name = "add", alias("sum"), deprecated = "Use `add_all` instead")]
+	fn add(&self, a: u64, b: u64) -> Result<u64>;
+
+	/// Adds all the numbers.
+	#[rpc(name = "add_all")]
+	fn add_all(&self, numbers: Vec<u64>) -> Result<u64>;
+
+	/// Does nothing.
+	#[rpc(name = "noop", deprecated = "Not needed anymore")]
+	fn noop(&self);
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn add(&self, a: u64, b: u64) -> Result<u64> {
+		Ok(a + b)
+	}
+
+	fn add_all(&self, numbers: Vec<u64>) -> Result<u64> {
+		Ok(numbers.into_iter().sum())
+	}
+
+	fn noop(&self) {}
+}
+
+fn io() -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	io
+}
+
+#[test]
+fn should_still_call_deprecated_methods() {
+	let io = io();
+
+	for method in &["add", "sum"] {
+		let request = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{}","params":[1,2]}}"#, method);
+		let response = r#"{"jsonrpc":"2.0","result":3,"id":1}"#;
+		assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+	}
+}
+
+#[test]
+fn should_describe_deprecated_methods() {
+	let mut io = io();
+	io.enable_discovery(jsonrpc_core::discover::Info::new("test", "1.0"));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"rpc.discover"}"#;
+	let result: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let methods = result["result"]["methods"].as_array().unwrap();
+	let method = |name: &str| methods.iter().find(|m| m["name"] == name).unwrap().clone();
+
+	assert_eq!(method("add")["summary"], "Adds two numbers.");
+	assert_eq!(method("add")["deprecated"], true);
+	assert_eq!(method("add")["x-deprecation"], "Use `add_all` instead");
+	assert_eq!(method("noop")["deprecated"], true);
+	assert!(method("add_all").get("deprecated").is_none());
+	assert!(method("add_all").get("x-deprecation").is_none());
+}
+
+#[test]
+fn should_call_deprecated_methods_from_client() {
+	let (client, rpc_client) = local::connect::<gen_client::Client, _, _>(io());
+	let call = async move {
+		assert_eq!(client.add(1, 2).await.unwrap(), 3);
+	};
+
+	match executor::block_on(future::select(Box::pin(call), rpc_client)) {
+		future::Either::Left(_) => {}
+		future::Either::Right((result, _)) => panic!("Client finished before the call: {:?}", result),
+	}
+}
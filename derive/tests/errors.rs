@@ -0,0 +1,129 @@
+use jsonrpc_core::futures::{executor, future};
+use jsonrpc_core::{Error, ErrorCode, IoHandler, RpcErrorCode};
+use jsonrpc_core_client::transports::local;
+use jsonrpc_derive::{rpc, RpcError};
+use serde_json::json;
+
+const NOT_FOUND: i64 = -32010;
+
+#[derive(Debug, PartialEq, RpcError)]
+pub enum AccountError {
+	#[rpc_error(code = NOT_FOUND, message = "Account not found")]
+	NotFound,
+	#[rpc_error(code = -32011, message = "Insufficient balance")]
+	InsufficientBalance { required: u64, available: u64 },
+	#[rpc_error(code = -32012)]
+	Locked(String),
+	#[rpc_error(code = -32013)]
+	Limits(u64, u64),
+}
+
+#[rpc]
+pub trait Rpc {
+	#[rpc(name = "withdraw")]
+	fn withdraw(&self, account: String, amount: u64) -> Result<u64, AccountError>;
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn withdraw(&self, account: String, amount: u64) -> Result<u64, AccountError> {
+		match account.as_str() {
+			"alice" if amount <= 10 => Ok(10 - amount),
+			"alice" => Err(AccountError::InsufficientBalance {
+				required: amount,
+				available: 10,
+			}),
+			"bob" => Err(AccountError::Locked("frozen by admin".into())),
+			_ => Err(AccountError::NotFound),
+		}
+	}
+}
+
+#[test]
+fn should_convert_variants_into_errors() {
+	let error: Error = AccountError::NotFound.into();
+	assert_eq!(
+		error,
+		Error {
+			code: ErrorCode::ServerError(-32010),
+			message: "Account not found".into(),
+			data: None,
+		}
+	);
+
+	let error: Error = AccountError::InsufficientBalance {
+		required: 5,
+		available: 3,
+	}
+	.into();
+	assert_eq!(error.message, "Insufficient balance");
+	assert_eq!(error.data, Some(json!({"required": 5, "available": 3})));
+
+	let error: Error = AccountError::Locked("frozen".into()).into();
+	assert_eq!(error.message, "Locked");
+	assert_eq!(error.data, Some(json!("frozen")));
+
+	let error: Error = AccountError::Limits(1, 2).into();
+	assert_eq!(error.data, Some(json!([1, 2])));
+}
+
+#[test]
+fn should_recover_variants_from_errors() {
+	for variant in [
+		AccountError::NotFound,
+		AccountError::InsufficientBalance {
+			required: 5,
+			available: 3,
+		},
+		AccountError::Locked("frozen".into()),
+		AccountError::Limits(1, 2),
+	] {
+		let error = RpcErrorCode::into_error(variant);
+		let recovered = AccountError::from_error(&error).expect("Variant should be recovered");
+		assert_eq!(RpcErrorCode::into_error(recovered), error);
+	}
+
+	// unknown code
+	assert_eq!(AccountError::from_error(&Error::internal_error()), None);
+	// known code with mismatching data
+	let mut error = RpcErrorCode::into_error(AccountError::Limits(1, 2));
+	error.data = Some(json!({"min": 1}));
+	assert_eq!(AccountError::from_error(&error), None);
+}
+
+#[test]
+fn should_respond_with_typed_errors() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"withdraw","params":["alice",15]}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32011,"message":"Insufficient balance","data":{"available":10,"required":15}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.into()));
+}
+
+#[test]
+fn client_should_map_errors_back_into_typed_errors() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	let (client, rpc_client) = local::connect::<gen_client::Client, _, _>(io);
+
+	let calls = async move {
+		assert_eq!(client.withdraw("alice".into(), 3).await.unwrap(), 7);
+
+		let error = client.withdraw("bob".into(), 3).await.unwrap_err();
+		assert_eq!(
+			error.into_typed().ok(),
+			Some(AccountError::Locked("frozen by admin".into()))
+		);
+
+		let error = client.withdraw("carol".into(), 3).await.unwrap_err();
+		assert_eq!(error.into_typed().ok(), Some(AccountError::NotFound));
+	};
+
+	match executor::block_on(future::select(Box::pin(calls), rpc_client)) {
+		future::Either::Left(_) => {}
+		future::Either::Right((result, _)) => panic!("Client finished before the calls: {:?}", result),
+	}
+}
@@ -254,3 +254,37 @@ fn should_accept_only_notifications() {
 		.unwrap()
 	);
 }
+
+#[test]
+fn should_describe_methods_for_discovery() {
+	let mut io = IoHandler::new();
+	let rpc = RpcImpl;
+	io.extend_with(rpc.to_delegate());
+	io.enable_discovery(jsonrpc_core::discover::Info::new("test", "1.0"));
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"rpc.discover"}"#;
+	let res = io.handle_request_sync(req);
+
+	// then
+	let result: serde_json::Value = serde_json::from_str(&res.unwrap()).unwrap();
+	let methods = result["result"]["methods"].as_array().unwrap();
+	let add = methods.iter().find(|m| m["name"] == "add").unwrap();
+	assert_eq!(
+		add,
+		&serde_json::json!({
+			"name": "add",
+			"summary": "Adds two numbers and returns a result.",
+			"description": "Adds two numbers and returns a result.",
+			"params": [
+				{ "name": "a", "required": true, "schema": { "type": "integer", "minimum": 0 } },
+				{ "name": "b", "required": true, "schema": { "type": "integer", "minimum": 0 } }
+			],
+			"result": { "name": "result", "required": true, "schema": { "type": "integer", "minimum": 0 } },
+			"x-aliases": ["add_alias1", "add_alias2"]
+		})
+	);
+	let notify = methods.iter().find(|m| m["name"] == "notify").unwrap();
+	assert_eq!(notify["x-notification"], true);
+	assert!(notify.get("result").is_none());
+}
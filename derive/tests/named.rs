@@ -0,0 +1,116 @@
+use jsonrpc_core::futures::{executor, future};
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_core_client::transports::local;
+use jsonrpc_derive::rpc;
+
+#[rpc]
+pub trait Rpc {
+	/// Transfers given amount, all of it if not specified.
+	#[rpc(name = "transfer", params = "named")]
+	fn transfer(&self, from: String, to: String, amount: Option<u64>) -> Result<String>;
+
+	/// Adds two numbers.
+	#[rpc(name = "add", params = "both")]
+	fn add(&self, a: u64, b: u64) -> Result<u64>;
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn transfer(&self, from: String, to: String, amount: Option<u64>) -> Result<String> {
+		match amount {
+			Some(amount) => Ok(format!("{} from {} to {}", amount, from, to)),
+			None => Ok(format!("all from {} to {}", from, to)),
+		}
+	}
+
+	fn add(&self, a: u64, b: u64) -> Result<u64> {
+		Ok(a + b)
+	}
+}
+
+fn io() -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+	io
+}
+
+#[test]
+fn should_accept_params_by_name() {
+	let io = io();
+
+	let cases = [
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"transfer","params":{"to":"bob","from":"alice","amount":5}}"#,
+			r#"{"jsonrpc":"2.0","result":"5 from alice to bob","id":1}"#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"transfer","params":{"from":"alice","to":"bob"}}"#,
+			r#"{"jsonrpc":"2.0","result":"all from alice to bob","id":1}"#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"transfer","params":["alice","bob",5]}"#,
+			r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"`params` should be an object"},"id":1}"#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"add","params":{"a":1,"b":2}}"#,
+			r#"{"jsonrpc":"2.0","result":3,"id":1}"#,
+		),
+		(
+			r#"{"jsonrpc":"2.0","id":1,"method":"add","params":[1,2]}"#,
+			r#"{"jsonrpc":"2.0","result":3,"id":1}"#,
+		),
+	];
+
+	for (request, response) in &cases {
+		assert_eq!(
+			io.handle_request_sync(request),
+			Some(response.to_string()),
+			"{}",
+			request
+		);
+		assert_eq!(
+			io.handle_raw_request_sync(request),
+			Some(response.to_string()),
+			"{}",
+			request
+		);
+	}
+}
+
+#[test]
+fn should_reject_unknown_and_missing_names() {
+	let io = io();
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"add","params":{"a":1,"c":2}}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params: unknown field `c`, expected `a` or `b`."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"add","params":{"a":1}}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params: missing field `b`."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+}
+
+mod client {
+	use super::*;
+
+	#[rpc(client, params = "named")]
+	pub trait Rpc {
+		#[rpc(name = "transfer")]
+		fn transfer(&self, from: String, to: String, amount: Option<u64>) -> Result<String>;
+	}
+
+	#[test]
+	fn should_talk_to_server_by_name() {
+		let (client, rpc_client) = local::connect::<gen_client::Client, _, _>(io());
+		let call = async move {
+			let result = client.transfer("alice".into(), "bob".into(), Some(5)).await;
+			assert_eq!(result.unwrap(), "5 from alice to bob");
+		};
+
+		match executor::block_on(future::select(Box::pin(call), rpc_client)) {
+			future::Either::Left(_) => {}
+			future::Either::Right((result, _)) => panic!("Client finished before the call: {:?}", result),
+		}
+	}
+}
@@ -0,0 +1,170 @@
+use jsonrpc_core::futures::{executor, future};
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_core_client::transports::local;
+use jsonrpc_derive::rpc;
+
+#[rpc(namespace = "eth")]
+pub trait Rpc {
+	/// Returns balance of given account.
+	#[rpc(name = "getBalance", alias("balance"))]
+	fn get_balance(&self, account: String) -> Result<u64>;
+
+	/// Returns the chain id.
+	#[rpc(name = "chainId")]
+	fn chain_id(&self) -> Result<u64>;
+}
+
+struct RpcImpl(u64);
+
+impl Rpc for RpcImpl {
+	fn get_balance(&self, _account: String) -> Result<u64> {
+		Ok(5)
+	}
+
+	fn chain_id(&self) -> Result<u64> {
+		Ok(self.0)
+	}
+}
+
+#[test]
+fn should_prefix_method_names_with_namespace() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl(1).to_delegate());
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_getBalance","params":["alice"]}"#;
+	let response = r#"{"jsonrpc":"2.0","result":5,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_balance","params":["alice"]}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"getBalance","params":["alice"]}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+}
+
+#[test]
+fn should_mount_trait_under_different_namespaces() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl(1).to_delegate());
+	io.extend_with(RpcImpl(5).to_delegate_with_namespace("testnet"));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId","params":[]}"#;
+	let response = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"testnet_chainId","params":[]}"#;
+	let response = r#"{"jsonrpc":"2.0","result":5,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"testnet_balance","params":["alice"]}"#;
+	let response = r#"{"jsonrpc":"2.0","result":5,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+}
+
+mod separator {
+	use super::*;
+
+	#[rpc(namespace = "admin", separator = ".")]
+	pub trait Rpc {
+		/// Returns the node version.
+		#[rpc(name = "version")]
+		fn version(&self) -> Result<String>;
+	}
+
+	struct RpcImpl;
+
+	impl Rpc for RpcImpl {
+		fn version(&self) -> Result<String> {
+			Ok("1.0".into())
+		}
+	}
+
+	#[test]
+	fn should_join_namespace_with_separator() {
+		let mut io = IoHandler::new();
+		io.extend_with(RpcImpl.to_delegate());
+		io.extend_with(RpcImpl.to_delegate_with_namespace("node"));
+
+		for method in &["admin.version", "node.version"] {
+			let request = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{}","params":[]}}"#, method);
+			let response = r#"{"jsonrpc":"2.0","result":"1.0","id":1}"#;
+			assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+		}
+	}
+}
+
+#[test]
+fn should_call_prefixed_methods_from_client() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl(1).to_delegate());
+	let (client, rpc_client) = local::connect::<gen_client::Client, _, _>(io);
+	let call = async move {
+		assert_eq!(client.get_balance("alice".into()).await.unwrap(), 5);
+	};
+
+	match executor::block_on(future::select(Box::pin(call), rpc_client)) {
+		future::Either::Left(_) => {}
+		future::Either::Right((result, _)) => panic!("Client finished before the call: {:?}", result),
+	}
+}
+
+mod pubsub {
+	use jsonrpc_core::futures::channel::mpsc;
+	use jsonrpc_core::{MetaIoHandler, Result};
+	use jsonrpc_derive::rpc;
+	use jsonrpc_pubsub::typed::Subscriber;
+	use jsonrpc_pubsub::{PubSubMetadata, Session, SubscriptionId};
+	use std::sync::Arc;
+
+	#[derive(Clone, Default)]
+	struct Metadata;
+	impl jsonrpc_core::Metadata for Metadata {}
+	impl PubSubMetadata for Metadata {
+		fn session(&self) -> Option<Arc<Session>> {
+			let (tx, _rx) = mpsc::unbounded();
+			Some(Arc::new(Session::new(tx)))
+		}
+	}
+
+	#[rpc(server, namespace = "eth")]
+	pub trait Rpc {
+		type Metadata;
+
+		/// Subscribes to new heads.
+		#[pubsub(subscription = "eth_subscription", subscribe, name = "subscribe")]
+		fn subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<u64>);
+
+		/// Unsubscribes from new heads.
+		#[pubsub(subscription = "eth_subscription", unsubscribe, name = "unsubscribe")]
+		fn unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+	}
+
+	struct RpcImpl;
+
+	impl Rpc for RpcImpl {
+		type Metadata = Metadata;
+
+		fn subscribe(&self, _meta: Self::Metadata, subscriber: Subscriber<u64>) {
+			let _sink = subscriber.assign_id(SubscriptionId::Number(5));
+		}
+
+		fn unsubscribe(&self, _meta: Option<Self::Metadata>, _id: SubscriptionId) -> Result<bool> {
+			Ok(true)
+		}
+	}
+
+	#[test]
+	fn should_prefix_subscription_methods() {
+		let mut io = MetaIoHandler::default();
+		io.extend_with(RpcImpl.to_delegate_with_namespace("testnet"));
+
+		let request = r#"{"jsonrpc":"2.0","id":1,"method":"testnet_subscribe","params":[]}"#;
+		let response = r#"{"jsonrpc":"2.0","result":5,"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, Metadata), Some(response.to_string()));
+
+		let request = r#"{"jsonrpc":"2.0","id":1,"method":"eth_subscribe","params":[]}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, Metadata), Some(response.to_string()));
+	}
+}
@@ -0,0 +1,144 @@
+use jsonrpc_core::futures::channel::mpsc;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::typed::Subscriber;
+use jsonrpc_pubsub::{PubSubMetadata, Session, SubscriptionId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Transfer {
+	pub to: String,
+	pub amount: u64,
+	pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub enum Status {
+	Pending,
+	Done,
+}
+
+#[derive(Clone, Default)]
+struct Metadata;
+impl jsonrpc_core::Metadata for Metadata {}
+impl PubSubMetadata for Metadata {
+	fn session(&self) -> Option<Arc<Session>> {
+		let (tx, _rx) = mpsc::unbounded();
+		Some(Arc::new(Session::new(tx)))
+	}
+}
+
+#[rpc(server, open_rpc)]
+pub trait Rpc {
+	type Metadata;
+
+	/// Sends a transfer.
+	///
+	/// Fails if the balance is too low.
+	#[rpc(name = "send", alias("transfer"))]
+	fn send(&self, transfer: Transfer, #[rpc(default)] dry_run: bool) -> Result<Status>;
+
+	/// Logs a message.
+	#[rpc(name = "log", deprecated = "Use tracing instead")]
+	fn log(&self, message: String);
+
+	/// Subscribes to transfer statuses.
+	#[pubsub(subscription = "status", subscribe, name = "status_subscribe")]
+	fn subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<Status>, to: Option<String>);
+
+	/// Unsubscribes from transfer statuses.
+	#[pubsub(subscription = "status", unsubscribe, name = "status_unsubscribe")]
+	fn unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	type Metadata = Metadata;
+
+	fn send(&self, _transfer: Transfer, _dry_run: bool) -> Result<Status> {
+		Ok(Status::Pending)
+	}
+
+	fn log(&self, _message: String) {}
+
+	fn subscribe(&self, _meta: Self::Metadata, _subscriber: Subscriber<Status>, _to: Option<String>) {}
+
+	fn unsubscribe(&self, _meta: Option<Self::Metadata>, _id: SubscriptionId) -> Result<bool> {
+		Ok(true)
+	}
+}
+
+#[test]
+fn should_generate_open_rpc_spec() {
+	let spec = serde_json::to_value(RpcImpl::open_rpc_spec()).unwrap();
+
+	assert_eq!(
+		spec["info"],
+		json!({ "title": "Rpc", "version": env!("CARGO_PKG_VERSION") })
+	);
+	let names: Vec<_> = spec["methods"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|method| method["name"].as_str().unwrap())
+		.collect();
+	assert_eq!(names, vec!["log", "send", "status_subscribe", "status_unsubscribe"]);
+
+	assert_eq!(
+		spec["methods"][1],
+		json!({
+			"name": "send",
+			"summary": "Sends a transfer.",
+			"description": "Sends a transfer.\n\nFails if the balance is too low.",
+			"params": [
+				{ "name": "transfer", "required": true, "schema": { "$ref": "#/components/schemas/Transfer" } },
+				{ "name": "dry_run", "required": false, "schema": { "type": "boolean" } }
+			],
+			"result": { "name": "result", "required": true, "schema": { "$ref": "#/components/schemas/Status" } },
+			"x-aliases": ["transfer"]
+		})
+	);
+	assert_eq!(spec["methods"][0]["x-notification"], true);
+	assert_eq!(spec["methods"][0]["deprecated"], true);
+	assert_eq!(spec["methods"][0]["x-deprecation"], "Use tracing instead");
+	assert_eq!(spec["methods"][2]["x-subscribe"], "status");
+	assert_eq!(spec["methods"][2]["params"][0]["required"], false);
+	assert_eq!(
+		spec["methods"][2]["result"]["schema"],
+		json!({ "title": "SubscriptionId" })
+	);
+	assert_eq!(spec["methods"][3]["x-unsubscribe"], "status");
+	assert_eq!(spec["methods"][3]["params"][0]["name"], "id");
+
+	let schemas = &spec["components"]["schemas"];
+	assert_eq!(schemas["Transfer"]["required"], json!(["amount", "to"]));
+	assert_eq!(schemas["Status"]["enum"], json!(["Pending", "Done"]));
+}
+
+#[test]
+fn should_describe_methods_with_inlined_schemas() {
+	let mut io = jsonrpc_pubsub::PubSubHandler::<Metadata>::default();
+	io.extend_with(RpcImpl.to_delegate());
+	io.enable_discovery(jsonrpc_core::discover::Info::new("test", "1.0"));
+
+	let request = r#"{"jsonrpc":"2.0","id":1,"method":"rpc.discover"}"#;
+	let response = io.handle_request_sync(request, Metadata).unwrap();
+
+	let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+	let methods = response["result"]["methods"].as_array().unwrap();
+	let send = methods.iter().find(|method| method["name"] == "send").unwrap();
+	assert_eq!(send["params"][0]["schema"]["type"], "object");
+	assert_eq!(send["params"][0]["schema"]["required"], json!(["amount", "to"]));
+	assert_eq!(send["params"][1]["required"], false);
+	assert_eq!(send["result"]["schema"]["enum"], json!(["Pending", "Done"]));
+	let subscribe = methods
+		.iter()
+		.find(|method| method["name"] == "status_subscribe")
+		.unwrap();
+	assert_eq!(subscribe["params"][0]["required"], false);
+	assert_eq!(subscribe["params"][0]["schema"]["type"], json!(["string", "null"]));
+}
@@ -0,0 +1,62 @@
+use jsonrpc_core::{IoHandler, Result};
+use jsonrpc_derive::rpc;
+
+#[rpc]
+pub trait Rpc {
+	/// Adds two numbers.
+	#[rpc(name = "add")]
+	fn add(&self, a: u64, b: u64) -> Result<u64>;
+
+	/// Concatenates optional suffix.
+	#[rpc(name = "greet")]
+	fn greet(&self, name: String, suffix: Option<String>) -> Result<String>;
+
+	/// Returns the params untouched.
+	#[rpc(name = "echo", params = "raw")]
+	fn echo(&self, params: jsonrpc_core::Params) -> Result<jsonrpc_core::Params>;
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn add(&self, a: u64, b: u64) -> Result<u64> {
+		Ok(a + b)
+	}
+
+	fn greet(&self, name: String, suffix: Option<String>) -> Result<String> {
+		Ok(format!("hello {}{}", name, suffix.unwrap_or_default()))
+	}
+
+	fn echo(&self, params: jsonrpc_core::Params) -> Result<jsonrpc_core::Params> {
+		Ok(params)
+	}
+}
+
+#[test]
+fn should_handle_raw_requests() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+
+	let requests = [
+		r#"{"jsonrpc":"2.0","id":1,"method":"add","params":[1,2]}"#,
+		r#"{"jsonrpc":"2.0","id":1,"method":"add","params":[1]}"#,
+		r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":["world"]}"#,
+		r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":["world","!"]}"#,
+		r#"{"jsonrpc":"2.0","id":1,"method":"greet","params":{"name":"world"}}"#,
+		r#"{"jsonrpc":"2.0","id":1,"method":"echo","params":{"a":[1]}}"#,
+	];
+
+	for request in &requests {
+		let raw = io.handle_raw_request_sync(request);
+		assert_eq!(raw, io.handle_request_sync(request), "{}", request);
+	}
+
+	assert_eq!(
+		io.handle_raw_request_sync(requests[0]),
+		Some(r#"{"jsonrpc":"2.0","result":3,"id":1}"#.into())
+	);
+	assert_eq!(
+		io.handle_raw_request_sync(requests[3]),
+		Some(r#"{"jsonrpc":"2.0","result":"hello world!","id":1}"#.into())
+	);
+}
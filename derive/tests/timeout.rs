@@ -0,0 +1,69 @@
+use jsonrpc_core::futures::future;
+use jsonrpc_core::{BoxFuture, IoHandler, Response, Result};
+use jsonrpc_derive::rpc;
+
+#[rpc]
+pub trait Rpc {
+	/// Never completes, should time out.
+	#[rpc(name = "hang", timeout_ms = 10)]
+	fn hang(&self) -> BoxFuture<Result<u64>>;
+
+	/// Completes immediately.
+	#[rpc(name = "ready", timeout_ms = 1000)]
+	fn ready(&self) -> BoxFuture<Result<u64>>;
+}
+
+struct RpcImpl;
+
+impl Rpc for RpcImpl {
+	fn hang(&self) -> BoxFuture<Result<u64>> {
+		Box::pin(future::pending())
+	}
+
+	fn ready(&self) -> BoxFuture<Result<u64>> {
+		Box::pin(future::ready(Ok(5)))
+	}
+}
+
+#[test]
+fn should_time_out_method_calls() {
+	let mut io = IoHandler::new();
+	io.extend_with(RpcImpl.to_delegate());
+
+	// when
+	let req1 = r#"{"jsonrpc":"2.0","id":1,"method":"hang","params":[]}"#;
+	let req2 = r#"{"jsonrpc":"2.0","id":1,"method":"ready","params":[]}"#;
+
+	let res1 = io.handle_request_sync(req1);
+	let res2 = io.handle_request_sync(req2);
+
+	// then
+	let result1: Response = serde_json::from_str(&res1.unwrap()).unwrap();
+	assert_eq!(
+		result1,
+		serde_json::from_str(
+			r#"{
+		"jsonrpc": "2.0",
+		"error": {
+			"code": -32001,
+			"message": "Request timed out"
+		},
+		"id": 1
+	}"#
+		)
+		.unwrap()
+	);
+
+	let result2: Response = serde_json::from_str(&res2.unwrap()).unwrap();
+	assert_eq!(
+		result2,
+		serde_json::from_str(
+			r#"{
+		"jsonrpc": "2.0",
+		"result": 5,
+		"id": 1
+	}"#
+		)
+		.unwrap()
+	);
+}
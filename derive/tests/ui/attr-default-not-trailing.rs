@@ -3,8 +3,8 @@ use jsonrpc_derive::rpc;
 #[rpc]
 pub trait Rpc {
 	/// Returns a protocol version
-	#[rpc(name = "add", params = "named")]
-	fn add(&self, a: u32, b: u32) -> Result<String>;
+	#[rpc(name = "add")]
+	fn add(&self, #[rpc(default)] a: u32, b: u32) -> Result<String>;
 }
 
 fn main() {}
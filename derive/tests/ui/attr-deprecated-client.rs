@@ -0,0 +1,16 @@
+#![deny(deprecated)]
+
+use jsonrpc_derive::rpc;
+
+#[rpc(client)]
+pub trait Rpc {
+	/// Returns a protocol version
+	#[rpc(name = "protocolVersion", deprecated = "Use `version` instead")]
+	fn protocol_version(&self) -> Result<String>;
+}
+
+fn call(client: gen_client::Client) {
+	let _ = client.protocol_version();
+}
+
+fn main() {}
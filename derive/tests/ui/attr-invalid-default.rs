@@ -0,0 +1,10 @@
+use jsonrpc_derive::rpc;
+
+#[rpc]
+pub trait Rpc {
+	/// Returns a protocol version
+	#[rpc(name = "add")]
+	fn add(&self, a: u32, #[rpc(default = 1)] b: u32) -> Result<String>;
+}
+
+fn main() {}
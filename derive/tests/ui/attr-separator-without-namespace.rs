@@ -0,0 +1,10 @@
+use jsonrpc_derive::rpc;
+
+#[rpc(separator = ".")]
+pub trait Rpc {
+	/// Returns a protocol version
+	#[rpc(name = "protocolVersion")]
+	fn protocol_version(&self) -> Result<String>;
+}
+
+fn main() {}
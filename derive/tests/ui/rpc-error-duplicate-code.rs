@@ -0,0 +1,11 @@
+use jsonrpc_derive::RpcError;
+
+#[derive(RpcError)]
+pub enum Error {
+	#[rpc_error(code = -32010)]
+	NotFound,
+	#[rpc_error(code = -32010)]
+	Locked,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use jsonrpc_derive::RpcError;
+
+#[derive(RpcError)]
+pub enum Error {
+	#[rpc_error(message = "Not found")]
+	NotFound,
+}
+
+fn main() {}
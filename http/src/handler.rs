@@ -9,7 +9,9 @@ use std::{fmt, mem, str};
 use hyper::header::{self, HeaderMap, HeaderValue};
 use hyper::{self, service::Service, Body, Method};
 
+use crate::jsonrpc::encoding::Encoding;
 use crate::jsonrpc::serde_json;
+use crate::jsonrpc::streaming::StreamingResponse;
 use crate::jsonrpc::{self as core, middleware, Metadata, Middleware};
 use crate::response::Response;
 use crate::server_utils::cors;
@@ -28,6 +30,7 @@ pub struct ServerHandler<M: Metadata = (), S: Middleware<M> = middleware::Noop>
 	health_api: Option<(String, String)>,
 	max_request_body_size: usize,
 	keep_alive: bool,
+	encodings: Vec<Arc<dyn Encoding>>,
 }
 
 impl<M: Metadata, S: Middleware<M>> ServerHandler<M, S> {
@@ -43,6 +46,7 @@ impl<M: Metadata, S: Middleware<M>> ServerHandler<M, S> {
 		health_api: Option<(String, String)>,
 		max_request_body_size: usize,
 		keep_alive: bool,
+		encodings: Vec<Arc<dyn Encoding>>,
 	) -> Self {
 		ServerHandler {
 			jsonrpc_handler,
@@ -55,6 +59,7 @@ impl<M: Metadata, S: Middleware<M>> ServerHandler<M, S> {
 			health_api,
 			max_request_body_size,
 			keep_alive,
+			encodings,
 		}
 	}
 }
@@ -115,6 +120,7 @@ where
 					max_request_body_size: self.max_request_body_size,
 					// initial value, overwritten when reading client headers
 					keep_alive: true,
+					encodings: self.encodings.clone(),
 				})
 			}
 		}
@@ -175,6 +181,7 @@ enum RpcHandlerState<M> {
 		uri: Option<hyper::Uri>,
 		request: Vec<u8>,
 		metadata: M,
+		encoding: Option<Arc<dyn Encoding>>,
 	},
 	ProcessRest {
 		uri: hyper::Uri,
@@ -186,7 +193,10 @@ enum RpcHandlerState<M> {
 	},
 	Writing(Response),
 	Waiting(Pin<Box<dyn Future<Output = Option<String>> + Send>>),
+	WaitingStreaming(Pin<Box<dyn Future<Output = Option<StreamingResponse>> + Send>>),
 	WaitingForResponse(Pin<Box<dyn Future<Output = Response> + Send>>),
+	WaitingEncoded(Pin<Box<dyn Future<Output = Option<Vec<u8>>> + Send>>, &'static str),
+	WritingRaw(hyper::Response<Body>),
 	Done,
 }
 
@@ -202,6 +212,9 @@ impl<M> fmt::Debug for RpcHandlerState<M> {
 			Writing(ref res) => write!(fmt, "Writing({:?})", res),
 			WaitingForResponse(_) => write!(fmt, "WaitingForResponse"),
 			Waiting(_) => write!(fmt, "Waiting"),
+			WaitingStreaming(_) => write!(fmt, "WaitingStreaming"),
+			WaitingEncoded(_, content_type) => write!(fmt, "WaitingEncoded({})", content_type),
+			WritingRaw(ref res) => write!(fmt, "WritingRaw({:?})", res),
 			Done => write!(fmt, "Done"),
 		}
 	}
@@ -218,6 +231,7 @@ pub struct RpcHandler<M: Metadata, S: Middleware<M>> {
 	health_api: Option<(String, String)>,
 	max_request_body_size: usize,
 	keep_alive: bool,
+	encodings: Vec<Arc<dyn Encoding>>,
 }
 
 impl<M: Metadata, S: Middleware<M>> Future for RpcHandler<M, S>
@@ -252,7 +266,8 @@ where
 				request,
 				metadata,
 				uri,
-			} => match this.process_body(body, request, uri, metadata, cx) {
+				encoding,
+			} => match this.process_body(body, request, uri, metadata, encoding, cx) {
 				Err(BodyError::Utf8(ref e)) => {
 					let mesg = format!("utf-8 encoding error at byte {} in request body", e.valid_up_to());
 					let resp = Response::bad_request(mesg);
@@ -284,26 +299,44 @@ where
 					Poll::Pending => RpcPollState::NotReady(RpcHandlerState::Waiting(waiting)),
 				}
 			}
+			RpcHandlerState::WaitingStreaming(mut waiting) => match Pin::new(&mut waiting).poll(cx) {
+				Poll::Ready(None) => RpcPollState::Ready(RpcHandlerState::Writing(Response::ok(String::new()))),
+				Poll::Ready(Some(StreamingResponse::Complete(result))) => {
+					RpcPollState::Ready(RpcHandlerState::Writing(Response::ok(format!("{}\n", result))))
+				}
+				Poll::Ready(Some(StreamingResponse::Chunked(chunks))) => {
+					use futures::{stream, StreamExt};
+
+					// Chunks are sent as they are produced, failures abort the response.
+					let body = chunks.chain(stream::once(async { Ok("\n".to_owned()) }));
+					let response = hyper::Response::builder()
+						.header(
+							header::CONTENT_TYPE,
+							HeaderValue::from_static("application/json; charset=utf-8"),
+						)
+						.body(Body::wrap_stream(body))
+						.expect("Content type is a valid header value; qed");
+					RpcPollState::Ready(RpcHandlerState::WritingRaw(response))
+				}
+				Poll::Pending => RpcPollState::NotReady(RpcHandlerState::WaitingStreaming(waiting)),
+			},
+			RpcHandlerState::WaitingEncoded(mut waiting, content_type) => match Pin::new(&mut waiting).poll(cx) {
+				Poll::Ready(response) => {
+					let response = hyper::Response::builder()
+						.header(header::CONTENT_TYPE, HeaderValue::from_static(content_type))
+						.body(response.unwrap_or_default().into())
+						.expect("Content type is a valid header value; qed");
+					RpcPollState::Ready(RpcHandlerState::WritingRaw(response))
+				}
+				Poll::Pending => RpcPollState::NotReady(RpcHandlerState::WaitingEncoded(waiting, content_type)),
+			},
 			state => RpcPollState::NotReady(state),
 		};
 
 		let (new_state, is_ready) = new_state.decompose();
 		match new_state {
-			RpcHandlerState::Writing(res) => {
-				let mut response: hyper::Response<Body> = res.into();
-				let cors_allow_origin = mem::replace(&mut this.cors_allow_origin, cors::AllowCors::Invalid);
-				let cors_allow_headers = mem::replace(&mut this.cors_allow_headers, cors::AllowCors::Invalid);
-
-				Self::set_response_headers(
-					response.headers_mut(),
-					this.is_options,
-					this.cors_max_age,
-					cors_allow_origin.into(),
-					cors_allow_headers.into(),
-					this.keep_alive,
-				);
-				Poll::Ready(Ok(response))
-			}
+			RpcHandlerState::Writing(res) => Poll::Ready(Ok(this.finish(res.into()))),
+			RpcHandlerState::WritingRaw(res) => Poll::Ready(Ok(this.finish(res))),
 			state => {
 				this.state = state;
 				if is_ready {
@@ -350,6 +383,7 @@ where
 			None => return RpcHandlerState::Writing(Response::closing()),
 		};
 		let metadata = handler.extractor.read_metadata(&request);
+		let encoding = self.find_encoding(request.headers().get("content-type"));
 
 		// Proceed
 		match *request.method() {
@@ -366,8 +400,17 @@ where
 					request: Default::default(),
 					uri,
 					body: request.into_body(),
+					encoding: None,
 				}
 			}
+			// Other encodings enabled for this server
+			Method::POST if encoding.is_some() => RpcHandlerState::ReadingBody {
+				metadata,
+				request: Default::default(),
+				uri: None,
+				body: request.into_body(),
+				encoding,
+			},
 			Method::POST if self.rest_api == RestApi::Unsecure && request.uri().path().split('/').count() > 2 => {
 				RpcHandlerState::ProcessRest {
 					metadata,
@@ -471,6 +514,7 @@ where
 		mut request: Vec<u8>,
 		uri: Option<hyper::Uri>,
 		metadata: M,
+		encoding: Option<Arc<dyn Encoding>>,
 		cx: &mut task::Context<'_>,
 	) -> Result<RpcPollState<M>, BodyError> {
 		use futures::Stream;
@@ -494,6 +538,19 @@ where
 						return Ok(RpcPollState::Ready(RpcHandlerState::ProcessRest { uri, metadata }));
 					}
 
+					if let Some(encoding) = encoding {
+						let content_type = encoding.content_type();
+						let response = match self.jsonrpc_handler.upgrade() {
+							Some(h) => h.handler.handle_encoded_request(&request, encoding, metadata),
+							None => return Ok(RpcPollState::Ready(RpcHandlerState::Writing(Response::closing()))),
+						};
+
+						return Ok(RpcPollState::Ready(RpcHandlerState::WaitingEncoded(
+							response,
+							content_type,
+						)));
+					}
+
 					let content = match str::from_utf8(&request) {
 						Ok(content) => content,
 						Err(err) => {
@@ -503,12 +560,12 @@ where
 					};
 
 					let response = match self.jsonrpc_handler.upgrade() {
-						Some(h) => h.handler.handle_request(content, metadata),
+						Some(h) => h.handler.handle_request_streaming(content, metadata),
 						None => return Ok(RpcPollState::Ready(RpcHandlerState::Writing(Response::closing()))),
 					};
 
 					// Content is ready
-					return Ok(RpcPollState::Ready(RpcHandlerState::Waiting(Box::pin(response))));
+					return Ok(RpcPollState::Ready(RpcHandlerState::WaitingStreaming(response)));
 				}
 				Poll::Pending => {
 					return Ok(RpcPollState::NotReady(RpcHandlerState::ReadingBody {
@@ -516,12 +573,29 @@ where
 						request,
 						metadata,
 						uri,
+						encoding,
 					}));
 				}
 			}
 		}
 	}
 
+	/// Sets CORS and connection headers of the final response.
+	fn finish(&mut self, mut response: hyper::Response<Body>) -> hyper::Response<Body> {
+		let cors_allow_origin = mem::replace(&mut self.cors_allow_origin, cors::AllowCors::Invalid);
+		let cors_allow_headers = mem::replace(&mut self.cors_allow_headers, cors::AllowCors::Invalid);
+
+		Self::set_response_headers(
+			response.headers_mut(),
+			self.is_options,
+			self.cors_max_age,
+			cors_allow_origin.into(),
+			cors_allow_headers.into(),
+			self.keep_alive,
+		);
+		response
+	}
+
 	fn set_response_headers(
 		headers: &mut HeaderMap,
 		is_options: bool,
@@ -574,6 +648,16 @@ where
 		}
 	}
 
+	/// Returns an encoding enabled for this server (other than JSON) matching the `content_type` header.
+	fn find_encoding(&self, content_type: Option<&header::HeaderValue>) -> Option<Arc<dyn Encoding>> {
+		let content_type = content_type.and_then(|val| val.to_str().ok())?;
+		let mime = content_type.split(';').next().unwrap_or_default().trim();
+		self.encodings
+			.iter()
+			.find(|encoding| encoding.content_type().eq_ignore_ascii_case(mime))
+			.cloned()
+	}
+
 	/// Returns true if the `content_type` header indicates a valid JSON
 	/// message.
 	fn is_json(content_type: Option<&header::HeaderValue>) -> bool {
@@ -44,6 +44,7 @@ use std::thread;
 
 use parking_lot::Mutex;
 
+use crate::jsonrpc::encoding::Encoding;
 use crate::jsonrpc::MetaIoHandler;
 use crate::server_utils::reactor::{Executor, UninitializedExecutor};
 use futures::{channel::oneshot, future};
@@ -247,6 +248,7 @@ pub struct ServerBuilder<M: jsonrpc::Metadata = (), S: jsonrpc::Middleware<M> =
 	keep_alive: bool,
 	threads: usize,
 	max_request_body_size: usize,
+	encodings: Vec<Arc<dyn Encoding>>,
 }
 
 impl<M: jsonrpc::Metadata + Default, S: jsonrpc::Middleware<M>> ServerBuilder<M, S>
@@ -298,6 +300,7 @@ where
 			keep_alive: true,
 			threads: 1,
 			max_request_body_size: 5 * 1024 * 1024,
+			encodings: Vec::new(),
 		}
 	}
 
@@ -419,6 +422,16 @@ where
 		self
 	}
 
+	/// Accepts requests of given encoding in addition to JSON.
+	///
+	/// The encoding of a request is selected by its `Content-Type` header
+	/// and the response is encoded the same way.
+	/// Binary encodings are available with `msgpack` and `cbor` features.
+	pub fn encoding<E: Encoding>(mut self, encoding: E) -> Self {
+		self.encodings.push(Arc::new(encoding));
+		self
+	}
+
 	/// Start this JSON-RPC HTTP server trying to bind to specified `SocketAddr`.
 	pub fn start_http(self, addr: &SocketAddr) -> io::Result<Server> {
 		let cors_domains = self.cors_domains;
@@ -440,6 +453,7 @@ where
 		let (done_tx, done_rx) = oneshot::channel();
 		let eloop = self.executor.init_with_name("http.worker0")?;
 		let req_max_size = self.max_request_body_size;
+		let encodings = self.encodings;
 		// The first threads `Executor` is initialised differently from the others
 		serve(
 			(shutdown_signal, local_addr_tx, done_tx),
@@ -456,6 +470,7 @@ where
 			keep_alive,
 			reuse_port,
 			req_max_size,
+			encodings.clone(),
 		);
 		let handles = (0..self.threads - 1)
 			.map(|i| {
@@ -478,6 +493,7 @@ where
 					keep_alive,
 					reuse_port,
 					req_max_size,
+					encodings.clone(),
 				);
 				Ok((eloop, close, local_addr_rx, done_rx))
 			})
@@ -536,6 +552,7 @@ fn serve<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>>(
 	keep_alive: bool,
 	reuse_port: bool,
 	max_request_body_size: usize,
+	encodings: Vec<Arc<dyn Encoding>>,
 ) where
 	S::Future: Unpin,
 	S::CallFuture: Unpin,
@@ -617,6 +634,7 @@ fn serve<M: jsonrpc::Metadata, S: jsonrpc::Middleware<M>>(
 				health_api.clone(),
 				max_request_body_size,
 				keep_alive,
+				encodings.clone(),
 			);
 			async { Ok::<_, Infallible>(service) }
 		});
@@ -71,6 +71,9 @@ fn io() -> IoHandler {
 		});
 		p.map_err(|_| Error::invalid_request())
 	});
+	io.add_stream_method("numbers", |_params: Params, _meta| {
+		futures::stream::iter((0..3).map(|i| Ok(Value::from(i))))
+	});
 
 	io
 }
@@ -205,6 +208,82 @@ fn should_return_unsupported_media_type_if_not_json() {
 	);
 }
 
+#[test]
+fn should_negotiate_encoding_by_content_type() {
+	use self::jsonrpc_core::encoding::{Encoding, MessagePack};
+
+	// given
+	let server = serve(|builder| builder.encoding(MessagePack));
+	let body = MessagePack
+		.encode_json(r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[]}"#)
+		.unwrap();
+
+	// when
+	let mut req = TcpStream::connect(server.address()).unwrap();
+	let head = format!(
+		"\
+		 POST / HTTP/1.1\r\n\
+		 Host: 127.0.0.1:8080\r\n\
+		 Connection: close\r\n\
+		 Content-Type: application/msgpack\r\n\
+		 Content-Length: {}\r\n\
+		 \r\n",
+		body.len()
+	);
+	req.write_all(head.as_bytes()).unwrap();
+	req.write_all(&body).unwrap();
+	let mut response = Vec::new();
+	req.read_to_end(&mut response).unwrap();
+
+	// then
+	let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+	let headers = String::from_utf8(response[..split].to_vec()).unwrap();
+	assert!(headers.starts_with("HTTP/1.1 200 OK"), "{}", headers);
+	assert!(headers.contains("content-type: application/msgpack"), "{}", headers);
+	assert_eq!(
+		MessagePack.decode_json(&response[split + 4..]).unwrap(),
+		r#"{"id":1,"jsonrpc":"2.0","result":"world"}"#
+	);
+}
+
+#[test]
+fn should_negotiate_cbor_encoding() {
+	use self::jsonrpc_core::encoding::{Cbor, Encoding};
+
+	// given
+	let server = serve(|builder| builder.encoding(Cbor));
+	let body = Cbor
+		.encode_json(r#"{"jsonrpc":"2.0","id":1,"method":"hello","params":[]}"#)
+		.unwrap();
+
+	// when
+	let mut req = TcpStream::connect(server.address()).unwrap();
+	let head = format!(
+		"\
+		 POST / HTTP/1.1\r\n\
+		 Host: 127.0.0.1:8080\r\n\
+		 Connection: close\r\n\
+		 Content-Type: application/cbor\r\n\
+		 Content-Length: {}\r\n\
+		 \r\n",
+		body.len()
+	);
+	req.write_all(head.as_bytes()).unwrap();
+	req.write_all(&body).unwrap();
+	let mut response = Vec::new();
+	req.read_to_end(&mut response).unwrap();
+
+	// then
+	let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+	let headers = String::from_utf8(response[..split].to_vec()).unwrap();
+	assert!(headers.starts_with("HTTP/1.1 200 OK"), "{}", headers);
+	assert!(headers.contains("content-type: application/cbor"), "{}", headers);
+	assert_eq!(
+		Cbor.decode_json(&response[split + 4..]).unwrap(),
+		r#"{"id":1,"jsonrpc":"2.0","result":"world"}"#
+	);
+}
+
 #[test]
 fn should_return_error_for_malformed_request() {
 	// given
@@ -1182,6 +1261,92 @@ fn should_handle_sync_requests_correctly() {
 	assert_eq!(response.body, world());
 }
 
+#[test]
+fn should_handle_methods_added_after_start() {
+	// given
+	let shared = jsonrpc_core::registry::SharedIoHandler::new(IoHandler::default());
+	let server = ServerBuilder::new(shared.handler())
+		.start_http(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let addr = *server.address();
+	shared.update(|io| io.add_sync_method("hello", |_: Params| Ok(Value::String("world".into()))));
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#;
+	let response = request(
+		server,
+		&format!(
+			"\
+			 POST / HTTP/1.1\r\n\
+			 Host: localhost:{}\r\n\
+			 Connection: close\r\n\
+			 Content-Type: application/json\r\n\
+			 Content-Length: {}\r\n\
+			 \r\n\
+			 {}\r\n\
+			 ",
+			addr.port(),
+			req.len(),
+			req
+		),
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert_eq!(response.body, world());
+}
+
+#[test]
+fn should_stream_results_of_streaming_methods_in_chunks() {
+	// given
+	let server = serve(id);
+	let addr = *server.address();
+
+	// when
+	let req = r#"{"jsonrpc":"2.0","id":1,"method":"numbers"}"#;
+	let mut stream = TcpStream::connect(addr).unwrap();
+	stream
+		.write_all(
+			format!(
+				"\
+				 POST / HTTP/1.1\r\n\
+				 Host: localhost:{}\r\n\
+				 Connection: close\r\n\
+				 Content-Type: application/json\r\n\
+				 Content-Length: {}\r\n\
+				 \r\n\
+				 {}\r\n\
+				 ",
+				addr.port(),
+				req.len(),
+				req
+			)
+			.as_bytes(),
+		)
+		.unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).unwrap();
+
+	// then
+	let (head, mut body) = response.split_at(response.find("\r\n\r\n").unwrap() + 4);
+	assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+	assert!(head.to_lowercase().contains("transfer-encoding: chunked\r\n"));
+	let mut chunks = Vec::new();
+	loop {
+		let (size, rest) = body.split_at(body.find("\r\n").unwrap());
+		let size = usize::from_str_radix(size, 16).unwrap();
+		if size == 0 {
+			break;
+		}
+		chunks.push(&rest[2..2 + size]);
+		body = &rest[2 + size + 2..];
+	}
+	assert_eq!(
+		chunks,
+		vec![r#"{"jsonrpc":"2.0","result":[0"#, ",1", ",2", r#"],"id":1}"#, "\n"]
+	);
+}
+
 #[test]
 fn should_handle_async_requests_with_immediate_response_correctly() {
 	// given
@@ -1,4 +1,6 @@
 //! Cross-platform JSON-RPC IPC transport.
+//!
+//! The `tower` feature enables `tower` integration of `jsonrpc-core` (see `jsonrpc_core::tower`).
 
 #![deny(missing_docs)]
 
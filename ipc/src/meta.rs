@@ -1,7 +1,7 @@
 use std::path::Path;
 
 use crate::jsonrpc::futures::channel::mpsc;
-use crate::jsonrpc::Metadata;
+use crate::jsonrpc::{CancellationToken, Metadata};
 use crate::server_utils::session;
 
 /// Request context
@@ -12,6 +12,24 @@ pub struct RequestContext<'a> {
 	pub endpoint_addr: &'a Path,
 	/// Direct pipe sender
 	pub sender: mpsc::UnboundedSender<String>,
+	cancellation: CancellationToken,
+}
+
+impl<'a> RequestContext<'a> {
+	/// Creates a context of a new connection.
+	pub fn new(session_id: session::SessionId, endpoint_addr: &'a Path, sender: mpsc::UnboundedSender<String>) -> Self {
+		RequestContext {
+			session_id,
+			endpoint_addr,
+			sender,
+			cancellation: CancellationToken::new(),
+		}
+	}
+
+	/// Token cancelled once the connection is closed.
+	pub fn cancellation(&self) -> &CancellationToken {
+		&self.cancellation
+	}
 }
 
 /// Metadata extractor (per session)
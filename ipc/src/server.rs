@@ -3,6 +3,7 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use crate::jsonrpc::encoding::{Encoding, Json};
 use crate::jsonrpc::futures::channel::mpsc;
 use crate::jsonrpc::{middleware, MetaIoHandler, Metadata, Middleware};
 use crate::meta::{MetaExtractor, NoopExtractor, RequestContext};
@@ -13,6 +14,7 @@ use parity_tokio_ipc::Endpoint;
 use parking_lot::Mutex;
 use tower_service::Service as _;
 
+use crate::server_utils::streaming::{self, MessageChunks};
 use crate::server_utils::{codecs, reactor, reactor::TaskExecutor, session, tokio_util};
 
 pub use parity_tokio_ipc::SecurityAttributes;
@@ -21,21 +23,26 @@ pub use parity_tokio_ipc::SecurityAttributes;
 pub struct Service<M: Metadata = (), S: Middleware<M> = middleware::Noop> {
 	handler: Arc<MetaIoHandler<M, S>>,
 	meta: M,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<M: Metadata, S: Middleware<M>> Service<M, S> {
-	/// Create new IPC server session with given handler and metadata.
-	pub fn new(handler: Arc<MetaIoHandler<M, S>>, meta: M) -> Self {
-		Service { handler, meta }
+	/// Create new IPC server session with given handler, metadata and encoding.
+	pub fn new(handler: Arc<MetaIoHandler<M, S>>, meta: M, encoding: Arc<dyn Encoding>) -> Self {
+		Service {
+			handler,
+			meta,
+			encoding,
+		}
 	}
 }
 
-impl<M: Metadata, S: Middleware<M>> tower_service::Service<String> for Service<M, S>
+impl<M: Metadata, S: Middleware<M>> tower_service::Service<Vec<u8>> for Service<M, S>
 where
 	S::Future: Unpin,
 	S::CallFuture: Unpin,
 {
-	type Response = Option<String>;
+	type Response = Option<MessageChunks>;
 	type Error = ();
 
 	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
@@ -44,10 +51,10 @@ where
 		Poll::Ready(Ok(()))
 	}
 
-	fn call(&mut self, req: String) -> Self::Future {
+	fn call(&mut self, req: Vec<u8>) -> Self::Future {
 		use futures::FutureExt;
-		trace!(target: "ipc", "Received request: {}", req);
-		Box::pin(self.handler.handle_request(&req, self.meta.clone()).map(Ok))
+		trace!(target: "ipc", "Received request: {}", String::from_utf8_lossy(&req));
+		Box::pin(streaming::handle_request(&self.handler, &req, self.encoding.clone(), self.meta.clone()).map(Ok))
 	}
 }
 
@@ -61,6 +68,7 @@ pub struct ServerBuilder<M: Metadata = (), S: Middleware<M> = middleware::Noop>
 	outgoing_separator: codecs::Separator,
 	security_attributes: SecurityAttributes,
 	client_buffer_size: usize,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<M: Metadata + Default, S: Middleware<M>> ServerBuilder<M, S>
@@ -97,6 +105,7 @@ where
 			outgoing_separator: codecs::Separator::default(),
 			security_attributes: SecurityAttributes::empty(),
 			client_buffer_size: 5,
+			encoding: Arc::new(Json),
 		}
 	}
 
@@ -140,6 +149,16 @@ where
 		self
 	}
 
+	/// Sets the encoding of requests and responses, JSON by default.
+	///
+	/// Messages of binary encodings are prefixed with their length (4-byte big-endian)
+	/// instead of being enveloped with separators.
+	/// Binary encodings are available with `msgpack` and `cbor` features.
+	pub fn encoding<E: Encoding>(mut self, encoding: E) -> Self {
+		self.encoding = Arc::new(encoding);
+		self
+	}
+
 	/// Creates a new server from the given endpoint.
 	pub fn start(self, path: &str) -> std::io::Result<Server> {
 		let executor = self.executor.initialize()?;
@@ -155,6 +174,7 @@ where
 		let (wait_signal, wait_receiver) = std::sync::mpsc::channel();
 		let security_attributes = self.security_attributes;
 		let client_buffer_size = self.client_buffer_size;
+		let encoding = self.encoding;
 
 		let fut = async move {
 			let mut endpoint = Endpoint::new(endpoint_addr);
@@ -191,29 +211,56 @@ where
 				}
 
 				let (sender, receiver) = mpsc::unbounded();
-				let meta = meta_extractor.extract(&RequestContext {
-					endpoint_addr: endpoint_addr.as_ref(),
-					session_id,
-					sender,
-				});
-				let mut service = Service::new(rpc_handler.clone(), meta);
-				let codec = codecs::StreamCodec::new(incoming_separator.clone(), outgoing_separator.clone());
+				let context = RequestContext::new(session_id, endpoint_addr.as_ref(), sender);
+				let cancellation = context.cancellation().clone();
+				let meta = meta_extractor.extract(&context);
+				let mut service = Service::new(rpc_handler.clone(), meta, encoding.clone());
+				let mut codec =
+					codecs::EncodedCodec::new(&*encoding, incoming_separator.clone(), outgoing_separator.clone());
+				// read for every connection, so that changes of a shared handler apply to new connections
+				if let Some(max_size) = rpc_handler.max_request_size() {
+					codec = codec.with_max_size(max_size);
+				}
 				let framed = tokio_util::codec::Decoder::framed(codec, io_stream);
 				let (writer, reader) = futures::StreamExt::split(framed);
 
-				let responses = reader
-					.map_ok(move |req| {
-						service
-							.call(req)
-							// Ignore service errors
-							.map(|x| Ok(x.ok().flatten()))
-					})
-					.try_buffer_unordered(client_buffer_size)
-					// Filter out previously ignored service errors as `None`s
-					.try_filter_map(futures::future::ok)
-					// we use `select_with_weak` here, instead of `select`, to close the stream
-					// as soon as the ipc pipe is closed
-					.select_with_weak(receiver.map(Ok));
+				// Abort calls still in progress as soon as the pipe is closed.
+				let cancel_on_close = cancellation.drop_guard();
+				let reader = reader.chain(
+					futures::stream::once(Box::pin(async move {
+						drop(cancel_on_close);
+						None::<std::io::Result<Vec<u8>>>
+					}))
+					.filter_map(futures::future::ready),
+				);
+
+				// Work around https://github.com/rust-lang/rust/issues/64552 by boxing the stream type
+				let responses: Pin<Box<dyn futures::Stream<Item = std::io::Result<codecs::Chunk>> + Send>> = Box::pin(
+					reader
+						.map_ok(move |req| {
+							service
+								.call(req)
+								// Ignore service errors
+								.map(|x| Ok(x.ok().flatten()))
+						})
+						.try_buffer_unordered(client_buffer_size)
+						// Filter out previously ignored service errors as `None`s
+						.try_filter_map(futures::future::ok)
+						// we use `select_with_weak` here, instead of `select`, to close the stream
+						// as soon as the ipc pipe is closed
+						.select_with_weak(receiver.filter_map({
+							// pushed messages are JSON text, re-encode them for the peer
+							let encoding = encoding.clone();
+							move |message| {
+								let message = encoding.encode_json(&message).map_err(|_| {
+									warn!(target: "ipc", "Dropping message which is not valid JSON: {}", message);
+								});
+								futures::future::ready(message.ok().map(|message| Ok(streaming::message(message))))
+							}
+						}))
+						// all chunks of a message are written before the next one is taken
+						.try_flatten(),
+				);
 
 				responses.forward(writer).then(move |_| {
 					trace!(target: "ipc", "Peer: service finished");
@@ -328,7 +375,7 @@ impl CloseHandle {
 mod tests {
 	use super::*;
 
-	use jsonrpc_core::Value;
+	use jsonrpc_core::{CancellationToken, Value};
 	use std::os::unix::net::UnixStream;
 	use std::thread;
 	use std::time::{self, Duration};
@@ -411,6 +458,89 @@ mod tests {
 		server.close();
 	}
 
+	#[test]
+	fn encoded_request() {
+		use futures::SinkExt;
+		use jsonrpc_core::encoding::MessagePack;
+
+		crate::logger::init_log();
+		let path = "/tmp/test-ipc-40010";
+		let server = server_builder()
+			.encoding(MessagePack)
+			.start(path)
+			.expect("Server must run with no issues");
+
+		let reply = async move {
+			let stream = tokio::net::UnixStream::connect(path).await?;
+			let codec = codecs::EncodedCodec::new(&MessagePack, codecs::Separator::Empty, Default::default());
+			let mut stream = tokio_util::codec::Decoder::framed(codec, stream);
+			let request = r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [42, 23], "id": 1}"#;
+			stream.send(MessagePack.encode_json(request).unwrap()).await?;
+			let (reply, _) = stream.into_future().await;
+
+			reply.expect("there should be one reply")
+		};
+		let rt = tokio::runtime::Runtime::new().unwrap();
+		let result = rt.block_on(reply).expect("wait for reply");
+
+		assert_eq!(
+			MessagePack.decode_json(&result).unwrap(),
+			r#"{"id":1,"jsonrpc":"2.0","result":"hello"}"#,
+		);
+		server.close();
+	}
+
+	#[test]
+	fn streamed_response() {
+		use futures::{future, stream, SinkExt};
+		use tokio::io::AsyncReadExt;
+
+		crate::logger::init_log();
+		let path = "/tmp/test-ipc-40020";
+		let (items_tx, items_rx) = mpsc::unbounded();
+		let items_rx = Mutex::new(Some(items_rx));
+		let mut io = MetaIoHandler::<()>::default();
+		io.add_stream_method("numbers", move |_params, _meta| {
+			let rest = items_rx.lock().take().expect("Called once; qed");
+			stream::once(future::ready(Ok(Value::from(1)))).chain(rest.map(Ok))
+		});
+		let server = ServerBuilder::new(io)
+			.start(path)
+			.expect("Server must run with no issues");
+
+		let reply = async move {
+			let mut stream = tokio::net::UnixStream::connect(path).await?;
+			let mut framed = tokio_util::codec::Decoder::framed(codecs::StreamCodec::stream_incoming(), &mut stream);
+			framed
+				.send(r#"{"jsonrpc": "2.0", "method": "numbers", "id": 1}"#.to_owned())
+				.await?;
+
+			// the first item is written before the rest is produced
+			let mut response = vec![];
+			while !response.ends_with(b"[1") {
+				let mut read_buf = [0u8; 64];
+				let read = stream.read(&mut read_buf).await?;
+				assert_ne!(read, 0, "Connection closed before the first item");
+				response.extend_from_slice(&read_buf[..read]);
+			}
+
+			items_tx.unbounded_send(Value::from(2)).unwrap();
+			drop(items_tx);
+			while !response.ends_with(b"\n") {
+				let mut read_buf = [0u8; 64];
+				let read = stream.read(&mut read_buf).await?;
+				assert_ne!(read, 0, "Connection closed before the response was complete");
+				response.extend_from_slice(&read_buf[..read]);
+			}
+			Ok::<_, std::io::Error>(String::from_utf8(response).unwrap())
+		};
+		let rt = tokio::runtime::Runtime::new().unwrap();
+		let result = rt.block_on(reply).expect("wait for reply");
+
+		assert_eq!(result, "{\"jsonrpc\":\"2.0\",\"result\":[1,2],\"id\":1}\n");
+		server.close();
+	}
+
 	#[test]
 	fn req_parallel() {
 		crate::logger::init_log();
@@ -579,6 +709,38 @@ mod tests {
 		server.close();
 	}
 
+	#[test]
+	fn test_cancel_calls_on_session_end() {
+		crate::logger::init_log();
+		let path = "/tmp/test-ipc-30010";
+		let (signal, receiver) = std::sync::mpsc::channel();
+		let signal = Arc::new(Mutex::new(signal));
+
+		let mut io = MetaIoHandler::<CancellationToken>::default();
+		io.add_method_with_meta("hang", move |_params, meta: CancellationToken| {
+			let signal = signal.clone();
+			meta.on_cancel(move || signal.lock().send(()).unwrap());
+			futures::future::pending()
+		});
+		let builder = ServerBuilder::with_meta_extractor(io, |context: &RequestContext| context.cancellation().clone());
+		let server = builder.start(path).expect("Server must run with no issues");
+
+		{
+			use std::io::Write;
+
+			let mut stream = UnixStream::connect(path).expect("Socket should connect");
+			stream
+				.write_all(b"{\"jsonrpc\": \"2.0\", \"method\": \"hang\", \"id\": 1}\n")
+				.unwrap();
+			thread::sleep(Duration::from_millis(50));
+		}
+
+		receiver
+			.recv_timeout(Duration::from_secs(5))
+			.expect("The call should be cancelled once the connection is closed");
+		server.close();
+	}
+
 	#[test]
 	fn close_handle() {
 		crate::logger::init_log();
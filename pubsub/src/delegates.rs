@@ -1,9 +1,12 @@
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::core::discover::MethodDescription;
 use crate::core::futures::Future;
+use crate::core::types::raw::AnyParams;
 use crate::core::{self, Metadata, Params, RemoteProcedure, RpcMethod, Value};
-use crate::handler::{SubscribeRpcMethod, UnsubscribeRpcMethod};
+use crate::handler::{describe_subscription, SubscribeRpcMethod, UnsubscribeRpcMethod};
 use crate::subscription::{new_subscription, Subscriber};
 use crate::types::{PubSubMetadata, SubscriptionId};
 
@@ -85,10 +88,13 @@ where
 				closure: unsubscribe.1,
 			},
 		);
+		let (sub_description, unsub_description) = describe_subscription(name);
 		self.inner
 			.add_method_with_meta(subscribe.0, move |_, params, meta| sub.call(params, meta));
+		self.inner.describe_method(subscribe.0, sub_description);
 		self.inner
 			.add_method_with_meta(unsubscribe.0, move |_, params, meta| unsub.call(params, meta));
+		self.inner.describe_method(unsubscribe.0, unsub_description);
 	}
 
 	/// Adds an alias to existing method.
@@ -125,6 +131,50 @@ where
 	{
 		self.inner.add_notification(name, notification)
 	}
+
+	/// Adds async method accepting either parsed or raw params to the delegate.
+	pub fn add_any_params_method<F, I>(&mut self, name: &str, method: F)
+	where
+		F: Fn(&T, AnyParams) -> I,
+		I: Future<Output = core::Result<Value>> + Send + 'static,
+		F: Send + Sync + 'static,
+	{
+		self.inner.add_any_params_method(name, method)
+	}
+
+	/// Adds async method with metadata accepting either parsed or raw params to the delegate.
+	pub fn add_any_params_method_with_meta<F, I>(&mut self, name: &str, method: F)
+	where
+		F: Fn(&T, AnyParams, M) -> I,
+		I: Future<Output = core::Result<Value>> + Send + 'static,
+		F: Send + Sync + 'static,
+	{
+		self.inner.add_any_params_method_with_meta(name, method)
+	}
+
+	/// Adds notification accepting either parsed or raw params to the delegate.
+	pub fn add_any_params_notification<F>(&mut self, name: &str, notification: F)
+	where
+		F: Fn(&T, AnyParams),
+		F: Send + Sync + 'static,
+	{
+		self.inner.add_any_params_notification(name, notification)
+	}
+
+	/// Attaches a description to a previously added method, notification or subscription.
+	pub fn describe_method(&mut self, name: &str, description: MethodDescription) {
+		self.inner.describe_method(name, description)
+	}
+
+	/// Marks a previously added method, notification or subscription as deprecated.
+	pub fn deprecate_method(&mut self, name: &str, note: &str) {
+		self.inner.deprecate_method(name, note)
+	}
+
+	/// Sets a deadline for calls to a previously added method.
+	pub fn set_method_timeout(&mut self, name: &str, timeout: Duration) {
+		self.inner.set_method_timeout(name, timeout)
+	}
 }
 
 impl<T, M> core::IoHandlerExtension<M> for IoDelegate<T, M>
@@ -1,4 +1,5 @@
 use crate::core;
+use crate::core::discover::MethodDescription;
 use crate::core::futures::Future;
 
 use crate::subscription::{new_subscription, Subscriber};
@@ -67,11 +68,27 @@ impl<T: PubSubMetadata, S: core::Middleware<T>> PubSubHandler<T, S> {
 		G: UnsubscribeRpcMethod<T>,
 	{
 		let (sub, unsub) = new_subscription(notification, subscribe.1, unsubscribe.1);
+		let (sub_description, unsub_description) = describe_subscription(notification);
 		self.handler.add_method_with_meta(subscribe.0, sub);
+		self.handler.describe_method(subscribe.0, sub_description);
 		self.handler.add_method_with_meta(unsubscribe.0, unsub);
+		self.handler.describe_method(unsubscribe.0, unsub_description);
 	}
 }
 
+/// Returns discovery descriptions of subscribe and unsubscribe methods of given subscription.
+pub(crate) fn describe_subscription(notification: &str) -> (MethodDescription, MethodDescription) {
+	let subscribe = MethodDescription {
+		subscribe: Some(notification.into()),
+		..Default::default()
+	};
+	let unsubscribe = MethodDescription {
+		unsubscribe: Some(notification.into()),
+		..Default::default()
+	};
+	(subscribe, unsubscribe)
+}
+
 impl<T: PubSubMetadata, S: core::Middleware<T>> ::std::ops::Deref for PubSubHandler<T, S> {
 	type Target = core::MetaIoHandler<T, S>;
 
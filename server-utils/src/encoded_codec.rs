@@ -0,0 +1,180 @@
+use bytes::{Bytes, BytesMut};
+use jsonrpc_core::encoding::Encoding;
+use std::io;
+use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
+
+use crate::stream_codec::{Separator, StreamCodec};
+
+/// Part of an outgoing message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Chunk {
+	/// Part of a message to be continued with further chunks, written as it is.
+	Partial(Vec<u8>),
+	/// Complete message, or the last part of a chunked one, enveloped as a whole message.
+	Last(Vec<u8>),
+}
+
+/// Stream codec for messages of any encoding (ipc, tcp).
+///
+/// Text messages are enveloped with separators (see `StreamCodec`),
+/// binary messages are prefixed with their length as a 4-byte big-endian integer.
+/// Text messages can also be written in chunks (see `Chunk`), so that the separator follows the last one.
+#[derive(Debug)]
+pub enum EncodedCodec {
+	/// Codec for text encodings.
+	Text(StreamCodec),
+	/// Codec for binary encodings.
+	Binary(LengthDelimitedCodec),
+}
+
+impl EncodedCodec {
+	/// New codec for messages of given encoding. Separators are only used for text encodings.
+	pub fn new(encoding: &dyn Encoding, incoming_separator: Separator, outgoing_separator: Separator) -> Self {
+		if encoding.is_text() {
+			EncodedCodec::Text(StreamCodec::new(incoming_separator, outgoing_separator))
+		} else {
+			EncodedCodec::Binary(LengthDelimitedCodec::new())
+		}
+	}
+
+	/// Limits size of incoming messages, in bytes (see `StreamCodec::with_max_size`).
+	///
+	/// Binary messages are rejected by their length prefix, before they are read.
+	pub fn with_max_size(self, max_size: usize) -> Self {
+		match self {
+			EncodedCodec::Text(codec) => EncodedCodec::Text(codec.with_max_size(max_size)),
+			EncodedCodec::Binary(mut codec) => {
+				codec.set_max_frame_length(max_size);
+				EncodedCodec::Binary(codec)
+			}
+		}
+	}
+}
+
+impl Decoder for EncodedCodec {
+	type Item = Vec<u8>;
+	type Error = io::Error;
+
+	fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+		match self {
+			EncodedCodec::Text(codec) => codec.decode(buf).map(|msg| msg.map(String::into_bytes)),
+			EncodedCodec::Binary(codec) => codec.decode(buf).map(|msg| msg.map(|msg| msg.to_vec())),
+		}
+	}
+}
+
+impl Encoder<Vec<u8>> for EncodedCodec {
+	type Error = io::Error;
+
+	fn encode(&mut self, msg: Vec<u8>, buf: &mut BytesMut) -> io::Result<()> {
+		match self {
+			EncodedCodec::Text(codec) => {
+				let msg =
+					String::from_utf8(msg).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8"))?;
+				codec.encode(msg, buf)
+			}
+			EncodedCodec::Binary(codec) => codec.encode(Bytes::from(msg), buf),
+		}
+	}
+}
+
+impl Encoder<Chunk> for EncodedCodec {
+	type Error = io::Error;
+
+	fn encode(&mut self, chunk: Chunk, buf: &mut BytesMut) -> io::Result<()> {
+		match (self, chunk) {
+			(EncodedCodec::Text(_), Chunk::Partial(chunk)) => {
+				buf.extend_from_slice(&chunk);
+				Ok(())
+			}
+			(EncodedCodec::Binary(_), Chunk::Partial(_)) => Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"binary messages can't be written in chunks",
+			)),
+			(codec, Chunk::Last(msg)) => codec.encode(msg, buf),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpc_core::encoding::Json;
+
+	#[derive(Debug)]
+	struct Binary;
+
+	impl Encoding for Binary {
+		fn content_type(&self) -> &'static str {
+			"application/octet-stream"
+		}
+
+		fn decode_request(&self, _request: &[u8]) -> Result<jsonrpc_core::Request, jsonrpc_core::Error> {
+			Err(jsonrpc_core::Error::parse_error())
+		}
+
+		fn encode_response(&self, _response: &jsonrpc_core::Response) -> Vec<u8> {
+			Vec::new()
+		}
+
+		fn decode_value(&self, _message: &[u8]) -> Result<jsonrpc_core::Value, jsonrpc_core::Error> {
+			Err(jsonrpc_core::Error::parse_error())
+		}
+
+		fn encode_value(&self, _message: &jsonrpc_core::Value) -> Vec<u8> {
+			Vec::new()
+		}
+	}
+
+	#[test]
+	fn should_frame_text_messages_with_separators() {
+		let mut codec = EncodedCodec::new(&Json, Separator::Empty, Separator::default());
+		let mut buf = BytesMut::new();
+		codec.encode(b"{}".to_vec(), &mut buf).unwrap();
+		assert_eq!(&buf[..], b"{}\n");
+
+		let mut buf = BytesMut::from(&b"{\"a\":1}{\"b\":2}"[..]);
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(b"{\"a\":1}".to_vec()));
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(b"{\"b\":2}".to_vec()));
+	}
+
+	#[test]
+	fn should_write_separator_after_last_chunk() {
+		let mut codec = EncodedCodec::new(&Json, Separator::Empty, Separator::default());
+		let mut buf = BytesMut::new();
+		codec.encode(Chunk::Partial(b"[1".to_vec()), &mut buf).unwrap();
+		codec.encode(Chunk::Partial(b",2".to_vec()), &mut buf).unwrap();
+		codec.encode(Chunk::Last(b"]".to_vec()), &mut buf).unwrap();
+		assert_eq!(&buf[..], b"[1,2]\n");
+
+		let mut codec = EncodedCodec::new(&Binary, Separator::Empty, Separator::default());
+		assert!(codec.encode(Chunk::Partial(vec![1]), &mut buf).is_err());
+	}
+
+	#[test]
+	fn should_reject_oversized_messages_before_they_are_complete() {
+		let mut codec = EncodedCodec::new(&Json, Separator::Byte(b'\n'), Separator::default()).with_max_size(4);
+		let mut buf = BytesMut::from(&b"{}\n{\"a\":"[..]);
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(b"{}".to_vec()));
+		assert!(codec.decode(&mut buf).is_err());
+
+		let mut codec = EncodedCodec::new(&Json, Separator::Empty, Separator::default()).with_max_size(4);
+		assert!(codec.decode(&mut BytesMut::from(&b"[1,2,"[..])).is_err());
+
+		let mut codec = EncodedCodec::new(&Binary, Separator::Empty, Separator::default()).with_max_size(4);
+		assert!(codec.decode(&mut BytesMut::from(&[0, 0, 0, 5][..])).is_err());
+	}
+
+	#[test]
+	fn should_prefix_binary_messages_with_length() {
+		let mut codec = EncodedCodec::new(&Binary, Separator::Empty, Separator::default());
+		let mut buf = BytesMut::new();
+		codec.encode(vec![0x0a, 0x00], &mut buf).unwrap();
+		codec.encode(vec![0xff], &mut buf).unwrap();
+		assert_eq!(&buf[..], &[0, 0, 0, 2, 0x0a, 0x00, 0, 0, 0, 1, 0xff][..]);
+
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(vec![0x0a, 0x00]));
+		assert_eq!(codec.decode(&mut buf).unwrap(), Some(vec![0xff]));
+		assert_eq!(codec.decode(&mut buf).unwrap(), None);
+	}
+}
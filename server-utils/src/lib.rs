@@ -13,11 +13,13 @@ pub use tokio_stream;
 pub use tokio_util;
 
 pub mod cors;
+mod encoded_codec;
 pub mod hosts;
 mod matcher;
 pub mod reactor;
 pub mod session;
 mod stream_codec;
+pub mod streaming;
 mod suspendable_stream;
 
 pub use crate::matcher::Pattern;
@@ -25,5 +27,6 @@ pub use crate::suspendable_stream::SuspendableStream;
 
 /// Codecs utilities
 pub mod codecs {
+	pub use crate::encoded_codec::{Chunk, EncodedCodec};
 	pub use crate::stream_codec::{Separator, StreamCodec};
 }
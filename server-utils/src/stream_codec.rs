@@ -23,6 +23,7 @@ impl Default for Separator {
 pub struct StreamCodec {
 	incoming_separator: Separator,
 	outgoing_separator: Separator,
+	max_size: Option<usize>,
 }
 
 impl StreamCodec {
@@ -36,8 +37,24 @@ impl StreamCodec {
 		StreamCodec {
 			incoming_separator,
 			outgoing_separator,
+			max_size: None,
 		}
 	}
+
+	/// Limits size of incoming messages, in bytes.
+	///
+	/// Decoding fails as soon as more data is buffered without a complete message,
+	/// so oversized messages are never buffered in full.
+	pub fn with_max_size(mut self, max_size: usize) -> Self {
+		self.max_size = Some(max_size);
+		self
+	}
+}
+
+/// Error of a message exceeding the size limit.
+fn too_large(max_size: usize) -> io::Error {
+	warn!("Dropping a message exceeding {} bytes.", max_size);
+	io::Error::new(io::ErrorKind::InvalidData, "message too large")
 }
 
 fn is_whitespace(byte: u8) -> bool {
@@ -49,6 +66,17 @@ impl tokio_util::codec::Decoder for StreamCodec {
 	type Error = io::Error;
 
 	fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+		let message = self.decode_message(buf)?;
+		match (message, self.max_size) {
+			(Some(message), Some(max_size)) if message.len() > max_size => Err(too_large(max_size)),
+			(None, Some(max_size)) if buf.len() > max_size => Err(too_large(max_size)),
+			(message, _) => Ok(message),
+		}
+	}
+}
+
+impl StreamCodec {
+	fn decode_message(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
 		if let Separator::Byte(separator) = self.incoming_separator {
 			if let Some(i) = buf.as_ref().iter().position(|&b| b == separator) {
 				let line = buf.split_to(i);
@@ -0,0 +1,106 @@
+//! Handling of requests received by stream transports (ipc, tcp).
+
+use std::io;
+use std::pin::Pin;
+use std::str;
+use std::sync::Arc;
+
+use futures::{future, stream, FutureExt, Stream, StreamExt};
+use jsonrpc_core::encoding::Encoding;
+use jsonrpc_core::streaming::StreamingResponse;
+use jsonrpc_core::{BoxFuture, MetaIoHandler, Metadata, Middleware};
+
+use crate::encoded_codec::Chunk;
+
+/// Chunks of a single outgoing message.
+///
+/// An error means the message could not be completed, the connection should be closed
+/// so that the peer doesn't mistake the part written so far for a complete message.
+pub type MessageChunks = Pin<Box<dyn Stream<Item = io::Result<Chunk>> + Send>>;
+
+/// Returns a message written at once.
+pub fn message(message: Vec<u8>) -> MessageChunks {
+	Box::pin(stream::once(future::ready(Ok(Chunk::Last(message)))))
+}
+
+/// Handles a request, resolving to the chunks of the response (if any).
+///
+/// Calls of streaming methods (see `MetaIoHandler::add_stream_method`) are answered chunk by chunk
+/// as the items are produced if messages are text, all other responses are written at once.
+pub fn handle_request<M: Metadata, S: Middleware<M>>(
+	handler: &MetaIoHandler<M, S>,
+	request: &[u8],
+	encoding: Arc<dyn Encoding>,
+	meta: M,
+) -> BoxFuture<Option<MessageChunks>> {
+	let request = match str::from_utf8(request) {
+		Ok(request) if encoding.is_text() => request,
+		_ => {
+			return Box::pin(
+				handler
+					.handle_encoded_request(request, encoding, meta)
+					.map(|r| r.map(message)),
+			)
+		}
+	};
+
+	Box::pin(handler.handle_request_streaming(request, meta).map(|response| {
+		response.map(|response| match response {
+			StreamingResponse::Complete(response) => message(response.into_bytes()),
+			StreamingResponse::Chunked(chunks) => {
+				let chunks = chunks.map(|chunk| match chunk {
+					Ok(chunk) => Ok(Chunk::Partial(chunk.into_bytes())),
+					Err(error) => {
+						warn!(
+							"Streaming method failed after part of the response was sent: {:?}",
+							error
+						);
+						Err(io::Error::other("streaming method failed"))
+					}
+				});
+				Box::pin(chunks.chain(stream::once(future::ready(Ok(Chunk::Last(Vec::new())))))) as MessageChunks
+			}
+		})
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use futures::TryStreamExt;
+	use jsonrpc_core::encoding::Json;
+	use jsonrpc_core::{IoHandler, Params, Value};
+
+	fn chunks(io: &IoHandler, request: &str) -> io::Result<Vec<Chunk>> {
+		let response = block_on(handle_request(io, request.as_bytes(), Arc::new(Json), ())).unwrap();
+		block_on(response.try_collect())
+	}
+
+	#[test]
+	fn should_write_results_of_streaming_methods_in_chunks() {
+		let mut io = IoHandler::new();
+		io.add_stream_method("numbers", |_params: Params, _meta: ()| {
+			stream::iter(vec![Ok(Value::from(1)), Ok(Value::from(2))])
+		});
+		io.add_stream_method("failing", |_params: Params, _meta: ()| {
+			stream::iter(vec![Ok(Value::from(1)), Err(jsonrpc_core::Error::internal_error())])
+		});
+		io.add_sync_method("hello", |_| Ok(Value::from("world")));
+
+		assert_eq!(
+			chunks(&io, r#"{"jsonrpc":"2.0","method":"numbers","id":1}"#).unwrap(),
+			vec![
+				Chunk::Partial(br#"{"jsonrpc":"2.0","result":[1"#.to_vec()),
+				Chunk::Partial(b",2".to_vec()),
+				Chunk::Partial(br#"],"id":1}"#.to_vec()),
+				Chunk::Last(Vec::new()),
+			]
+		);
+		assert_eq!(
+			chunks(&io, r#"{"jsonrpc":"2.0","method":"hello","id":1}"#).unwrap(),
+			vec![Chunk::Last(br#"{"jsonrpc":"2.0","result":"world","id":1}"#.to_vec())]
+		);
+		assert!(chunks(&io, r#"{"jsonrpc":"2.0","method":"failing","id":1}"#).is_err());
+	}
+}
@@ -28,8 +28,55 @@ extern crate log;
 pub use jsonrpc_core;
 pub use tokio;
 
-use jsonrpc_core::{MetaIoHandler, Metadata, Middleware};
-use tokio_util::codec::{FramedRead, LinesCodec};
+use jsonrpc_core::streaming::{ResponseChunks, StreamingResponse};
+use jsonrpc_core::{Error, MetaIoHandler, Metadata, Middleware, Response, Version};
+use tokio_util::codec::{Decoder, FramedRead, LinesCodec, LinesCodecError};
+
+/// A line read from STDIN.
+enum Line {
+	/// A complete request.
+	Request(String),
+	/// A line longer than the maximal request size, the rest of it is skipped.
+	TooLarge(usize),
+}
+
+/// `LinesCodec` which keeps reading after an oversized line.
+struct LineReader {
+	codec: LinesCodec,
+	max_size: Option<usize>,
+}
+
+impl LineReader {
+	fn new(max_size: Option<usize>) -> Self {
+		let codec = match max_size {
+			Some(max_size) => LinesCodec::new_with_max_length(max_size),
+			None => LinesCodec::new(),
+		};
+		LineReader { codec, max_size }
+	}
+
+	fn map(&self, line: Result<Option<String>, LinesCodecError>) -> Result<Option<Line>, LinesCodecError> {
+		match (line, self.max_size) {
+			(Err(LinesCodecError::MaxLineLengthExceeded), Some(max_size)) => Ok(Some(Line::TooLarge(max_size))),
+			(line, _) => line.map(|line| line.map(Line::Request)),
+		}
+	}
+}
+
+impl Decoder for LineReader {
+	type Item = Line;
+	type Error = LinesCodecError;
+
+	fn decode(&mut self, buf: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+		let line = self.codec.decode(buf);
+		self.map(line)
+	}
+
+	fn decode_eof(&mut self, buf: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+		let line = self.codec.decode_eof(buf);
+		self.map(line)
+	}
+}
 
 /// Stdio server builder
 pub struct ServerBuilder<M: Metadata = (), T: Middleware<M> = jsonrpc_core::NoopMiddleware> {
@@ -54,24 +101,48 @@ where
 	/// Will block until EOF is read or until an error occurs.
 	/// The server reads from STDIN line-by-line, one request is taken
 	/// per line and each response is written to STDOUT on a new line.
+	/// Results of streaming methods are written as they are produced.
+	/// Lines longer than the handler's maximal request size are skipped
+	/// and answered with an error. The size is read once, when this method is called.
 	pub fn build(&self) -> impl Future<Output = ()> + 'static {
 		let handler = self.handler.clone();
+		let max_request_size = handler.max_request_size();
 
 		async move {
 			let stdin = tokio::io::stdin();
 			let mut stdout = tokio::io::stdout();
 
-			let mut framed_stdin = FramedRead::new(stdin, LinesCodec::new());
+			let mut framed_stdin = FramedRead::new(stdin, LineReader::new(max_request_size));
 
 			use futures::StreamExt;
 			while let Some(request) = framed_stdin.next().await {
 				match request {
-					Ok(line) => {
-						let res = Self::process(&handler, line).await;
-						let mut sanitized = res.replace('\n', "");
-						sanitized.push('\n');
+					Ok(Line::TooLarge(max_size)) => {
 						use tokio::io::AsyncWriteExt;
-						if let Err(e) = stdout.write_all(sanitized.as_bytes()).await {
+						let response = Response::from(Error::request_too_large(max_size), Some(Version::V2));
+						let response = jsonrpc_core::serde_json::to_string(&response)
+							.expect("Response is serializable to JSON; qed");
+						if let Err(e) = stdout.write_all(format!("{}\n", response).as_bytes()).await {
+							log::warn!("Error writing response: {:?}", e);
+						}
+					}
+					Ok(Line::Request(line)) => {
+						use tokio::io::AsyncWriteExt;
+						let mut chunks = Self::process(&handler, line).await;
+						while let Some(chunk) = chunks.next().await {
+							// the line is terminated anyway, so a truncated response is never valid JSON
+							let chunk = match chunk {
+								Ok(chunk) => chunk.replace('\n', ""),
+								Err(e) => {
+									log::warn!("Error producing response: {:?}", e);
+									break;
+								}
+							};
+							if let Err(e) = stdout.write_all(chunk.as_bytes()).await {
+								log::warn!("Error writing response: {:?}", e);
+							}
+						}
+						if let Err(e) = stdout.write_all(b"\n").await {
 							log::warn!("Error writing response: {:?}", e);
 						}
 					}
@@ -83,15 +154,16 @@ where
 		}
 	}
 
-	/// Process a request asynchronously
-	fn process(io: &Arc<MetaIoHandler<M, T>>, input: String) -> impl Future<Output = String> + Send {
-		use jsonrpc_core::futures::FutureExt;
-		let f = io.handle_request(&input, Default::default());
+	/// Process a request asynchronously, resolving to chunks of the response
+	fn process(io: &Arc<MetaIoHandler<M, T>>, input: String) -> impl Future<Output = ResponseChunks> + Send {
+		use jsonrpc_core::futures::{future, stream, FutureExt};
+		let f = io.handle_request_streaming(&input, Default::default());
 		f.map(move |result| match result {
-			Some(res) => res,
+			Some(StreamingResponse::Complete(res)) => Box::pin(stream::once(future::ready(Ok(res)))) as ResponseChunks,
+			Some(StreamingResponse::Chunked(chunks)) => chunks,
 			None => {
 				info!("JSON RPC request produced no response: {:?}", input);
-				String::from("")
+				Box::pin(stream::empty())
 			}
 		})
 	}
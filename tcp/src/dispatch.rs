@@ -5,6 +5,8 @@ use std::sync::Arc;
 use std::task::Poll;
 
 use crate::futures::{channel::mpsc, Stream};
+use crate::jsonrpc::encoding::Encoding;
+use crate::server_utils::streaming::{self, MessageChunks};
 
 use parking_lot::Mutex;
 
@@ -13,14 +15,21 @@ pub type SenderChannels = Mutex<HashMap<SocketAddr, mpsc::UnboundedSender<String
 pub struct PeerMessageQueue<S: Stream + Unpin> {
 	up: S,
 	receiver: Option<mpsc::UnboundedReceiver<String>>,
+	encoding: Arc<dyn Encoding>,
 	_addr: SocketAddr,
 }
 
 impl<S: Stream + Unpin> PeerMessageQueue<S> {
-	pub fn new(response_stream: S, receiver: mpsc::UnboundedReceiver<String>, addr: SocketAddr) -> Self {
+	pub fn new(
+		response_stream: S,
+		receiver: mpsc::UnboundedReceiver<String>,
+		encoding: Arc<dyn Encoding>,
+		addr: SocketAddr,
+	) -> Self {
 		PeerMessageQueue {
 			up: response_stream,
 			receiver: Some(receiver),
+			encoding,
 			_addr: addr,
 		}
 	}
@@ -77,8 +86,8 @@ impl Dispatcher {
 	}
 }
 
-impl<S: Stream<Item = std::io::Result<String>> + Unpin> Stream for PeerMessageQueue<S> {
-	type Item = std::io::Result<String>;
+impl<S: Stream<Item = std::io::Result<MessageChunks>> + Unpin> Stream for PeerMessageQueue<S> {
+	type Item = std::io::Result<MessageChunks>;
 
 	// The receiver will never return `Ok(Async::Ready(None))`
 	// Because the sender is kept in `SenderChannels` and it will never be dropped until `the stream` is resolved.
@@ -107,13 +116,22 @@ impl<S: Stream<Item = std::io::Result<String>> + Unpin> Stream for PeerMessageQu
 			Some(rx) => rx,
 		};
 
-		match Pin::new(&mut rx).poll_next(cx) {
-			Poll::Ready(Some(item)) => Poll::Ready(Some(Ok(item))),
-			Poll::Ready(None) | Poll::Pending if up_closed => {
-				this.receiver = None;
-				Poll::Ready(None)
-			}
-			Poll::Ready(None) | Poll::Pending => Poll::Pending,
+		loop {
+			return match Pin::new(&mut rx).poll_next(cx) {
+				// pushed messages are JSON text, re-encode them for the peer
+				Poll::Ready(Some(item)) => match this.encoding.encode_json(&item) {
+					Ok(item) => Poll::Ready(Some(Ok(streaming::message(item)))),
+					Err(_) => {
+						warn!(target: "tcp", "Dropping message which is not valid JSON: {}", item);
+						continue;
+					}
+				},
+				Poll::Ready(None) | Poll::Pending if up_closed => {
+					this.receiver = None;
+					Poll::Ready(None)
+				}
+				Poll::Ready(None) | Poll::Pending => Poll::Pending,
+			};
 		}
 	}
 }
@@ -1,6 +1,6 @@
 use std::net::SocketAddr;
 
-use crate::jsonrpc::{futures::channel::mpsc, Metadata};
+use crate::jsonrpc::{futures::channel::mpsc, CancellationToken, Metadata};
 
 /// Request context
 pub struct RequestContext {
@@ -8,6 +8,23 @@ pub struct RequestContext {
 	pub peer_addr: SocketAddr,
 	/// Peer Sender channel
 	pub sender: mpsc::UnboundedSender<String>,
+	cancellation: CancellationToken,
+}
+
+impl RequestContext {
+	/// Creates a context of a new connection.
+	pub fn new(peer_addr: SocketAddr, sender: mpsc::UnboundedSender<String>) -> Self {
+		RequestContext {
+			peer_addr,
+			sender,
+			cancellation: CancellationToken::new(),
+		}
+	}
+
+	/// Token cancelled once the connection is closed.
+	pub fn cancellation(&self) -> &CancellationToken {
+		&self.cancellation
+	}
 }
 
 /// Metadata extractor (per session)
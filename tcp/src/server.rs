@@ -6,7 +6,9 @@ use std::sync::Arc;
 use tower_service::Service as _;
 
 use crate::futures::{self, future};
+use crate::jsonrpc::encoding::{Encoding, Json};
 use crate::jsonrpc::{middleware, MetaIoHandler, Metadata, Middleware};
+use crate::server_utils::streaming::{self, MessageChunks};
 use crate::server_utils::tokio_stream::wrappers::TcpListenerStream;
 use crate::server_utils::{codecs, reactor, tokio, tokio_util::codec::Framed, SuspendableStream};
 
@@ -22,6 +24,7 @@ pub struct ServerBuilder<M: Metadata = (), S: Middleware<M> = middleware::Noop>
 	channels: Arc<SenderChannels>,
 	incoming_separator: codecs::Separator,
 	outgoing_separator: codecs::Separator,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<M: Metadata + Default, S: Middleware<M> + 'static> ServerBuilder<M, S>
@@ -56,6 +59,7 @@ where
 			channels: Default::default(),
 			incoming_separator: Default::default(),
 			outgoing_separator: Default::default(),
+			encoding: Arc::new(Json),
 		}
 	}
 
@@ -78,6 +82,16 @@ where
 		self
 	}
 
+	/// Sets the encoding of requests and responses, JSON by default.
+	///
+	/// Messages of binary encodings are prefixed with their length (4-byte big-endian)
+	/// instead of being enveloped with separators.
+	/// Binary encodings are available with `msgpack` and `cbor` features.
+	pub fn encoding<E: Encoding>(mut self, encoding: E) -> Self {
+		self.encoding = Arc::new(encoding);
+		self
+	}
+
 	/// Starts a new server
 	pub fn start(self, addr: &SocketAddr) -> io::Result<Server> {
 		let meta_extractor = self.meta_extractor.clone();
@@ -85,6 +99,7 @@ where
 		let channels = self.channels.clone();
 		let incoming_separator = self.incoming_separator;
 		let outgoing_separator = self.outgoing_separator;
+		let encoding = self.encoding;
 		let address = addr.to_owned();
 		let (tx, rx) = std::sync::mpsc::channel();
 		let (stop_tx, stop_rx) = futures::channel::oneshot::channel();
@@ -109,48 +124,59 @@ where
 					trace!(target: "tcp", "Accepted incoming connection from {}", &peer_addr);
 					let (sender, receiver) = futures::channel::mpsc::unbounded();
 
-					let context = RequestContext {
-						peer_addr,
-						sender: sender.clone(),
-					};
+					let context = RequestContext::new(peer_addr, sender.clone());
+					let cancellation = context.cancellation().clone();
 
 					let meta = meta_extractor.extract(&context);
-					let mut service = Service::new(peer_addr, rpc_handler.clone(), meta);
-					let (mut writer, reader) = Framed::new(
-						socket,
-						codecs::StreamCodec::new(incoming_separator.clone(), outgoing_separator.clone()),
-					)
-					.split();
-
+					let mut service = Service::new(peer_addr, rpc_handler.clone(), meta, encoding.clone());
+					let mut codec =
+						codecs::EncodedCodec::new(&*encoding, incoming_separator.clone(), outgoing_separator.clone());
+					// read for every connection, so that changes of a shared handler apply to new connections
+					if let Some(max_size) = rpc_handler.max_request_size() {
+						codec = codec.with_max_size(max_size);
+					}
+					let (mut writer, reader) = Framed::new(socket, codec).split();
+
+					// Text encodings send an empty line for requests without response,
+					// an empty binary message would be malformed though.
+					let is_text = encoding.is_text();
+					let no_response = move || -> MessageChunks {
+						if is_text {
+							streaming::message(Vec::new())
+						} else {
+							Box::pin(futures::stream::empty())
+						}
+					};
 					// Work around https://github.com/rust-lang/rust/issues/64552 by boxing the stream type
-					let responses: Pin<Box<dyn futures::Stream<Item = io::Result<String>> + Send>> =
+					let responses: Pin<Box<dyn futures::Stream<Item = io::Result<MessageChunks>> + Send>> =
 						Box::pin(reader.and_then(move |req| {
-							service.call(req).then(|response| match response {
+							service.call(req).then(move |response| match response {
 								Err(e) => {
 									warn!(target: "tcp", "Error while processing request: {:?}", e);
-									future::ok(String::new())
+									future::ok(no_response())
 								}
 								Ok(None) => {
 									trace!(target: "tcp", "JSON RPC request produced no response");
-									future::ok(String::new())
-								}
-								Ok(Some(response_data)) => {
-									trace!(target: "tcp", "Sent response: {}", &response_data);
-									future::ok(response_data)
+									future::ok(no_response())
 								}
+								Ok(Some(response)) => future::ok(response),
 							})
 						}));
 
-					let mut peer_message_queue = {
+					let peer_message_queue = {
 						let mut channels = channels.lock();
 						channels.insert(peer_addr, sender);
 
-						PeerMessageQueue::new(responses, receiver, peer_addr)
+						PeerMessageQueue::new(responses, receiver, encoding.clone(), peer_addr)
 					};
+					// All chunks of a message are written before the next message is taken from the queue.
+					let mut outgoing: Pin<Box<dyn futures::Stream<Item = io::Result<codecs::Chunk>> + Send>> =
+						Box::pin(peer_message_queue.try_flatten());
 
 					let shared_channels = channels.clone();
 					let writer = async move {
-						writer.send_all(&mut peer_message_queue).await?;
+						let _cancel_on_close = cancellation.drop_guard();
+						writer.send_all(&mut outgoing).await?;
 						trace!(target: "tcp", "Peer {}: service finished", peer_addr);
 						let mut channels = shared_channels.lock();
 						channels.remove(&peer_addr);
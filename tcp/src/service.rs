@@ -4,32 +4,35 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use crate::futures;
+use crate::jsonrpc::encoding::Encoding;
 use crate::jsonrpc::{middleware, MetaIoHandler, Metadata, Middleware};
+use crate::server_utils::streaming::{self, MessageChunks};
 
 pub struct Service<M: Metadata = (), S: Middleware<M> = middleware::Noop> {
 	handler: Arc<MetaIoHandler<M, S>>,
 	peer_addr: SocketAddr,
 	meta: M,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<M: Metadata, S: Middleware<M>> Service<M, S> {
-	pub fn new(peer_addr: SocketAddr, handler: Arc<MetaIoHandler<M, S>>, meta: M) -> Self {
+	pub fn new(peer_addr: SocketAddr, handler: Arc<MetaIoHandler<M, S>>, meta: M, encoding: Arc<dyn Encoding>) -> Self {
 		Service {
 			handler,
 			peer_addr,
 			meta,
+			encoding,
 		}
 	}
 }
 
-impl<M: Metadata, S: Middleware<M>> tower_service::Service<String> for Service<M, S>
+impl<M: Metadata, S: Middleware<M>> tower_service::Service<Vec<u8>> for Service<M, S>
 where
 	S::Future: Unpin,
 	S::CallFuture: Unpin,
 {
 	// These types must match the corresponding protocol types:
-	type Response = Option<String>;
+	type Response = Option<MessageChunks>;
 	// For non-streaming protocols, service errors are always io::Error
 	type Error = ();
 
@@ -41,9 +44,9 @@ where
 	}
 
 	// Produce a future for computing a response from a request.
-	fn call(&mut self, req: String) -> Self::Future {
-		use futures::FutureExt;
-		trace!(target: "tcp", "Accepted request from peer {}: {}", &self.peer_addr, req);
-		Box::pin(self.handler.handle_request(&req, self.meta.clone()).map(Ok))
+	fn call(&mut self, req: Vec<u8>) -> Self::Future {
+		use crate::futures::FutureExt;
+		trace!(target: "tcp", "Accepted request from peer {}: {}", &self.peer_addr, String::from_utf8_lossy(&req));
+		Box::pin(streaming::handle_request(&self.handler, &req, self.encoding.clone(), self.meta.clone()).map(Ok))
 	}
 }
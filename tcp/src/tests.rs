@@ -112,6 +112,102 @@ fn doc_test_handle() {
 	);
 }
 
+#[test]
+fn encoded_request() {
+	use jsonrpc_core::encoding::{Encoding, MessagePack};
+
+	crate::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17781".parse().unwrap();
+
+	let server = casual_server().encoding(MessagePack);
+	let _server = server.start(&addr).expect("Server must run with no issues");
+
+	let request = MessagePack
+		.encode_json(r#"{"jsonrpc": "2.0", "method": "say_hello", "params": [42, 23], "id": 1}"#)
+		.unwrap();
+	let mut frame = (request.len() as u32).to_be_bytes().to_vec();
+	frame.extend(request);
+
+	let result = dummy_request(&addr, frame);
+
+	assert_eq!(
+		u32::from_be_bytes([result[0], result[1], result[2], result[3]]) as usize,
+		result.len() - 4
+	);
+	assert_eq!(
+		MessagePack.decode_json(&result[4..]).unwrap(),
+		r#"{"id":1,"jsonrpc":"2.0","result":"hello"}"#,
+	);
+}
+
+#[test]
+fn oversized_request() {
+	crate::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17784".parse().unwrap();
+
+	let mut io = MetaIoHandler::<()>::default();
+	io.add_sync_method("say_hello", |_params| Ok(Value::String("hello".to_string())));
+	io.set_max_request_size(64);
+	let _server = ServerBuilder::new(io)
+		.start(&addr)
+		.expect("Server must run with no issues");
+
+	let mut request = b"{\"jsonrpc\": \"2.0\", \"method\": \"say_hello\", \"params\": [".to_vec();
+	request.extend(vec![b'1'; 64]);
+	request.extend(b"], \"id\": 1}\n");
+	let result = dummy_request_str(&addr, request);
+
+	// the connection is closed before the request is complete
+	assert_eq!(result, "");
+}
+
+#[test]
+fn streamed_response() {
+	use crate::futures::{channel::mpsc, future, stream, StreamExt};
+
+	crate::logger::init_log();
+	let addr: SocketAddr = "127.0.0.1:17783".parse().unwrap();
+
+	let (items_tx, items_rx) = mpsc::unbounded();
+	let items_rx = Mutex::new(Some(items_rx));
+	let mut io = MetaIoHandler::<()>::default();
+	io.add_stream_method("numbers", move |_params, _meta| {
+		let rest = items_rx.lock().take().expect("Called once; qed");
+		stream::once(future::ready(Ok(Value::from(1)))).chain(rest.map(Ok))
+	});
+	let _server = ServerBuilder::new(io)
+		.start(&addr)
+		.expect("Server must run with no issues");
+
+	let client = async move {
+		let mut stream = TcpStream::connect(&addr).await?;
+		let request = b"{\"jsonrpc\": \"2.0\", \"method\": \"numbers\", \"id\": 1}\n";
+		stream.write_all(&request[..]).await?;
+		stream.shutdown().await?;
+
+		// the first item is written before the rest is produced
+		let mut response = vec![];
+		while !response.ends_with(b"[1") {
+			let mut read_buf = [0u8; 64];
+			let read = stream.read(&mut read_buf).await?;
+			assert_ne!(read, 0, "Connection closed before the first item");
+			response.extend_from_slice(&read_buf[..read]);
+		}
+
+		items_tx.unbounded_send(Value::from(2)).unwrap();
+		drop(items_tx);
+		stream.read_to_end(&mut response).await?;
+		assert_eq!(
+			String::from_utf8(response).unwrap(),
+			"{\"jsonrpc\":\"2.0\",\"result\":[1,2],\"id\":1}\n"
+		);
+
+		Ok::<(), Box<dyn std::error::Error>>(())
+	};
+
+	run_future(client).unwrap();
+}
+
 #[test]
 fn req_parallel() {
 	use std::thread;
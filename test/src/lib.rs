@@ -47,6 +47,8 @@
 
 extern crate jsonrpc_core as rpc;
 
+pub mod replay;
+
 /// Test RPC options.
 #[derive(Default, Debug)]
 pub struct Options {
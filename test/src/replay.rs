@@ -0,0 +1,250 @@
+//! Replaying logs recorded by `RecordingMiddleware`.
+//!
+//! Requests are replayed one by one, in recorded order, and responses are compared
+//! with the recorded ones, which makes it possible to reproduce issues seen in production
+//! and to check that a new version of a handler still responds the same way.
+//!
+//! ```
+//! use jsonrpc_core::{IoHandler, Value};
+//! use jsonrpc_test::replay;
+//!
+//! let log = r#"{"timestamp_ms":0,"request":{"jsonrpc":"2.0","method":"add","params":[2,3],"id":1},"response":{"jsonrpc":"2.0","result":5,"id":1},"duration_ms":0.1}"#;
+//!
+//! let mut io = IoHandler::new();
+//! io.add_sync_method("add", |params: jsonrpc_core::Params| {
+//!     let (a, b): (u64, u64) = params.parse()?;
+//!     Ok(Value::from(a * b))
+//! });
+//!
+//! let report = replay::replay(&io, log.as_bytes(), |_| ()).unwrap();
+//! assert_eq!(report.replayed, 1);
+//! assert_eq!(report.mismatches.len(), 1);
+//! ```
+
+use std::fmt;
+use std::io::{self, BufRead};
+
+use crate::rpc::futures::executor;
+use crate::rpc::middleware::recording::Record;
+use crate::rpc::{MetaIoHandler, Metadata, Middleware, Request, Response, Value};
+
+/// Response of a replayed request which differs from the recorded one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+	/// Line of the log (starting at 1) the request was recorded at.
+	pub line: usize,
+	/// The replayed request.
+	pub request: Request,
+	/// Recorded response.
+	pub expected: Option<Response>,
+	/// Response produced by the handler.
+	pub actual: Option<Response>,
+}
+
+impl Mismatch {
+	/// Returns paths (e.g. `$[1].result.balance`) at which responses differ, with both values.
+	///
+	/// A missing value means the path is only present in the other response.
+	pub fn differences(&self) -> Vec<(String, Option<Value>, Option<Value>)> {
+		let to_value = |response: &Option<Response>| {
+			serde_json::to_value(response).expect("Responses are serializable to JSON; qed")
+		};
+		let mut differences = Vec::new();
+		diff(
+			"$".into(),
+			&to_value(&self.expected),
+			&to_value(&self.actual),
+			&mut differences,
+		);
+		differences
+	}
+}
+
+fn diff(path: String, expected: &Value, actual: &Value, out: &mut Vec<(String, Option<Value>, Option<Value>)>) {
+	match (expected, actual) {
+		(Value::Object(expected), Value::Object(actual)) => {
+			for (key, expected) in expected {
+				let path = format!("{}.{}", path, key);
+				match actual.get(key) {
+					Some(actual) => diff(path, expected, actual, out),
+					None => out.push((path, Some(expected.clone()), None)),
+				}
+			}
+			for (key, actual) in actual {
+				if !expected.contains_key(key) {
+					out.push((format!("{}.{}", path, key), None, Some(actual.clone())));
+				}
+			}
+		}
+		(Value::Array(expected), Value::Array(actual)) => {
+			for i in 0..expected.len().max(actual.len()) {
+				let path = format!("{}[{}]", path, i);
+				match (expected.get(i), actual.get(i)) {
+					(Some(expected), Some(actual)) => diff(path, expected, actual, out),
+					(expected, actual) => out.push((path, expected.cloned(), actual.cloned())),
+				}
+			}
+		}
+		(expected, actual) if expected != actual => out.push((path, Some(expected.clone()), Some(actual.clone()))),
+		_ => {}
+	}
+}
+
+/// Result of replaying a log.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+	/// Number of replayed requests.
+	pub replayed: usize,
+	/// Replayed requests with responses differing from the recorded ones.
+	pub mismatches: Vec<Mismatch>,
+}
+
+impl Report {
+	/// Returns `true` if all responses matched the recorded ones.
+	pub fn is_ok(&self) -> bool {
+		self.mismatches.is_empty()
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(
+			f,
+			"Replayed {} requests, {} responses differ.",
+			self.replayed,
+			self.mismatches.len()
+		)?;
+		let show = |value: &Option<Value>| match value {
+			Some(value) => value.to_string(),
+			None => "<missing>".into(),
+		};
+		for mismatch in &self.mismatches {
+			let request = serde_json::to_string(&mismatch.request).expect("Requests are serializable to JSON; qed");
+			writeln!(f, "\nline {}: {}", mismatch.line, request)?;
+			for (path, expected, actual) in mismatch.differences() {
+				writeln!(f, "  {}: expected {}, got {}", path, show(&expected), show(&actual))?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Replays requests of a log recorded by `RecordingMiddleware` and compares the responses.
+///
+/// `meta` creates metadata of each request from its record, e.g. from the recorded summary.
+/// Empty lines are skipped, other lines which are not valid records fail the replay.
+pub fn replay<T, S, R, F>(io: &MetaIoHandler<T, S>, log: R, meta: F) -> io::Result<Report>
+where
+	T: Metadata,
+	S: Middleware<T>,
+	R: BufRead,
+	F: Fn(&Record) -> T,
+{
+	let mut report = Report::default();
+	for (i, line) in log.lines().enumerate() {
+		let line = line?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		let record: Record = serde_json::from_str(&line).map_err(|err| {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("Invalid record at line {}: {}", i + 1, err),
+			)
+		})?;
+
+		let actual = executor::block_on(io.handle_rpc_request(record.request.clone(), meta(&record)));
+		report.replayed += 1;
+		if actual != record.response {
+			report.mismatches.push(Mismatch {
+				line: i + 1,
+				request: record.request,
+				expected: record.response,
+				actual,
+			});
+		}
+	}
+	Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rpc::middleware::recording::RecordingMiddleware;
+	use crate::rpc::{Error, Params};
+	use std::sync::{Arc, Mutex};
+
+	#[derive(Clone, Default)]
+	struct Log(Arc<Mutex<Vec<u8>>>);
+
+	impl io::Write for Log {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	fn record(log: Log) {
+		let recording = RecordingMiddleware::new(log);
+		let mut io = MetaIoHandler::with_middleware(recording.clone());
+		io.add_sync_method("balance", |_: Params| Ok(serde_json::json!({"free": 10, "locked": 2})));
+		io.add_sync_method("fail", |_: Params| -> rpc::Result<Value> {
+			Err(Error::internal_error())
+		});
+		io.add_notification("notify", |_: Params| {});
+
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"balance","id":1}"#, ());
+		io.handle_request_sync(
+			r#"[{"jsonrpc":"2.0","method":"fail","id":2},{"jsonrpc":"2.0","method":"notify"}]"#,
+			(),
+		);
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"notify"}"#, ());
+		recording.flush();
+	}
+
+	#[test]
+	fn should_replay_recorded_requests() {
+		let log = Log::default();
+		record(log.clone());
+		let log = log.0.lock().unwrap().clone();
+
+		let mut io = MetaIoHandler::<()>::default();
+		io.add_sync_method("balance", |_: Params| {
+			Ok(serde_json::json!({"free": 10, "reserved": 2}))
+		});
+		io.add_sync_method("fail", |_: Params| -> rpc::Result<Value> {
+			Err(Error::internal_error())
+		});
+		io.add_notification("notify", |_: Params| {});
+
+		let report = replay(&io, &log[..], |_| ()).unwrap();
+
+		assert_eq!(report.replayed, 3);
+		assert!(!report.is_ok());
+		assert_eq!(report.mismatches.len(), 1);
+		assert_eq!(report.mismatches[0].line, 1);
+		assert_eq!(
+			report.mismatches[0].differences(),
+			vec![
+				("$.result.locked".into(), Some(Value::from(2)), None),
+				("$.result.reserved".into(), None, Some(Value::from(2))),
+			]
+		);
+		assert!(report
+			.to_string()
+			.contains("  $.result.locked: expected 2, got <missing>\n"));
+	}
+
+	#[test]
+	fn should_fail_on_invalid_records() {
+		let io = MetaIoHandler::<()>::default();
+
+		let err = replay(&io, &b"\n{}\n"[..], |_| ()).unwrap_err();
+
+		assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+		assert!(err.to_string().starts_with("Invalid record at line 2"));
+	}
+}
@@ -1,4 +1,7 @@
 //! `WebSockets` server.
+//!
+//! Results of streaming methods (see `jsonrpc_core::streaming`) are not streamed over websockets,
+//! they are collected and sent in a single message.
 
 #![deny(missing_docs)]
 
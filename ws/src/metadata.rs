@@ -5,6 +5,7 @@ use std::sync::{atomic, Arc};
 use std::task::{Context, Poll};
 
 use crate::core;
+use crate::core::encoding::Encoding;
 use crate::core::futures::channel::mpsc;
 use crate::server_utils::{reactor::TaskExecutor, session};
 use crate::ws;
@@ -76,15 +77,47 @@ pub struct RequestContext {
 	pub out: Sender,
 	/// Remote to underlying event loop.
 	pub executor: TaskExecutor,
+	pub(crate) cancellation: core::CancellationToken,
+	pub(crate) encoding: Arc<dyn Encoding>,
 }
 
 impl RequestContext {
+	/// Creates a context of a new session, exchanging JSON messages.
+	pub fn new(
+		session_id: session::SessionId,
+		origin: Option<Origin>,
+		protocols: Vec<String>,
+		out: Sender,
+		executor: TaskExecutor,
+	) -> Self {
+		RequestContext {
+			session_id,
+			origin,
+			protocols,
+			out,
+			executor,
+			cancellation: Default::default(),
+			encoding: Arc::new(core::encoding::Json),
+		}
+	}
+
+	/// Token cancelled once the session is closed.
+	pub fn cancellation(&self) -> &core::CancellationToken {
+		&self.cancellation
+	}
+
+	/// Encoding of messages exchanged with the client.
+	pub fn encoding(&self) -> &Arc<dyn Encoding> {
+		&self.encoding
+	}
+
 	/// Get this session as a `Sink` spawning a new future
 	/// in the underlying event loop.
 	pub fn sender(&self) -> mpsc::UnboundedSender<String> {
 		let out = self.out.clone();
 		let (sender, receiver) = mpsc::unbounded();
-		self.executor.spawn(SenderFuture(out, Box::new(receiver)));
+		self.executor
+			.spawn(SenderFuture(out, self.encoding.clone(), Box::new(receiver)));
 		sender
 	}
 }
@@ -124,7 +157,22 @@ impl<M: core::Metadata + Default> MetaExtractor<M> for NoopExtractor {
 	}
 }
 
-struct SenderFuture(Sender, Box<dyn futures::Stream<Item = String> + Send + Unpin>);
+/// Creates a message of given encoding, text messages are sent in text frames.
+pub(crate) fn encoded_message(encoding: &dyn Encoding, message: Vec<u8>) -> ws::Message {
+	if encoding.is_text() {
+		String::from_utf8(message)
+			.map(ws::Message::Text)
+			.unwrap_or_else(|e| ws::Message::Binary(e.into_bytes()))
+	} else {
+		ws::Message::Binary(message)
+	}
+}
+
+struct SenderFuture(
+	Sender,
+	Arc<dyn Encoding>,
+	Box<dyn futures::Stream<Item = String> + Send + Unpin>,
+);
 
 impl Future for SenderFuture {
 	type Output = ();
@@ -134,10 +182,18 @@ impl Future for SenderFuture {
 
 		let this = Pin::into_inner(self);
 		loop {
-			match Pin::new(&mut this.1).poll_next(cx) {
+			match Pin::new(&mut this.2).poll_next(cx) {
 				Poll::Pending => return Poll::Pending,
 				Poll::Ready(None) => return Poll::Ready(()),
 				Poll::Ready(Some(val)) => {
+					// subscription updates are JSON text, re-encode them for the client
+					let val = match this.1.encode_json(&val) {
+						Ok(val) => encoded_message(&*this.1, val),
+						Err(_) => {
+							warn!("Dropping a subscription update which is not valid JSON: {}", val);
+							continue;
+						}
+					};
 					if let Err(e) = this.0.send(val) {
 						warn!("Error sending a subscription update: {:?}", e);
 						return Poll::Ready(());
@@ -4,6 +4,7 @@ use std::thread;
 use std::{cmp, fmt};
 
 use crate::core;
+use crate::core::encoding::Encoding;
 use crate::server_utils::cors::Origin;
 use crate::server_utils::hosts::{self, Host};
 use crate::server_utils::reactor::{Executor, UninitializedExecutor};
@@ -60,6 +61,7 @@ impl Server {
 		max_payload_bytes: usize,
 		max_in_buffer_capacity: usize,
 		max_out_buffer_capacity: usize,
+		encoding: Arc<dyn Encoding>,
 	) -> Result<Server>
 	where
 		S::Future: Unpin,
@@ -100,6 +102,7 @@ impl Server {
 			request_middleware,
 			stats,
 			executor,
+			encoding,
 		))?;
 		let broadcaster = ws.broadcaster();
 
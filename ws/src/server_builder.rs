@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use crate::core;
+use crate::core::encoding::{Encoding, Json};
 use crate::server_utils::cors::Origin;
 use crate::server_utils::hosts::{DomainsValidation, Host};
 use crate::server_utils::reactor::{self, UninitializedExecutor};
@@ -25,6 +26,7 @@ pub struct ServerBuilder<M: core::Metadata, S: core::Middleware<M>> {
 	max_payload_bytes: usize,
 	max_in_buffer_capacity: usize,
 	max_out_buffer_capacity: usize,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<M: core::Metadata + Default, S: core::Middleware<M>> ServerBuilder<M, S>
@@ -64,6 +66,7 @@ where
 			max_payload_bytes: 5 * 1024 * 1024,
 			max_in_buffer_capacity: 10 * 1024 * 1024,
 			max_out_buffer_capacity: 10 * 1024 * 1024,
+			encoding: Arc::new(Json),
 		}
 	}
 
@@ -132,6 +135,17 @@ where
 		self
 	}
 
+	/// Sets the encoding of requests and responses, JSON by default.
+	///
+	/// Messages of text encodings are sent in text frames, others in binary frames.
+	/// Requests are accepted in both kinds of frames, except for binary frames with text encodings:
+	/// the connection is closed with `Unsupported` status then.
+	/// Binary encodings are available with `msgpack` and `cbor` features.
+	pub fn encoding<E: Encoding>(mut self, encoding: E) -> Self {
+		self.encoding = Arc::new(encoding);
+		self
+	}
+
 	/// Starts a new `WebSocket` server in separate thread.
 	/// Returns a `Server` handle which closes the server when droped.
 	pub fn start(self, addr: &SocketAddr) -> Result<Server> {
@@ -148,6 +162,7 @@ where
 			self.max_payload_bytes,
 			self.max_in_buffer_capacity,
 			self.max_out_buffer_capacity,
+			self.encoding,
 		)
 	}
 }
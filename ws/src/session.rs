@@ -4,6 +4,7 @@ use std::sync::{atomic, Arc};
 use std::task::{Context, Poll};
 
 use crate::core;
+use crate::core::encoding::Encoding;
 use futures::channel::oneshot;
 use futures::future;
 use futures::FutureExt;
@@ -168,6 +169,8 @@ impl<M: core::Metadata, S: core::Middleware<M>> Drop for Session<M, S> {
 			stats.close_session(self.context.session_id)
 		}
 
+		self.context.cancellation.cancel();
+
 		// signal to all still-live tasks that the session has been dropped.
 		for (_index, task) in self.task_slab.lock().iter_mut() {
 			if let Some(task) = task.take() {
@@ -258,7 +261,18 @@ where
 	}
 
 	fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
-		let req = msg.as_text()?;
+		let encoding = self.context.encoding.clone();
+		if encoding.is_text() && msg.is_binary() {
+			warn!(
+				"Binary frame received, but messages are encoded as {}.",
+				encoding.content_type()
+			);
+			if let Err(e) = self.context.out.close(ws::CloseCode::Unsupported) {
+				warn!("Error while closing connection: {:?}", e);
+			}
+			return Ok(());
+		}
+		let req = msg.into_data();
 		let out = self.context.out.clone();
 		let metadata = self
 			.metadata
@@ -271,14 +285,14 @@ where
 		let poll_liveness = LivenessPoll::create(self.task_slab.clone());
 
 		let active_lock = self.active.clone();
-		let response = self.handler.handle_request(req, metadata);
+		let response = self.handler.handle_encoded_request(&req, encoding.clone(), metadata);
 
 		let future = response.map(move |response| {
 			if !active_lock.load(atomic::Ordering::SeqCst) {
 				return;
 			}
 			if let Some(result) = response {
-				let res = out.send(result);
+				let res = out.send(metadata::encoded_message(&*encoding, result));
 				match res {
 					Err(error::Error::ConnectionClosed) => {
 						active_lock.store(false, atomic::Ordering::SeqCst);
@@ -307,6 +321,7 @@ pub struct Factory<M: core::Metadata, S: core::Middleware<M>> {
 	request_middleware: Option<Arc<dyn RequestMiddleware>>,
 	stats: Option<Arc<dyn SessionStats>>,
 	executor: TaskExecutor,
+	encoding: Arc<dyn Encoding>,
 }
 
 impl<M: core::Metadata, S: core::Middleware<M>> Factory<M, S> {
@@ -318,6 +333,7 @@ impl<M: core::Metadata, S: core::Middleware<M>> Factory<M, S> {
 		request_middleware: Option<Arc<dyn RequestMiddleware>>,
 		stats: Option<Arc<dyn SessionStats>>,
 		executor: TaskExecutor,
+		encoding: Arc<dyn Encoding>,
 	) -> Self {
 		Factory {
 			session_id: 0,
@@ -328,6 +344,7 @@ impl<M: core::Metadata, S: core::Middleware<M>> Factory<M, S> {
 			request_middleware,
 			stats,
 			executor,
+			encoding,
 		}
 	}
 }
@@ -354,6 +371,8 @@ where
 				protocols: Vec::new(),
 				out: metadata::Sender::new(sender, active),
 				executor: self.executor.clone(),
+				cancellation: Default::default(),
+				encoding: self.encoding.clone(),
 			},
 			handler: self.handler.clone(),
 			meta_extractor: self.meta_extractor.clone(),
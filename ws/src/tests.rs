@@ -248,3 +248,74 @@ fn close_handle_makes_wait_return() {
 		.expect("Expected server to close");
 	assert!(result.is_ok());
 }
+
+#[test]
+fn should_close_connection_on_binary_frame_for_text_encoding() {
+	use crate::ws::{connect, CloseCode, Handler, Message};
+
+	struct Client(mpsc::Sender<CloseCode>);
+	impl Handler for Client {
+		fn on_close(&mut self, code: CloseCode, _reason: &str) {
+			self.0.send(code).unwrap();
+		}
+	}
+
+	// given
+	let server = ServerBuilder::new(core::IoHandler::default())
+		.start(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let (tx, rx) = mpsc::channel();
+
+	// when
+	connect(format!("ws://{}", server.addr()), |out| {
+		let request = r#"{"jsonrpc":"2.0", "method":"hello", "params": [], "id": 1}"#;
+		out.send(Message::Binary(request.as_bytes().to_vec())).unwrap();
+		Client(tx.clone())
+	})
+	.unwrap();
+
+	// then
+	assert_eq!(
+		rx.recv_timeout(Duration::from_secs(10)).unwrap(),
+		CloseCode::Unsupported
+	);
+}
+
+#[test]
+fn should_respond_in_binary_frames_for_binary_encoding() {
+	use crate::core::encoding::{Encoding, MessagePack};
+	use crate::ws::{connect, CloseCode, Message};
+
+	// given
+	let mut io = core::IoHandler::default();
+	io.add_sync_method("hello", |_params: core::Params| Ok(core::Value::String("world".into())));
+	let server = ServerBuilder::new(io)
+		.encoding(MessagePack)
+		.start(&"127.0.0.1:0".parse().unwrap())
+		.unwrap();
+	let (tx, rx) = mpsc::channel();
+
+	// when
+	connect(format!("ws://{}", server.addr()), |out| {
+		let request = MessagePack
+			.encode_json(r#"{"jsonrpc":"2.0", "method":"hello", "params": [], "id": 1}"#)
+			.unwrap();
+		out.send(Message::Binary(request)).unwrap();
+
+		let tx = tx.clone();
+		move |msg: Message| {
+			tx.send(msg).unwrap();
+			out.close(CloseCode::Normal)
+		}
+	})
+	.unwrap();
+
+	// then
+	match rx.recv_timeout(Duration::from_secs(10)).unwrap() {
+		Message::Binary(response) => assert_eq!(
+			MessagePack.decode_json(&response).unwrap(),
+			r#"{"id":1,"jsonrpc":"2.0","result":"world"}"#
+		),
+		msg => panic!("Unexpected message: {:?}", msg),
+	}
+}